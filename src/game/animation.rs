@@ -0,0 +1,139 @@
+use std::collections::HashMap;
+use std::hash::Hash;
+
+use game::graphics;
+use game::sprite;
+use game::units;
+
+/// Plays one clip out of several, keyed by the states an entity can be
+/// in. The entity asks for the state it's in every update; the animator
+/// switches clips (starting the new one over) when that changes, & when a
+/// clip which plays once finishes it moves on as `then` says.
+pub struct Animator<S> {
+	clips:       HashMap<S, Box<sprite::Updatable<units::Game>>>,
+	transitions: HashMap<S, S>,
+	// the state last asked for & the one being played, which differ once
+	// a transition has been taken
+	requested:   S,
+	current:     S,
+	done:        bool
+}
+
+impl<S: Hash + Eq + Copy> Animator<S> {
+	pub fn new(initial: S) -> Animator<S> {
+		Animator {
+			clips:       HashMap::new(),
+			transitions: HashMap::new(),
+			requested:   initial,
+			current:     initial,
+			done:        false
+		}
+	}
+
+	/// Plays `clip` while in `state`.
+	pub fn add_clip(&mut self, state: S, clip: Box<sprite::Updatable<units::Game>>) {
+		self.clips.insert(state, clip);
+	}
+
+	/// Moves on to `to` once the clip for `from` finishes.
+	pub fn then(&mut self, from: S, to: S) {
+		self.transitions.insert(from, to);
+	}
+
+	/// Switches to `state`'s clip, unless it was the state asked for last.
+	pub fn request(&mut self, state: S) {
+		if state != self.requested {
+			self.requested = state;
+			self.enter(state);
+		}
+	}
+
+	/// Forwards the elapsed time to the clip playing. Responds w/ its state
+	/// if it finished just now, after taking any transition out of it.
+	pub fn update(&mut self, elapsed_time: units::Millis) -> Option<S> {
+		let finished = match self.clips.get_mut(&self.current) {
+			Some(clip) => { clip.update(elapsed_time); clip.is_finished() },
+			None => { false }
+		};
+		if !finished || self.done {
+			return None;
+		}
+
+		let state = self.current;
+		self.done = true;
+		let next = self.transitions.get(&state).map(|to| *to);
+		match next {
+			Some(to) => { self.enter(to); },
+			None => {}
+		}
+		Some(state)
+	}
+
+	pub fn draw(&self, display: &mut graphics::Graphics, coords: (units::Game, units::Game)) {
+		match self.clips.get(&self.current) {
+			Some(clip) => { clip.draw(display, coords); },
+			None => {}
+		}
+	}
+
+	fn enter(&mut self, state: S) {
+		self.current = state;
+		self.done = false;
+		match self.clips.get_mut(&state) {
+			Some(clip) => { clip.restart(); },
+			None => {}
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::Animator;
+	use game::sprite::{AnimatedSprite, Playback, Updatable};
+	use game::units;
+
+	/// A clip of `frames` 100ms frames, played as `playback`.
+	fn clip(frames: u32, playback: Playback) -> Box<Updatable<units::Game>> {
+		let frames: Vec<_> = (0.. frames).map(|frame| ((units::Tile(frame), units::Tile(0)), units::Millis(100))).collect();
+		let sprite = AnimatedSprite::from_frames("sheet.bmp".to_string(), (units::Tile(1), units::Tile(1)), &frames, playback);
+		Box::new(sprite.ok().unwrap()) as Box<Updatable<units::Game>>
+	}
+
+	fn animator() -> Animator<&'static str> {
+		let mut animator = Animator::new("idle");
+		animator.add_clip("idle", clip(2, Playback::Loop));
+		animator.add_clip("hit", clip(2, Playback::Once));
+		animator.then("hit", "idle");
+		animator
+	}
+
+	#[test]
+	fn finished_clip_moves_on() {
+		let mut animator = animator();
+		animator.request("hit");
+		assert_eq!(animator.current, "hit");
+		assert_eq!(animator.update(units::Millis(100)), None);
+		assert_eq!(animator.update(units::Millis(100)), Some("hit"));
+		assert_eq!(animator.current, "idle");
+
+		// looping clips never finish
+		for _ in 0.. 10 {
+			assert_eq!(animator.update(units::Millis(100)), None);
+		}
+	}
+
+	#[test]
+	fn asking_for_the_same_state_doesnt_restart_it() {
+		let mut animator = animator();
+		animator.request("hit");
+		animator.update(units::Millis(100));
+		animator.request("hit");
+		assert_eq!(animator.update(units::Millis(100)), Some("hit"));
+
+		// but asking for it anew after something else does
+		animator.request("idle");
+		animator.request("hit");
+		assert_eq!(animator.current, "hit");
+		assert_eq!(animator.update(units::Millis(100)), None);
+	}
+}
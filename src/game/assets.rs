@@ -0,0 +1,217 @@
+use std::cell::Cell;
+use std::collections::HashMap;
+use std::rc::Rc;
+use std::string::String;
+use std::vec::Vec;
+
+use sdl2::pixels;
+use sdl2::render;
+use sdl2::surface;
+
+use game::packs;
+use game::units;
+
+// the size of the placeholder drawn in place of a missing image, which is
+// stretched over wherever the image would have been drawn
+static PLACEHOLDER_SIZE: u32 = 32;
+
+/// A texture held by `Textures`. Handles are cheap to clone; the texture
+/// is kept for as long as any of them is around.
+pub struct TextureHandle {
+	index: usize,
+	refs:  Rc<Cell<usize>>
+}
+
+impl Clone for TextureHandle {
+	fn clone(&self) -> TextureHandle {
+		self.refs.set(self.refs.get() + 1);
+		TextureHandle { index: self.index, refs: self.refs.clone() }
+	}
+}
+
+impl Drop for TextureHandle {
+	fn drop(&mut self) {
+		self.refs.set(self.refs.get() - 1);
+	}
+}
+
+impl PartialEq for TextureHandle {
+	fn eq(&self, other: &TextureHandle) -> bool {
+		self.index == other.index
+	}
+}
+
+impl Eq for TextureHandle {}
+
+// a loaded image & how many handles to it are out
+struct Slot {
+	path:        String,
+	texture:     render::Texture,
+	// how much bigger than the base art its tiles are, or none if it's
+	// a placeholder
+	scale:       Option<f64>,
+	refs:        Rc<Cell<usize>>
+}
+
+/// The images loaded from disk, each once however many handles to it
+/// there are, & freed by `free_unused()` once the last handle is dropped.
+pub struct Textures {
+	slots:       Vec<Option<Slot>>,
+	by_path:     HashMap<String, usize>,
+	placeholder: bool
+}
+
+impl Textures {
+	/// When `placeholder` is set, an image which can't be loaded is warned
+	/// about & drawn as a magenta box rather than being an error; meant
+	/// for development builds, where a missing file shouldn't stop the game.
+	pub fn new(placeholder: bool) -> Textures {
+		Textures { slots: Vec::new(), by_path: HashMap::new(), placeholder: placeholder }
+	}
+
+	/// A handle to the bitmap at `file_path`, loaded from the topmost pack
+	/// which has it unless a handle to it is already out.
+	///
+	/// When `transparent_black` is set, black pixels aren't drawn. Fails w/
+	/// a message naming the file if it's missing or unreadable, unless
+	/// placeholders are allowed.
+	pub fn load(&mut self, screen: &render::Renderer, file_path: &str, transparent_black: bool) -> Result<TextureHandle, String> {
+		match self.by_path.get(file_path) {
+			Some(&index) => { return Ok(self.handle(index)); },
+			None => {}
+		}
+
+		let scale = packs::tile_size(file_path) as f64 / units::TILE_SIZE as f64;
+		let (texture, scale) = match load_bmp(screen, file_path, transparent_black) {
+			Ok(texture) => { (texture, Some(scale)) },
+			Err(msg) => {
+				if !self.placeholder {
+					return Err(msg);
+				}
+				println!("warning: {}, drawing a placeholder", msg);
+				(try!(magenta(screen)), None)
+			}
+		};
+
+		Ok(self.insert(file_path, texture, scale))
+	}
+
+	/// A handle to a placeholder standing in for `file_path`, which is then
+	/// handed out by `load` in its place until it's freed. For images which
+	/// failed to load somewhere the error can't be passed on from.
+	pub fn placeholder(&mut self, screen: &render::Renderer, file_path: &str) -> Result<TextureHandle, String> {
+		let texture = try!(magenta(screen));
+		Ok(self.insert(file_path, texture, None))
+	}
+
+	/// The texture behind `handle`, & how much to scale rectangles on it
+	/// (measured in the base art's pixels) by to fit its own tile size.
+	/// A placeholder has no scale; it's stretched over the whole destination.
+	pub fn get(&self, handle: &TextureHandle) -> (&render::Texture, Option<f64>) {
+		let slot = self.slots[handle.index].as_ref().unwrap();
+		(&slot.texture, slot.scale)
+	}
+
+	/// Frees every texture no handle refers to any more. Responds w/ how
+	/// many were freed.
+	pub fn free_unused(&mut self) -> usize {
+		let mut freed = 0;
+		for slot in self.slots.iter_mut() {
+			let unused = match *slot {
+				Some(ref slot) => { slot.refs.get() == 0 },
+				None => { false }
+			};
+			if unused {
+				self.by_path.remove(&slot.as_ref().unwrap().path);
+				*slot = None;
+				freed += 1;
+			}
+		}
+
+		freed
+	}
+
+	fn insert(&mut self, file_path: &str, texture: render::Texture, scale: Option<f64>) -> TextureHandle {
+		let slot = Slot { path: file_path.to_string(), texture: texture, scale: scale, refs: Rc::new(Cell::new(0)) };
+		let free = self.slots.iter().position(|slot| slot.is_none());
+		let index = match free {
+			Some(index) => { self.slots[index] = Some(slot); index },
+			None => { self.slots.push(Some(slot)); self.slots.len() - 1 }
+		};
+		self.by_path.insert(file_path.to_string(), index);
+		self.handle(index)
+	}
+
+	fn handle(&self, index: usize) -> TextureHandle {
+		let refs = self.slots[index].as_ref().unwrap().refs.clone();
+		refs.set(refs.get() + 1);
+		TextureHandle { index: index, refs: refs }
+	}
+}
+
+fn load_bmp(screen: &render::Renderer, file_path: &str, transparent_black: bool) -> Result<render::Texture, String> {
+	let mut bitmap = match surface::Surface::load_bmp(&packs::locate(file_path)) {
+		Ok(bitmap) => { bitmap },
+		Err(msg) => { return Err(format!("could not load image {}: {}", file_path, msg)); }
+	};
+
+	if transparent_black {
+		match bitmap.set_color_key(true, pixels::Color::RGB(0,0,0)) {
+			Ok(_) => {},
+			Err(msg) => { return Err(format!("could not key image {}: {}", file_path, msg)); }
+		}
+	}
+
+	match screen.create_texture_from_surface(&bitmap) {
+		Ok(texture) => { Ok(texture) },
+		Err(msg) => { Err(format!("could not render image {}: {}", file_path, msg)) }
+	}
+}
+
+fn magenta(screen: &render::Renderer) -> Result<render::Texture, String> {
+	let mut fill = match surface::Surface::new(PLACEHOLDER_SIZE, PLACEHOLDER_SIZE, pixels::PixelFormatEnum::RGB888) {
+		Ok(fill) => { fill },
+		Err(msg) => { return Err(format!("could not make a placeholder: {}", msg)); }
+	};
+	match fill.fill_rect(None, pixels::Color::RGB(255, 0, 255)) {
+		Ok(_) => {},
+		Err(msg) => { return Err(format!("could not make a placeholder: {}", msg)); }
+	}
+
+	match screen.create_texture_from_surface(&fill) {
+		Ok(texture) => { Ok(texture) },
+		Err(msg) => { Err(format!("could not make a placeholder: {}", msg)) }
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use std::cell::Cell;
+	use std::rc::Rc;
+
+	use super::TextureHandle;
+
+	#[test]
+	fn handles_count_their_copies() {
+		let refs = Rc::new(Cell::new(1));
+		let handle = TextureHandle { index: 3, refs: refs.clone() };
+		{
+			let copy = handle.clone();
+			let other = copy.clone();
+			assert_eq!(refs.get(), 3);
+			assert!(copy == handle && other == handle);
+		}
+		assert_eq!(refs.get(), 1);
+
+		drop(handle);
+		// no handle is left, so `free_unused` would free the texture
+		assert_eq!(refs.get(), 0);
+	}
+
+	#[test]
+	fn handles_to_other_textures_differ() {
+		let refs = Rc::new(Cell::new(2));
+		let (first, second) = (TextureHandle { index: 0, refs: refs.clone() }, TextureHandle { index: 1, refs: refs.clone() });
+		assert!(first != second);
+	}
+}
@@ -1,6 +1,8 @@
+use std::cell::RefCell;
 use std::rc::Rc;
 
 use game;
+use game::assets;
 use game::graphics;
 use game::units;
 use game::units::{AsPixel};
@@ -12,15 +14,25 @@ use std::string;
 
 static BACKGROUND_SIZE: units::Tile = units::Tile(4);
 
-#[derive(Clone)]
 pub struct FixedBackdrop {
-	surface_id: String 
+	surface_id: String,
+	texture:    RefCell<Option<assets::TextureHandle>>
+}
+
+impl Clone for FixedBackdrop {
+	fn clone(&self) -> FixedBackdrop {
+		FixedBackdrop { surface_id: self.surface_id.clone(), texture: RefCell::new(self.texture.borrow().clone()) }
+	}
 }
 
 impl FixedBackdrop {
-	pub fn new(path: string::String, graphics: &mut graphics::Graphics) -> FixedBackdrop {
-		let asset = graphics.load_image(path.clone(), false);
-		FixedBackdrop { surface_id: path }
+	pub fn new(path: string::String) -> FixedBackdrop {
+		FixedBackdrop { surface_id: path, texture: RefCell::new(None) }
+	}
+
+	/// The image the backdrop is painted w/.
+	pub fn path(&self) -> &str {
+		&self.surface_id
 	}
 
 	/// Repeatedly paints the asset across the entire screen.
@@ -29,9 +41,16 @@ impl FixedBackdrop {
 	pub fn draw(&self, graphics: &mut graphics::Graphics) {
 		let (mut x, mut y) = (0i32,0i32);
 		let units::Pixel(tile_size) = BACKGROUND_SIZE.to_pixel();
+		if self.texture.borrow().is_none() {
+			*self.texture.borrow_mut() = graphics.image_or_placeholder(&self.surface_id, false);
+		}
+		let texture = match *self.texture.borrow() {
+			Some(ref texture) => { texture.clone() },
+			None => { return; }
+		};
 
-		while units::Pixel(x) < game::LEVEL_WIDTH.to_pixel() {
-			while units::Pixel(y) < game::LEVEL_HEIGHT.to_pixel() {
+		while units::Pixel(x) < game::SCREEN_WIDTH.to_pixel() {
+			while units::Pixel(y) < game::SCREEN_HEIGHT.to_pixel() {
 				let src  = match Rect::new(0, 0, tile_size as u32, tile_size as u32) {
 					Ok(rect) => { rect },
 					Err(msg) => { panic!(msg) }
@@ -41,7 +60,7 @@ impl FixedBackdrop {
 					Err(msg) => { panic!(msg) }
 				};
 
-				graphics.blit_surface(&self.surface_id, &src.unwrap(), &dest.unwrap());
+				graphics.blit_surface(&texture, &src.unwrap(), &dest.unwrap());
 				y+= tile_size;
 			}
 
@@ -0,0 +1,153 @@
+use game;
+use game::collisions::Rectangle;
+use game::units;
+use game::units::AsGame;
+
+// how long the camera takes to catch up w/ the player
+static FOLLOW_LAG: units::Millis = units::Millis(150);
+
+/// How the view keeps up w/ the player.
+#[derive(PartialEq,Eq,Clone,Copy)]
+pub enum CameraMode {
+	/// Eases toward the player, keeping them centered on screen.
+	Follow,
+	/// Flips between fixed screen-sized pages of the level.
+	Page
+}
+
+impl CameraMode {
+	pub fn name(&self) -> &'static str {
+		match *self {
+			CameraMode::Follow => "follow",
+			CameraMode::Page   => "page"
+		}
+	}
+
+	pub fn from_name(name: &str) -> Option<CameraMode> {
+		match name {
+			"follow" => Some(CameraMode::Follow),
+			"page"   => Some(CameraMode::Page),
+			_        => None
+		}
+	}
+}
+
+/// The part of the level which is visible, expressed as the map
+/// coordinates of the top-left corner of the screen.
+#[derive(Clone)]
+pub struct Camera {
+	x:      units::Game,
+	y:      units::Game,
+	width:  units::Game,
+	height: units::Game,
+	mode:   CameraMode
+}
+
+impl Camera {
+	/// Creates a camera looking at the top-left corner of a level
+	/// `width` by `height` tiles large.
+	pub fn new(mode: CameraMode, width: units::Tile, height: units::Tile) -> Camera {
+		Camera {
+			x:      units::Game(0.0),
+			y:      units::Game(0.0),
+			width:  width.to_game(),
+			height: height.to_game(),
+			mode:   mode
+		}
+	}
+
+	pub fn get_mode(&self) -> CameraMode {
+		self.mode
+	}
+
+	pub fn set_mode(&mut self, mode: CameraMode) {
+		self.mode = mode;
+	}
+
+	/// Moves the view toward the point (`target_x`, `target_y`) on the map.
+	///
+	/// In `Follow` mode the camera closes the distance over `FOLLOW_LAG`,
+	/// in `Page` mode it jumps to the page holding the target. The view
+	/// never leaves the level.
+	pub fn follow(&mut self, target_x: units::Game, target_y: units::Game, elapsed_time: units::Millis) {
+		match self.mode {
+			CameraMode::Follow => {
+				let (goal_x, goal_y) = self.centered_on(target_x, target_y);
+				let (units::Millis(elapsed), units::Millis(lag)) = (elapsed_time, FOLLOW_LAG);
+				let catch_up = units::Game((elapsed as f64 / lag as f64).min(1.0));
+				self.x = self.x + (goal_x - self.x) * catch_up;
+				self.y = self.y + (goal_y - self.y) * catch_up;
+			},
+			CameraMode::Page => { self.snap_to(target_x, target_y); }
+		}
+	}
+
+	/// Moves the view to (`target_x`, `target_y`) at once, e.g. when a level starts.
+	pub fn snap_to(&mut self, target_x: units::Game, target_y: units::Game) {
+		let (x, y) = match self.mode {
+			CameraMode::Follow => { self.centered_on(target_x, target_y) },
+			CameraMode::Page   => {
+				let (units::Game(x), units::Game(y)) = (target_x, target_y);
+				let (units::Game(width), units::Game(height)) = (game::SCREEN_WIDTH.to_game(), game::SCREEN_HEIGHT.to_game());
+				self.clamp(units::Game((x / width).floor() * width), units::Game((y / height).floor() * height))
+			}
+		};
+		self.x = x;
+		self.y = y;
+	}
+
+	/// Converts a position on the map to a position on screen.
+	pub fn to_screen(&self, map_x: units::Game, map_y: units::Game) -> (units::Game, units::Game) {
+		(map_x - self.x, map_y - self.y)
+	}
+
+	/// Responds true if a tile-sized sprite at (`map_x`, `map_y`) is at
+	/// least partly in view.
+	pub fn on_screen(&self, map_x: units::Game, map_y: units::Game) -> bool {
+		let lower_limit_x = self.x - units::Tile(1);
+		let upper_limit_x = self.x + game::SCREEN_WIDTH;
+		let lower_limit_y = self.y - units::Tile(1);
+		let upper_limit_y = self.y + game::SCREEN_HEIGHT;
+
+		map_x >= lower_limit_x && map_x <= upper_limit_x && map_y >= lower_limit_y && map_y <= upper_limit_y
+	}
+
+	/// The area of the map `on_screen` may respond true for, w/ room for
+	/// a tile-sized sprite on every side.
+	pub fn view_rectangle(&self) -> Rectangle {
+		Rectangle {
+			x:      self.x - units::Tile(1),
+			y:      self.y - units::Tile(1),
+			width:  game::SCREEN_WIDTH.to_game() + units::Tile(2),
+			height: game::SCREEN_HEIGHT.to_game() + units::Tile(2)
+		}
+	}
+
+	/// The rows & columns of tiles which are at least partly in view,
+	/// as half-open `(first, last)` ranges.
+	pub fn visible_tiles(&self) -> ((u32, u32), (u32, u32)) {
+		let (units::Game(x), units::Game(y)) = (self.x, self.y);
+		let units::Game(tile) = units::Tile(1).to_game();
+		let (units::Tile(screen_cols), units::Tile(screen_rows)) = (game::SCREEN_WIDTH, game::SCREEN_HEIGHT);
+		let (first_col, first_row) = ((x / tile).floor().max(0.0) as u32, (y / tile).floor().max(0.0) as u32);
+
+		((first_col, first_col + screen_cols + 1), (first_row, first_row + screen_rows + 1))
+	}
+
+	fn centered_on(&self, target_x: units::Game, target_y: units::Game) -> (units::Game, units::Game) {
+		self.clamp(target_x - game::SCREEN_WIDTH.to_game() / units::Game(2.0),
+		           target_y - game::SCREEN_HEIGHT.to_game() / units::Game(2.0))
+	}
+
+	/// Keeps the top-left corner of the view inside the level.
+	/// Levels smaller than the screen are centered on it instead.
+	fn clamp(&self, x: units::Game, y: units::Game) -> (units::Game, units::Game) {
+		let units::Game(max_x) = self.width - game::SCREEN_WIDTH;
+		let units::Game(max_y) = self.height - game::SCREEN_HEIGHT;
+		let (units::Game(x), units::Game(y)) = (x, y);
+
+		let x = if max_x < 0.0 { max_x / 2.0 } else { x.min(max_x).max(0.0) };
+		let y = if max_y < 0.0 { max_y / 2.0 } else { y.min(max_y).max(0.0) };
+		(units::Game(x), units::Game(y))
+	}
+}
@@ -2,35 +2,18 @@ use std::collections::HashMap;
 
 use game::collisions::Rectangle;
 use game::sprite;
+use game::camera;
 use game::graphics;
-use game::common;
 use game::common::Character;
 use game::vehicle::Vehicle;
 use game::vehicle::Part;
 
 use game::units;
 
-static SPRITE_NUM_FRAMES:   units::Frame  = 4;
-static SPRITE_FPS:          units::Fps    = 20;
-
-const COMPLETE_X_OFFSET: units::Tile = units::Tile(0);
-const SCRAPPED_X_OFFSET: units::Tile = units::Tile(0);
-const SCRAPPED_Y_OFFSET: units::Tile = units::Tile(2);
-const PERM_1_OFFSET:     units::Tile = units::Tile(6);
-const PERM_2_OFFSET:     units::Tile = units::Tile(18);
-const PERM_3_OFFSET:     units::Tile = units::Tile(18);
-const PERM_4_OFFSET:     units::Tile = units::Tile(12);
-const PERM_5_OFFSET:     units::Tile = units::Tile(12);
-const PERM_6_OFFSET:     units::Tile = units::Tile(6);
-const PERM_1_3_5_OFFSET: units::Tile = units::Tile(2);
-const PERM_2_4_6_OFFSET: units::Tile = units::Tile(0);
-const PARTS_Y_OFFSET:    units::Tile = units::Tile(4);
-const TIRE_OFFSET:       units::Tile = units::Tile(2);
-const DOOR_OFFSET:       units::Tile = units::Tile(0);
-const ENGINE_OFFSET:     units::Tile = units::Tile(4);
+// each part's name in the sprite manifest, by part type (from 1)
+static PART_NAMES: [&'static str; 3] = ["tire", "engine", "door"];
 
 static NUM_PARTS:                u32 = 3;
-static POSSIBLE_CONFIGS:         u32 = 8;
 
 pub struct Car {
 	pub x: units::Game,
@@ -38,7 +21,7 @@ pub struct Car {
 	pub map_x: units::Game, 
 	pub map_y: units::Game,
 
-	sprites:  HashMap<u32, Box<sprite::Updatable<units::Game>>>,
+	sprites:  HashMap<String, Box<sprite::Updatable<units::Game>>>,
 	parts:    HashMap<u32, u32>
 }
 
@@ -55,10 +38,9 @@ pub struct Door {
 }
 
 impl Car {
-	pub fn new(display: &mut graphics::Graphics,
-	           x: units::Game, y: units::Game) -> Car {
+	pub fn new(x: units::Game, y: units::Game) -> Car {
 		
-		let sprite_map = HashMap::<u32, Box<sprite::Updatable<_>>>::new();
+		let sprite_map = HashMap::<String, Box<sprite::Updatable<_>>>::new();
 		let part_map = HashMap::<u32, u32>::new();
 
 		let mut new_car = Car { 
@@ -69,136 +51,37 @@ impl Car {
 			parts: part_map
 		};
 
-		new_car.load_sprites(display);
+		new_car.load_sprites();
 
 		new_car
 	}
 
-	fn load_sprites(&mut self, 
-	               display: &mut graphics::Graphics) {
-
-		for i in 0.. POSSIBLE_CONFIGS {
-			match i {
-				0 => {
-					self.sprites.insert(0u32, {
-							let asset_path = "assets/base/racer.bmp".to_string();
-							let sprite_x = SCRAPPED_X_OFFSET;
-							let sprite_y = SCRAPPED_Y_OFFSET;
-
-							Box::new( sprite::Sprite::new(
-								display, 
-								(sprite_x, sprite_y), 
-								(units::Tile(6), units::Tile(2)),
-								asset_path
-							) ) as Box<sprite::Updatable<_>>
-						}
-					);
-				},
-				1 => {
-					self.sprites.insert(1u32, {
-							let asset_path = "assets/base/racer.bmp".to_string();
-							let sprite_x = PERM_1_OFFSET;
-							let sprite_y = PERM_1_3_5_OFFSET;
-
-							Box::new( sprite::Sprite::new(
-								display, 
-								(sprite_x, sprite_y), 
-								(units::Tile(6), units::Tile(2)),
-								asset_path
-							) ) as Box<sprite::Updatable<_>>
-						}
-					);
-				},
-				2 => {
-					self.sprites.insert(2u32, {
-							let asset_path = "assets/base/racer.bmp".to_string();
-							let sprite_x = PERM_2_OFFSET;
-							let sprite_y = PERM_2_4_6_OFFSET;
-
-							Box::new( sprite::Sprite::new(
-								display, 
-								(sprite_x, sprite_y), 
-								(units::Tile(6), units::Tile(2)),
-								asset_path
-							) ) as Box<sprite::Updatable<_>>
-						}
-					);
-				},
-				3 => {
-					self.sprites.insert(3u32, {
-							let asset_path = "assets/base/racer.bmp".to_string();
-							let sprite_x = PERM_3_OFFSET;
-							let sprite_y = PERM_1_3_5_OFFSET;
-
-							Box::new( sprite::Sprite::new(
-								display, 
-								(sprite_x, sprite_y), 
-								(units::Tile(6), units::Tile(2)),
-								asset_path
-							) ) as Box<sprite::Updatable<_>>
-						}
-					);
-				},
-				4 => {
-					self.sprites.insert(4u32, {
-							let asset_path = "assets/base/racer.bmp".to_string();
-							let sprite_x = PERM_4_OFFSET;
-							let sprite_y = PERM_2_4_6_OFFSET;
-
-							Box::new( sprite::Sprite::new(
-								display, 
-								(sprite_x, sprite_y), 
-								(units::Tile(6), units::Tile(2)),
-								asset_path
-							) ) as Box<sprite::Updatable<_>>
-						}
-					);
-				},
-				5 => {
-					self.sprites.insert(5u32, {
-							let asset_path = "assets/base/racer.bmp".to_string();
-							let sprite_x = PERM_5_OFFSET;
-							let sprite_y = PERM_1_3_5_OFFSET;
-
-							Box::new( sprite::Sprite::new(
-								display, 
-								(sprite_x, sprite_y), 
-								(units::Tile(6), units::Tile(2)),
-								asset_path
-							) ) as Box<sprite::Updatable<_>>
-						}
-					);
-				},
-				6 => {
-					self.sprites.insert(6u32, {
-							let asset_path = "assets/base/racer.bmp".to_string();
-							let sprite_x = PERM_6_OFFSET;
-							let sprite_y = PERM_2_4_6_OFFSET;
-
-							Box::new( sprite::Sprite::new(
-								display, 
-								(sprite_x, sprite_y), 
-								(units::Tile(6), units::Tile(2)),
-								asset_path
-							) ) as Box<sprite::Updatable<_>>
-						}
-					);
-				},
-				_ => {
-					self.sprites.insert(7u32, {
-							let asset_path = "assets/base/racer.bmp".to_string();
-							let sprite_x = COMPLETE_X_OFFSET;
-							let sprite_y = COMPLETE_X_OFFSET;
-
-							Box::new( sprite::Sprite::new(
-								display, 
-								(sprite_x, sprite_y), 
-								(units::Tile(6), units::Tile(2)),
-								asset_path
-							) ) as Box<sprite::Updatable<_>>
-						}
-					);
-				}
+	/// Loads a sprite for every set of parts the car can have on.
+	fn load_sprites(&mut self) {
+		let mut configs = vec![String::from("scrapped"), String::from("complete")];
+		for first in 0.. PART_NAMES.len() {
+			configs.push(format!("config.{}", PART_NAMES[first]));
+			for second in first + 1.. PART_NAMES.len() {
+				configs.push(format!("config.{}+{}", PART_NAMES[first], PART_NAMES[second]));
+			}
+		}
+
+		for config in configs.into_iter() {
+			let sprite = sprite::named(&format!("car.{}", config));
+			self.sprites.insert(config, sprite);
+		}
+	}
+
+	/// The sprite for the parts on so far: `scrapped`, `complete`, or
+	/// `config.` & the parts' names in order, joined by `+`.
+	fn config(&self) -> String {
+		let parts = self.parts();
+		match parts.len() {
+			0 => String::from("scrapped"),
+			n if n == NUM_PARTS as usize => String::from("complete"),
+			_ => {
+				let names: Vec<&str> = parts.iter().map(|part| PART_NAMES[*part as usize - 1]).collect();
+				format!("config.{}", names.join("+"))
 			}
 		}
 	}
@@ -214,7 +97,7 @@ impl Vehicle for Car {
 	}
 
 	fn update(&mut self, elapsed_time: units::Millis) {
-		let sprite_ref = self.sprites.get_mut(&7u32);
+		let sprite_ref = self.sprites.get_mut("complete");
 		sprite_ref.unwrap().update(elapsed_time);
 	}
 
@@ -222,30 +105,22 @@ impl Vehicle for Car {
 		self.x = self.x + units::Game(4.0);
 	}
 
-	fn draw(&self, display: &mut graphics::Graphics) {
-		let mut config = 0u32;
-		if self.parts.len() == NUM_PARTS as usize {
-			config = 7u32;
-		} else if self.parts.contains_key(&1u32) && self.parts.contains_key(&3u32) {
-			config = 2u32;
-		} else if self.parts.contains_key(&2u32) && self.parts.contains_key(&3u32) {
-			config = 4u32;
-		} else if self.parts.contains_key(&1u32) && self.parts.contains_key(&2u32) {
-			config = 6u32;
-		} else if self.parts.contains_key(&3u32) {
-			config = 5u32;
-		} else if self.parts.contains_key(&2u32) {
-			config = 3u32;
-		} else if self.parts.contains_key(&1u32) {
-			config = 1u32;
-		}
-		self.sprites.get(&config).unwrap().draw(display, (self.x, self.y));
+	fn draw(&self, display: &mut graphics::Graphics, camera: &camera::Camera) {
+		let config = self.config();
+		self.sprites.get(&config).unwrap().draw(display, camera.to_screen(self.x, self.y));
 	}
 
 	fn add_part(&mut self, part_num: u32) {
 		self.parts.insert(part_num, 1);
 	}
 
+	/// The parts added so far, in order of their type.
+	fn parts(&self) -> Vec<u32> {
+		let mut parts: Vec<u32> = self.parts.keys().cloned().collect();
+		parts.sort();
+		parts
+	}
+
 	fn is_built(&self) -> bool {
 		if self.parts.len() == NUM_PARTS as usize {
 			true
@@ -276,8 +151,7 @@ impl Vehicle for Car {
 }
 
 impl Tire {
-	pub fn new(display: &mut graphics::Graphics,
-	           x: units::Game, y: units::Game) -> Tire {
+	pub fn new(x: units::Game, y: units::Game) -> Tire {
 
 		let mut new_tire = Tire { 
 			character: Character::new(x, y)
@@ -285,44 +159,28 @@ impl Tire {
 
 		for motion in sprite::MOTIONS.iter() {
 			for facing in sprite::FACINGS.iter() {
-				new_tire.load_sprite(display, (*motion, *facing));
+				new_tire.load_sprite((*motion, *facing));
 			}
 		}
 
 		new_tire
 	}
 
-	pub fn load_sprite(&mut self, 
-	               display: &mut graphics::Graphics,
-	               movement: (sprite::Motion, sprite::Facing)) {
-
-		let asset_path = "assets/base/racer.bmp".to_string();
-		let motion_frame = TIRE_OFFSET;
-
-		let facing_frame = PARTS_Y_OFFSET;
-
-		let loaded_sprite = Box::new( sprite::Sprite::new(
-			display,
-			(motion_frame, facing_frame),
-			(units::Tile(2), units::Tile(1)),
-			asset_path
-		) ) as Box<sprite::Updatable<_>>;
-
-		self.character.sprites.insert(movement, loaded_sprite);
+	pub fn load_sprite(&mut self, movement: (sprite::Motion, sprite::Facing)) {
+		self.character.sprites.insert(movement, sprite::named("car.part.tire"));
 	}
 }
 
 impl Part for Tire {
     fn damage_rectangle(&self) -> Rectangle {
 		Rectangle {
-			x: self.character.x + units::Tile(1), y: self.character.y + units::HalfTile(1),
+			x: self.character.map_x + units::Tile(1), y: self.character.map_y + units::HalfTile(1),
 			width: units::Game(32.0), height: units::Game(16.0),
 		}
 	}
 
-	fn draw(&self, display: &mut graphics::Graphics) {
-		let correction_x = self.character.get_map_x() % common::SCREEN_CORRECTION;
-		let correction_y = self.character.get_map_y() % common::SCREEN_CORRECTION;
+	fn draw(&self, display: &mut graphics::Graphics, camera: &camera::Camera) {
+		let (correction_x, correction_y) = camera.to_screen(self.character.get_map_x(), self.character.get_map_y());
 		self.character.sprites.get(&self.character.movement).unwrap().draw(display, (correction_x, correction_y));
 	}
 
@@ -330,14 +188,6 @@ impl Part for Tire {
 		1
 	}
 
-    fn get_x(&self) -> units::Game {
-		self.character.center_x()
-	}
-
-	fn get_y(&self) -> units::Game {
-		self.character.center_y()
-	}
-
 	fn get_map_x(&self) -> units::Game {
 		self.character.map_center_x()
 	}
@@ -348,8 +198,7 @@ impl Part for Tire {
 }
 
 impl Engine {
-	pub fn new(display: &mut graphics::Graphics,
-	           x: units::Game, y: units::Game) -> Engine {
+	pub fn new(x: units::Game, y: units::Game) -> Engine {
 
 		let mut new_engine = Engine { 
 			character: Character::new(x, y)
@@ -357,44 +206,28 @@ impl Engine {
 
 		for motion in sprite::MOTIONS.iter() {
 			for facing in sprite::FACINGS.iter() {
-				new_engine.load_sprite(display, (*motion, *facing));
+				new_engine.load_sprite((*motion, *facing));
 			}
 		}
 
 		new_engine
 	}
 
-	pub fn load_sprite(&mut self, 
-	               display: &mut graphics::Graphics,
-	               movement: (sprite::Motion, sprite::Facing)) {
-
-		let asset_path = "assets/base/racer.bmp".to_string();
-		let motion_frame = ENGINE_OFFSET;
-
-		let facing_frame = PARTS_Y_OFFSET;
-
-		let loaded_sprite = Box::new( sprite::Sprite::new(
-			display,
-			(motion_frame, facing_frame),
-			(units::Tile(1), units::Tile(1)),
-			asset_path
-		) ) as Box<sprite::Updatable<_>>;
-
-		self.character.sprites.insert(movement, loaded_sprite);
+	pub fn load_sprite(&mut self, movement: (sprite::Motion, sprite::Facing)) {
+		self.character.sprites.insert(movement, sprite::named("car.part.engine"));
 	}
 }
 
 impl Part for Engine {
 	fn damage_rectangle(&self) -> Rectangle {
 		Rectangle {
-			x: self.character.x + units::HalfTile(1), y: self.character.y + units::HalfTile(1),
+			x: self.character.map_x + units::HalfTile(1), y: self.character.map_y + units::HalfTile(1),
 			width: units::Game(16.0), height: units::Game(16.0),
 		}
 	}
 
-	fn draw(&self, display: &mut graphics::Graphics) {
-		let correction_x = self.character.get_map_x() % common::SCREEN_CORRECTION;
-		let correction_y = self.character.get_map_y() % common::SCREEN_CORRECTION;
+	fn draw(&self, display: &mut graphics::Graphics, camera: &camera::Camera) {
+		let (correction_x, correction_y) = camera.to_screen(self.character.get_map_x(), self.character.get_map_y());
 		self.character.sprites.get(&self.character.movement).unwrap().draw(display, (correction_x, correction_y));
 	}
 
@@ -402,14 +235,6 @@ impl Part for Engine {
 		2
 	}
 
-	fn get_x(&self) -> units::Game {
-		self.character.center_x()
-	}
-
-	fn get_y(&self) -> units::Game {
-		self.character.center_y()
-	}
-
 	fn get_map_x(&self) -> units::Game {
 		self.character.map_center_x()
 	}
@@ -420,8 +245,7 @@ impl Part for Engine {
 }
 
 impl Door {
-	pub fn new(display: &mut graphics::Graphics,
-	           x: units::Game, y: units::Game) -> Door {
+	pub fn new(x: units::Game, y: units::Game) -> Door {
 
 		let mut new_door = Door { 
 			character: Character::new(x, y)
@@ -429,44 +253,28 @@ impl Door {
 
 		for motion in sprite::MOTIONS.iter() {
 			for facing in sprite::FACINGS.iter() {
-				new_door.load_sprite(display, (*motion, *facing));
+				new_door.load_sprite((*motion, *facing));
 			}
 		}
 
 		new_door
 	}
 
-	pub fn load_sprite(&mut self, 
-	               display: &mut graphics::Graphics,
-	               movement: (sprite::Motion, sprite::Facing)) {
-
-		let asset_path = "assets/base/racer.bmp".to_string();
-		let motion_frame = DOOR_OFFSET;
-
-		let facing_frame = PARTS_Y_OFFSET;
-
-		let loaded_sprite = Box::new( sprite::Sprite::new(
-			display,
-			(motion_frame, facing_frame),
-			(units::Tile(2), units::Tile(2)),
-			asset_path
-		) ) as Box<sprite::Updatable<_>>;
-
-		self.character.sprites.insert(movement, loaded_sprite);
+	pub fn load_sprite(&mut self, movement: (sprite::Motion, sprite::Facing)) {
+		self.character.sprites.insert(movement, sprite::named("car.part.door"));
 	}
 }
 
 impl Part for Door {
 	fn damage_rectangle(&self) -> Rectangle {
 		Rectangle {
-			x: self.character.x + units::Tile(1), y: self.character.y + units::Tile(1),
+			x: self.character.map_x + units::Tile(1), y: self.character.map_y + units::Tile(1),
 			width: units::Game(32.0), height: units::Game(16.0),
 		}
 	}
 
-	fn draw(&self, display: &mut graphics::Graphics) {
-		let correction_x = self.character.get_map_x() % common::SCREEN_CORRECTION;
-		let correction_y = self.character.get_map_y() % common::SCREEN_CORRECTION;
+	fn draw(&self, display: &mut graphics::Graphics, camera: &camera::Camera) {
+		let (correction_x, correction_y) = camera.to_screen(self.character.get_map_x(), self.character.get_map_y());
 		self.character.sprites.get(&self.character.movement).unwrap().draw(display, (correction_x, correction_y));
 	}
 
@@ -474,14 +282,6 @@ impl Part for Door {
 		3
 	}
 
-	fn get_x(&self) -> units::Game {
-		self.character.center_x()
-	}
-
-	fn get_y(&self) -> units::Game {
-		self.character.center_y()
-	}
-
 	fn get_map_x(&self) -> units::Game {
 		self.character.map_center_x()
 	}
@@ -1,24 +1,21 @@
-use rand;
 use rand::Rng;
 
+use std::cmp;
 use std::collections::HashMap;
 
+use game::camera;
 use game::graphics;
 use game::sprite;
 
 use game::collisions::{Info,Rectangle};
 use game::map;
+use game::random;
 
 use game::units;
-use game::units::AsGame;
+use game::units::{AsGame,AsTile};
 
 pub type MotionTup = (sprite::Motion, sprite::Facing);
 
-static SPRITE_NUM_FRAMES:  units::Frame  = 3;
-static SPRITE_FPS:         units::Fps    = 20;
-
-static KILL_FRAME:  units::Tile = units::Tile(0);
-
 // collision detection boxes
 // (expressed as `units::Game`)
 static X_BOX: Rectangle = Rectangle {
@@ -30,17 +27,22 @@ static Y_BOX: Rectangle = Rectangle {
 	width: units::Game(12.0), height: units::Game(30.0)
 };
 
-// Screen correction for changing pages
-pub const SCREEN_CORRECTION: units::Tile = units::Tile(20);
+/// Where a character is & where it is headed, as kept by a saved game.
+#[derive(Clone,Copy)]
+pub struct CharacterState {
+	pub map_x:      units::Game,
+	pub map_y:      units::Game,
+	pub velocity_x: units::Velocity,
+	pub velocity_y: units::Velocity,
+	pub target_x:   units::Game,
+	pub target_y:   units::Game
+}
 
 pub struct Character {
 	// assets
 	pub sprites:   HashMap<MotionTup, Box<sprite::Updatable<units::Game>>>,
 	pub killed_sprite: Vec<Box<sprite::Updatable<units::Game>>>, 
 
-	// positioning on screen
-	pub x: units::Game, 
-	pub y: units::Game,
 	// positioning on map
 	pub map_x: units::Game,
 	pub map_y: units::Game,
@@ -54,6 +56,11 @@ pub struct Character {
 	pub accel_y:       i32, 
 	pub target_x:      units::Game, 
 	pub target_y:      units::Game,
+	speed_factor:      f64,
+
+	// pathfinding, as `(col, row)` tiles
+	path:              Vec<(units::Tile, units::Tile)>,
+	path_goal:         Option<(units::Tile, units::Tile)>,
 
 	// flags
 	pub killed: i32
@@ -76,8 +83,6 @@ impl Character {
 			sprites:   sprite_map,
 			killed_sprite: killed_vec,
 
-			x: x,
-			y: y,
 			map_x: x,
 			map_y: y,
 			movement: (sprite::Motion::Standing, sprite::Facing::East),
@@ -88,6 +93,10 @@ impl Character {
 			accel_y: 0, 
 			target_x: x, 
 			target_y: y, 
+			speed_factor: 1.0,
+
+			path: Vec::new(),
+			path_goal: None,
 
 			killed: -1
 		};
@@ -95,27 +104,43 @@ impl Character {
 		new_character
 	}
 
+	pub fn state(&self) -> CharacterState {
+		CharacterState {
+			map_x:      self.map_x,
+			map_y:      self.map_y,
+			velocity_x: self.velocity_x,
+			velocity_y: self.velocity_y,
+			target_x:   self.target_x,
+			target_y:   self.target_y
+		}
+	}
+
+	/// Puts the character back where `state` left it. Any path it was
+	/// following is planned again on its next step.
+	pub fn restore_state(&mut self, state: &CharacterState) {
+		self.map_x = state.map_x;
+		self.map_y = state.map_y;
+		self.velocity_x = state.velocity_x;
+		self.velocity_y = state.velocity_y;
+		self.target_x = state.target_x;
+		self.target_y = state.target_y;
+		self.path.clear();
+		self.path_goal = None;
+	}
+
 	// Draws player to screen
-	pub fn draw(&self, display: &mut graphics::Graphics) {
+	pub fn draw(&self, display: &mut graphics::Graphics, camera: &camera::Camera) {
+		let screen = camera.to_screen(self.map_x, self.map_y);
 		if self.killed >= 0 {
-			self.killed_sprite.get(0).unwrap().draw(display, (self.x, self.y));
+			self.killed_sprite.get(0).unwrap().draw(display, screen);
 		} else {
-			self.sprites.get(&self.movement).unwrap().draw(display, (self.x, self.y));
+			self.sprites.get(&self.movement).unwrap().draw(display, screen);
 		}
 	}
 
-	pub fn load_killed_sprite(&mut self, display: &mut graphics::Graphics)
+	pub fn load_killed_sprite(&mut self)
 	{
-		let asset_path = "assets/base/killed.bmp".to_string();
-		let motion_frame = KILL_FRAME; 
-		let facing_frame = units::Tile(0);
-		let sprite = Box::new( sprite::AnimatedSprite::new(
-				display, asset_path, 
-				(motion_frame, facing_frame),
-				(units::Tile(1), units::Tile(1)),
-				SPRITE_NUM_FRAMES, SPRITE_FPS
-			).unwrap() ) as Box<sprite::Updatable<_>>;
-		self.killed_sprite.push(sprite);
+		self.killed_sprite.push(sprite::named("killed"));
 	}
 
 	pub fn current_motion(&mut self) {
@@ -142,7 +167,25 @@ impl Character {
 		self.movement = (last_action, direction);
 	}
 
+	/// Caps the character's speed to `factor` of its usual maximum until
+	/// `clear_slow_down()`. The strongest slow down applied wins.
+	pub fn slow_down(&mut self, factor: f64) {
+		self.speed_factor = self.speed_factor.min(factor);
+	}
+
+	pub fn clear_slow_down(&mut self) {
+		self.speed_factor = 1.0;
+	}
+
+	/// `max_velocity` scaled down by any slow down in effect.
+	fn slowed(&self, max_velocity: units::Velocity) -> units::Velocity {
+		let units::Velocity(max_velocity) = max_velocity;
+		units::Velocity(max_velocity * self.speed_factor)
+	}
+
 	pub fn update_x(&mut self, map: &map::Map, acceleration: units::Acceleration, max_velocity: units::Velocity) {
+		let max_velocity = self.slowed(max_velocity);
+
 		// compute next velocity
 		let accel_x: units::Acceleration = if self.accel_x < 0  {
 			-acceleration
@@ -157,54 +200,49 @@ impl Character {
 		} else if self.accel_x > 0 {
 			self.velocity_x = units::min(self.velocity_x, max_velocity);
 		} 
+		if self.speed_factor < 1.0 { // coasting doesn't get anyone out of a slow down
+			self.velocity_x = units::max(units::min(self.velocity_x, max_velocity), -max_velocity);
+		}
 
 		// x-axis collision checking 
 		let delta = self.velocity_x * self.elapsed_time;
 		if delta > units::Game(0.0) { // moving right
 			// collisions right-side
 			let mut info = self.get_collision_info(&self.right_collision(delta), map);
-			self.x = if info.collided {
+			if info.collided {
 				self.velocity_x = units::Velocity(0.0);
 				self.map_x = info.col.to_game() - X_BOX.right();
-				(info.col.to_game() - X_BOX.right()) % SCREEN_CORRECTION.to_game()
 			} else {
 				self.map_x = self.map_x + delta;
-				self.map_x % SCREEN_CORRECTION.to_game()
-			};
+			}
 
 			// collisions left-side
 			info = self.get_collision_info(&self.left_collision(units::Game(0.0)), map);
-			self.x = if info.collided {
+			if info.collided {
 				self.map_x = info.col.to_game() + X_BOX.right();
-				(info.col.to_game() + X_BOX.right()) % SCREEN_CORRECTION.to_game()
-			} else {
-				self.map_x % SCREEN_CORRECTION.to_game()
-			};
+			}
 
 		} else { // moving left
 			// collisions left-side
 			let mut info = self.get_collision_info(&self.left_collision(delta), map);
-			self.x = if info.collided {
+			if info.collided {
 				self.velocity_x = units::Velocity(0.0);
 				self.map_x = info.col.to_game() + X_BOX.right();
-				(info.col.to_game() + X_BOX.right()) % SCREEN_CORRECTION.to_game()
 			} else {
 				self.map_x = self.map_x + delta;
-				self.map_x % SCREEN_CORRECTION.to_game()
-			};
+			}
 
 			// collisions right-side
 			info = self.get_collision_info(&self.right_collision(units::Game(0.0)), map);
-			self.x = if info.collided {
+			if info.collided {
 				self.map_x = info.col.to_game() - X_BOX.right();
-				(info.col.to_game() - X_BOX.right()) % SCREEN_CORRECTION.to_game()
-			} else {
-				self.map_x % SCREEN_CORRECTION.to_game()
-			};
+			}
 		}
 	}
 
 	pub fn update_y (&mut self, map: &map::Map, acceleration: units::Acceleration, max_velocity: units::Velocity) {
+		let max_velocity = self.slowed(max_velocity);
+
 		// compute next velocity
 		let accel_y: units::Acceleration = if self.accel_y < 0  {
 			-acceleration 
@@ -219,6 +257,9 @@ impl Character {
 		} else if self.accel_y > 0 {
 			self.velocity_y = units::min(self.velocity_y, max_velocity);
 		} 
+		if self.speed_factor < 1.0 { // coasting doesn't get anyone out of a slow down
+			self.velocity_y = units::max(units::min(self.velocity_y, max_velocity), -max_velocity);
+		}
 
 		// calculate delta
 		let delta = self.velocity_y * self.elapsed_time;
@@ -227,42 +268,32 @@ impl Character {
 		if delta > units::Game(0.0) { // moving up
 			// react to collision
 			let mut info = self.get_collision_info(&self.bottom_collision(delta), map);
-			self.y = if info.collided {
+			if info.collided {
 				self.velocity_y = units::Velocity(0.0);
 				self.map_y = info.row.to_game() - Y_BOX.bottom();
-				(info.row.to_game() - Y_BOX.bottom()) % SCREEN_CORRECTION.to_game()
 			} else {
 				self.map_y = self.map_y + delta;
-				self.map_y % SCREEN_CORRECTION.to_game()
-			};
+			}
 
 			info = self.get_collision_info(&self.top_collision(units::Game(0.0)), map);
-			self.y = if info.collided {
+			if info.collided {
 				self.map_y = info.row.to_game() + Y_BOX.height();
-				(info.row.to_game() + Y_BOX.height()) % SCREEN_CORRECTION.to_game()
-			} else {
-				self.map_y % SCREEN_CORRECTION.to_game()
-			};
+			}
 
 		} else { // moving down
 			// react to collision
 			let mut info = self.get_collision_info(&self.top_collision(delta), map);
-			self.y = if info.collided {
+			if info.collided {
 				self.velocity_y = units::Velocity(0.0);
 				self.map_y = info.row.to_game() + Y_BOX.height();
-				(info.row.to_game() + Y_BOX.height()) % SCREEN_CORRECTION.to_game()
 			} else {
 				self.map_y = self.map_y + delta;
-				self.map_y % SCREEN_CORRECTION.to_game()
-			};
+			}
 
 			info = self.get_collision_info(&self.bottom_collision(units::Game(0.0)), map);
-			self.y = if info.collided {
+			if info.collided {
 				self.map_y = info.row.to_game() - Y_BOX.bottom();
-				(info.row.to_game() - Y_BOX.bottom()) % SCREEN_CORRECTION.to_game()
-			} else {
-				self.map_y % SCREEN_CORRECTION.to_game()
-			};
+			}
 		}
 	}
 
@@ -290,22 +321,6 @@ impl Character {
 		}
 	}
 
-	pub fn center_x(&self) -> units::Game {
-		self.x + units::HalfTile(1)
-	}
-
-	pub fn center_y(&self) -> units::Game {
-		self.y + units::HalfTile(1)
-	}
-
-	pub fn get_x(&self) -> units::Game {
-		self.x
-	}
-
-	pub fn get_y(&self) -> units::Game {
-		self.y
-	}
-
 	pub fn map_center_x(&self) -> units::Game {
 		self.map_x + units::HalfTile(1)
 	}
@@ -376,34 +391,81 @@ impl Character {
 		(xs + ys).sqrt()
 	}
 
-	pub fn set_new_target(&mut self) {
-		let mut rng = rand::thread_rng();
+	/// Steers toward (`goal_x`, `goal_y`) along the shortest path around
+	/// the walls of `map`.
+	///
+	/// The path is planned again whenever the goal moves to another tile
+	/// or the character strays off it. Once on the goal's tile, or if the
+	/// goal can't be reached, the character heads straight for the goal.
+	pub fn follow_path(&mut self, map: &map::Map, goal_x: units::Game, goal_y: units::Game) {
+		let here = (self.map_center_x().to_tile(), self.map_center_y().to_tile());
+		let goal = (goal_x.to_tile(), goal_y.to_tile());
+
+		// drop the waypoints which have been reached
+		let reached = self.path.iter().position(|waypoint| *waypoint == here);
+		match reached {
+			Some(reached) => { self.path.drain(0.. reached + 1); },
+			None => {}
+		}
+
+		let strayed = match self.path.first() {
+			Some(&(units::Tile(col), units::Tile(row))) => {
+				let (units::Tile(here_col), units::Tile(here_row)) = here;
+				cmp::max(col, here_col) - cmp::min(col, here_col) > 1 || cmp::max(row, here_row) - cmp::min(row, here_row) > 1
+			},
+			None => { false }
+		};
+		if strayed || self.path_goal != Some(goal) {
+			self.path = map.find_path(here, goal).unwrap_or(Vec::new());
+			self.path_goal = Some(goal);
+		}
+
+		let (waypoint_x, waypoint_y) = match self.path.first() {
+			Some(&(col, row)) => { (col.to_game() + units::HalfTile(1), row.to_game() + units::HalfTile(1)) },
+			None => { (goal_x, goal_y) }
+		};
+		self.accel_x = match self.map_center_x() {
+			ref center if center < &waypoint_x => 1,
+			ref center if center > &waypoint_x => -1,
+			_                                  => 0
+		};
+		self.accel_y = match self.map_center_y() {
+			ref center if center < &waypoint_y => 1,
+			ref center if center > &waypoint_y => -1,
+			_                                  => 0
+		};
+	}
+
+	/// Picks a nearby point to wander to once the current one is reached,
+	/// turning back before the edges of `map`.
+	pub fn set_new_target(&mut self, map: &map::Map, rng: &mut random::GameRng) {
 		let distance_to_target = self.distance( self.target_x, self.target_y );
 
 		if distance_to_target < 20.0 {
+			let (units::Tile(cols), units::Tile(rows)) = (map.width(), map.height());
 			let chance_x = rng.gen_range(1u32, 3u32);
 			let chance_y = rng.gen_range(1u32, 3u32);
 			let plus_or_minus = rng.gen_range(1u32, 3u32);
 			let center_x = self.map_center_x();
 			let center_y = self.map_center_y();
 			if plus_or_minus == 1 {
-				if center_x > units::Tile(1).to_game() && center_x < units::Tile(56).to_game() {
+				if center_x > units::Tile(1).to_game() && center_x < units::Tile(cols.saturating_sub(4)).to_game() {
 					self.target_x = center_x + units::Tile(chance_x).to_game()
 				} else {
 					self.target_x = center_x - units::Tile(chance_x).to_game()
 				}
-				if center_y > units::Tile(1).to_game() && center_y < units::Tile(56).to_game() {
+				if center_y > units::Tile(1).to_game() && center_y < units::Tile(rows.saturating_sub(4)).to_game() {
 					self.target_y = center_y + units::Tile(chance_y).to_game()
 				} else {
 					self.target_y = center_y - units::Tile(chance_y).to_game()
 				}
 			} else {
-				if center_x > units::Tile(3).to_game() && center_x < units::Tile(58).to_game() {
+				if center_x > units::Tile(3).to_game() && center_x < units::Tile(cols.saturating_sub(2)).to_game() {
 					self.target_x = center_x - units::Tile(chance_x).to_game()
 				} else {
 					self.target_x = center_x + units::Tile(chance_x).to_game()
 				}
-				if center_y > units::Tile(3).to_game() && center_y < units::Tile(58).to_game() {
+				if center_y > units::Tile(3).to_game() && center_y < units::Tile(rows.saturating_sub(2)).to_game() {
 					self.target_y = center_y - units::Tile(chance_y).to_game()
 				} else {
 					self.target_y = center_y + units::Tile(chance_y).to_game()
@@ -412,13 +474,14 @@ impl Character {
 		}
 	}
 
-	pub fn set_new_random_target(&mut self) {
-		let mut rng = rand::thread_rng();
+	/// Picks any point inside the border of `map` once the current one is reached.
+	pub fn set_new_random_target(&mut self, map: &map::Map, rng: &mut random::GameRng) {
 		let distance_to_target = self.distance( self.target_x, self.target_y );
 
 		if distance_to_target < 20.0 {
-			self.target_x = units::Tile(rng.gen_range(1u32, 58u32)).to_game();
-			self.target_y = units::Tile(rng.gen_range(1u32, 58u32)).to_game();
+			let (units::Tile(cols), units::Tile(rows)) = (map.width(), map.height());
+			self.target_x = units::Tile(rng.gen_range(1u32, cmp::max(2, cols.saturating_sub(2)))).to_game();
+			self.target_y = units::Tile(rng.gen_range(1u32, cmp::max(2, rows.saturating_sub(2)))).to_game();
 		}
 	}
 
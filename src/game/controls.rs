@@ -0,0 +1,253 @@
+use std::fs;
+use std::fs::File;
+use std::io::{BufRead, BufReader};
+use std::path::{Path, PathBuf};
+use std::string::String;
+use std::vec::Vec;
+
+use sdl2::keyboard::Keycode;
+
+use game::storage;
+
+static CONTROLS_HEADER: &'static str = "just-run controls 1";
+static CONTROLS_FILE:   &'static str = "controls.txt";
+
+/// Something the player can do, whichever keys it's bound to.
+#[derive(PartialEq,Eq,Clone,Copy,Debug)]
+pub enum Action {
+	MoveLeft,
+	MoveRight,
+	MoveUp,
+	MoveDown,
+	Pause,
+	Quit,
+	ToggleControlMode,
+	UseItem,
+	Save,
+	Resume,
+	Options
+}
+
+/// Every action, in the order the controls screen lists them.
+pub static ACTIONS: [Action; 11] = [
+	Action::MoveLeft, Action::MoveRight, Action::MoveUp, Action::MoveDown,
+	Action::Pause, Action::Quit, Action::ToggleControlMode, Action::UseItem,
+	Action::Save, Action::Resume, Action::Options
+];
+
+impl Action {
+	pub fn name(&self) -> &'static str {
+		match *self {
+			Action::MoveLeft          => "move_left",
+			Action::MoveRight         => "move_right",
+			Action::MoveUp            => "move_up",
+			Action::MoveDown          => "move_down",
+			Action::Pause             => "pause",
+			Action::Quit              => "quit",
+			Action::ToggleControlMode => "toggle_control_mode",
+			Action::UseItem           => "use_item",
+			Action::Save              => "save",
+			Action::Resume            => "resume",
+			Action::Options           => "options"
+		}
+	}
+
+	pub fn from_name(name: &str) -> Option<Action> {
+		ACTIONS.iter().find(|action| action.name() == name).map(|action| *action)
+	}
+
+	/// The keys an action is bound to out of the box.
+	fn default_keys(&self) -> Vec<Keycode> {
+		match *self {
+			Action::MoveLeft          => vec![Keycode::Left],
+			Action::MoveRight         => vec![Keycode::Right],
+			Action::MoveUp            => vec![Keycode::Up],
+			Action::MoveDown          => vec![Keycode::Down],
+			Action::Pause             => vec![Keycode::Return],
+			Action::Quit              => vec![Keycode::Escape],
+			Action::ToggleControlMode => vec![Keycode::LShift],
+			Action::UseItem           => vec![Keycode::Space],
+			Action::Save              => vec![Keycode::S],
+			Action::Resume            => vec![Keycode::R],
+			Action::Options           => vec![Keycode::O]
+		}
+	}
+}
+
+/// Which keys set off which actions. An action may have several keys &
+/// a key may set off several actions.
+///
+/// Bindings are kept in the data directory (see `storage::data_dir()`)
+/// as plain text:
+///
+///- `just-run controls 1`
+///- one `<action> <key> [<key>...]` line per action, w/ keys named as by
+///  `key_name()`; actions which are left out keep their default keys
+#[derive(Clone)]
+pub struct Bindings {
+	keys: Vec<(Action, Vec<Keycode>)>,
+	path: PathBuf
+}
+
+impl Bindings {
+	/// The default bindings.
+	pub fn new() -> Bindings {
+		Bindings {
+			keys: ACTIONS.iter().map(|action| (*action, action.default_keys())).collect(),
+			path: storage::data_dir().join(CONTROLS_FILE)
+		}
+	}
+
+	/// Reads the bindings from the data directory. A missing file leaves
+	/// every action on its default keys.
+	pub fn load() -> Bindings {
+		let bindings = Bindings::new();
+		if fs::metadata(&bindings.path).is_err() {
+			return bindings;
+		}
+
+		let path = bindings.path.clone();
+		Bindings::from_file(&path)
+	}
+
+	/// Reads a bindings file. A bad line is reported & its action keeps
+	/// its default keys, so a broken file never leaves the game unplayable.
+	pub fn from_file(file_path: &Path) -> Bindings {
+		let mut bindings = Bindings::new();
+		bindings.path = file_path.to_path_buf();
+		let file = match File::open(file_path) {
+			Ok(file) => { file },
+			Err(msg) => {
+				println!("could not open controls {}: {}", file_path.display(), msg);
+				return bindings;
+			}
+		};
+
+		for (index, line) in BufReader::new(file).lines().enumerate() {
+			let line_num = index + 1;
+			let line = match line {
+				Ok(line) => { line },
+				Err(msg) => { println!("{}:{}: {}", file_path.display(), line_num, msg); break; }
+			};
+
+			if line_num == 1 {
+				if line.trim() != CONTROLS_HEADER {
+					println!("{}:{}: not a controls file, using the default keys", file_path.display(), line_num);
+					return bindings;
+				}
+				continue;
+			}
+
+			let fields: Vec<&str> = line.split_whitespace().collect();
+			if fields.len() == 0 {
+				continue;
+			}
+			let action = match Action::from_name(fields[0]) {
+				Some(action) => { action },
+				None => { println!("{}:{}: unknown action `{}`", file_path.display(), line_num, fields[0]); continue; }
+			};
+			let keys: Vec<Option<Keycode>> = fields[1..].iter().map(|name| key_from_name(name)).collect();
+			if keys.len() == 0 || keys.iter().any(|key| key.is_none()) {
+				println!("{}:{}: `{}` needs one or more known keys, keeping its defaults", file_path.display(), line_num, fields[0]);
+				continue;
+			}
+
+			bindings.bind(action, keys.into_iter().filter_map(|key| key).collect());
+		}
+
+		bindings
+	}
+
+	/// Writes the bindings back to their file, atomically.
+	pub fn save(&self) -> Result<(), String> {
+		let mut contents = String::new();
+		contents.push_str(CONTROLS_HEADER);
+		contents.push_str("\n");
+		for &(action, ref keys) in self.keys.iter() {
+			let names: Vec<String> = keys.iter().map(|key| key_name(*key)).collect();
+			contents.push_str(&format!("{} {}\n", action.name(), names.join(" ")));
+		}
+
+		storage::write_atomically(&self.path, &contents)
+	}
+
+	/// The keys bound to `action`.
+	pub fn keys(&self, action: Action) -> &[Keycode] {
+		match self.keys.iter().find(|binding| binding.0 == action) {
+			Some(binding) => { &binding.1 },
+			None => { &[] }
+		}
+	}
+
+	/// Replaces every key bound to `action` w/ `keys`.
+	pub fn bind(&mut self, action: Action, keys: Vec<Keycode>) {
+		match self.keys.iter_mut().find(|binding| binding.0 == action) {
+			Some(binding) => { binding.1 = keys; },
+			None => {}
+		}
+	}
+
+	/// Binds `key` to `action` as well as whatever it's bound to already.
+	pub fn add_key(&mut self, action: Action, key: Keycode) {
+		let mut keys = self.keys(action).to_vec();
+		if !keys.contains(&key) {
+			keys.push(key);
+		}
+		self.bind(action, keys);
+	}
+
+	/// Every action `key` sets off.
+	pub fn actions_for(&self, key: Keycode) -> Vec<Action> {
+		self.keys.iter()
+			.filter(|binding| binding.1.contains(&key))
+			.map(|binding| binding.0)
+			.collect()
+	}
+}
+
+// keys which aren't a letter or digit, by name
+static KEY_NAMES: [(&'static str, Keycode); 17] = [
+	("Left", Keycode::Left), ("Right", Keycode::Right), ("Up", Keycode::Up), ("Down", Keycode::Down),
+	("Return", Keycode::Return), ("Escape", Keycode::Escape), ("Space", Keycode::Space),
+	("Tab", Keycode::Tab), ("Backspace", Keycode::Backspace),
+	("LShift", Keycode::LShift), ("RShift", Keycode::RShift),
+	("LCtrl", Keycode::LCtrl), ("RCtrl", Keycode::RCtrl),
+	("LAlt", Keycode::LAlt), ("RAlt", Keycode::RAlt),
+	("KpEnter", Keycode::KpEnter), ("Delete", Keycode::Delete)
+];
+
+/// A name for `key` which `key_from_name()` reads back: letters & digits
+/// are themselves (e.g. `W`), other keys are named (e.g. `LShift`) or
+/// failing that numbered (e.g. `#1073741882`).
+pub fn key_name(key: Keycode) -> String {
+	let code = key as i32;
+	if code >= 'a' as i32 && code <= 'z' as i32 {
+		return ((code as u8 - b'a' + b'A') as char).to_string();
+	}
+	if code >= '0' as i32 && code <= '9' as i32 {
+		return (code as u8 as char).to_string();
+	}
+
+	match KEY_NAMES.iter().find(|&&(_, named)| named == key) {
+		Some(&(name, _)) => { name.to_string() },
+		None => { format!("#{}", code) }
+	}
+}
+
+/// The key `key_name()` gave `name`.
+pub fn key_from_name(name: &str) -> Option<Keycode> {
+	let bytes = name.as_bytes();
+	if bytes.len() == 1 {
+		let letter = bytes[0];
+		return match letter {
+			b'A'...b'Z' => Keycode::from_i32((letter - b'A' + b'a') as i32),
+			b'a'...b'z' | b'0'...b'9' => Keycode::from_i32(letter as i32),
+			_ => None
+		};
+	}
+	if bytes.len() > 1 && bytes[0] == b'#' {
+		return name[1..].parse::<i32>().ok().and_then(|code| Keycode::from_i32(code));
+	}
+
+	KEY_NAMES.iter().find(|&&(named, _)| named == name).map(|&(_, key)| key)
+}
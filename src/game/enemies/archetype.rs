@@ -0,0 +1,348 @@
+use std::fs;
+use std::fs::File;
+use std::io::{BufRead, BufReader};
+use std::rc::Rc;
+use std::vec::Vec;
+use rand::Rng;
+
+use game::packs;
+use game::random;
+use game::sprite;
+use game::units;
+
+static ZOMBIES_HEADER: &'static str = "just-run zombies 1";
+
+/// How a kind of zombie decides where to go.
+#[derive(PartialEq,Eq,Clone,Copy)]
+pub enum Behaviour {
+	/// Paths to the player once they are within its detection radius
+	/// (or always, w/o one), wandering nearby until then.
+	Chase,
+	/// Wanders between nearby points & ignores the player.
+	Wander,
+	/// Heads for random points anywhere on the level.
+	Random,
+	/// Chases like `Chase`, & every `spawn_every` updates the first of
+	/// its kind spawns another one where it was heading.
+	Spawner
+}
+
+/// Where a kind of zombie's sprites come from.
+pub enum Looks {
+	/// `<name>.west` & `<name>.east` in the sprite manifest (see `sprite::named()`).
+	Named(String),
+	/// Its own sprites, facing west & east.
+	Sheet(sprite::SpriteDef, sprite::SpriteDef)
+}
+
+/// Everything which sets one kind of zombie apart from another.
+pub struct Archetype {
+	pub name:         String,
+
+	pub looks:        Looks,
+
+	// physics
+	pub accel:        units::Acceleration,
+	pub chase_accel:  units::Acceleration,
+	pub max_velocity: units::Velocity,
+
+	pub detection:    Option<units::Game>,
+	pub behaviour:    Behaviour,
+	pub spawn_every:  u32,
+	pub weight:       u32
+}
+
+/// Every kind of zombie the game knows, numbered in the order they were
+/// listed. A zombie's kind (see `zombie_type()`) is its index in here.
+pub struct Archetypes {
+	kinds: Vec<Rc<Archetype>>
+}
+
+impl Archetypes {
+	/// Reads the kinds of zombies from `file_path`, a plain text file:
+	///
+	///- `just-run zombies 1`
+	///- `zombie <name>` starts a new kind, which the lines after it describe
+	///- `sprite <name>` its sprites in the sprite manifest, `<name>.west` & `<name>.east`
+	///- or else `sheet <image>` & `frame <sheet col> <west row> <east row>`,
+	///  w/ `animation <frames> <fps>` to animate the sprite, which is still otherwise
+	///- `accel <a>`, `chase_accel <a>` (defaults to `accel`) & `max_velocity <v>`
+	///- `detection <distance>` how close the player must be to be chased
+	///- `behaviour <chase|wander|random|spawner>` & `spawn_every <updates>`
+	///- `weight <n>` how often the kind is picked at random, relative to the
+	///  others (defaults to 1, 0 for kinds which only spawn from maps)
+	///
+	/// Lines starting w/ `#` are comments. Returns an error naming the
+	/// offending line if the file is malformed.
+	pub fn load(file_path: &str) -> Result<Archetypes, String> {
+		let file = match File::open(packs::locate(file_path)) {
+			Ok(file) => { file },
+			Err(msg) => { return Err(format!("could not open zombies {}: {}", file_path, msg)); }
+		};
+
+		let mut drafts: Vec<Draft> = Vec::new();
+		for (index, line) in BufReader::new(file).lines().enumerate() {
+			let line_num = index + 1;
+			let line = match line {
+				Ok(line) => { line },
+				Err(msg) => { return Err(format!("{}:{}: {}", file_path, line_num, msg)); }
+			};
+
+			if line_num == 1 {
+				if line.trim() != ZOMBIES_HEADER {
+					return Err(format!("{}:{}: not a zombies file", file_path, line_num));
+				}
+				continue;
+			}
+
+			let fields: Vec<&str> = line.split_whitespace().collect();
+			if fields.len() == 0 || fields[0].starts_with("#") {
+				continue;
+			}
+
+			if fields[0] == "zombie" {
+				if fields.len() != 2 {
+					return Err(format!("{}:{}: expected `zombie <name>`", file_path, line_num));
+				}
+				if drafts.iter().any(|draft| draft.name == fields[1]) {
+					return Err(format!("{}:{}: zombie `{}` is listed twice", file_path, line_num, fields[1]));
+				}
+				drafts.push(Draft::new(fields[1], line_num));
+				continue;
+			}
+
+			let draft = match drafts.last_mut() {
+				Some(draft) => { draft },
+				None => { return Err(format!("{}:{}: `{}` must follow a `zombie`", file_path, line_num, fields[0])); }
+			};
+			match (fields[0], fields.len()) {
+				("sprite", 2) => {
+					draft.sprite = Some(fields[1].to_string());
+				},
+				("sheet", 2) => {
+					match fs::metadata(packs::locate(fields[1])) {
+						Ok(_) => { draft.sheet = Some(fields[1].to_string()); },
+						Err(_) => { return Err(format!("{}:{}: missing asset `{}`", file_path, line_num, fields[1])); }
+					}
+				},
+				("frame", 4) => {
+					draft.frame = Some((units::Tile(try!(parse_number(file_path, line_num, fields[1]))),
+					                    units::Tile(try!(parse_number(file_path, line_num, fields[2]))),
+					                    units::Tile(try!(parse_number(file_path, line_num, fields[3])))));
+				},
+				("animation", 3) => {
+					draft.animation = Some((try!(parse_number(file_path, line_num, fields[1])),
+					                        try!(parse_number(file_path, line_num, fields[2]))));
+				},
+				("accel", 2) => {
+					draft.accel = Some(units::Acceleration(try!(parse_float(file_path, line_num, fields[1]))));
+				},
+				("chase_accel", 2) => {
+					draft.chase_accel = Some(units::Acceleration(try!(parse_float(file_path, line_num, fields[1]))));
+				},
+				("max_velocity", 2) => {
+					draft.max_velocity = Some(units::Velocity(try!(parse_float(file_path, line_num, fields[1]))));
+				},
+				("detection", 2) => {
+					draft.detection = Some(units::Game(try!(parse_float(file_path, line_num, fields[1]))));
+				},
+				("behaviour", 2) => {
+					draft.behaviour = Some(match fields[1] {
+						"chase"   => Behaviour::Chase,
+						"wander"  => Behaviour::Wander,
+						"random"  => Behaviour::Random,
+						"spawner" => Behaviour::Spawner,
+						other     => { return Err(format!("{}:{}: unknown behaviour `{}`", file_path, line_num, other)); }
+					});
+				},
+				("spawn_every", 2) => {
+					draft.spawn_every = Some(try!(parse_number(file_path, line_num, fields[1])));
+				},
+				("weight", 2) => {
+					draft.weight = try!(parse_number(file_path, line_num, fields[1]));
+				},
+				(other, _) => {
+					return Err(format!("{}:{}: unexpected `{}`", file_path, line_num, other));
+				}
+			}
+		}
+
+		let mut kinds: Vec<Rc<Archetype>> = Vec::new();
+		for draft in drafts.into_iter() {
+			kinds.push(Rc::new(try!(draft.finish(file_path))));
+		}
+		if !kinds.iter().any(|kind| kind.weight > 0) {
+			return Err(format!("{}: no zombie has a weight above 0", file_path));
+		}
+
+		Ok(Archetypes { kinds: kinds })
+	}
+
+	/// The kind of zombie called `name`.
+	pub fn find(&self, name: &str) -> Option<u32> {
+		self.kinds.iter().position(|kind| kind.name == name).map(|index| index as u32)
+	}
+
+	pub fn get(&self, kind: u32) -> Option<&Rc<Archetype>> {
+		self.kinds.get(kind as usize)
+	}
+
+	/// The kinds of spawner which are due to spawn on `update`.
+	pub fn spawning(&self, update: u32) -> Vec<u32> {
+		self.kinds.iter().enumerate().filter(|&(_, kind)| {
+			kind.behaviour == Behaviour::Spawner && update % kind.spawn_every == 0
+		}).map(|(index, _)| index as u32).collect()
+	}
+
+	/// Picks a kind of zombie at random, in proportion to their weights.
+	pub fn pick(&self, rng: &mut random::GameRng) -> u32 {
+		let total = self.kinds.iter().fold(0, |total, kind| total + kind.weight);
+		let mut roll = rng.gen_range(0u32, total);
+		for (index, kind) in self.kinds.iter().enumerate() {
+			if roll < kind.weight {
+				return index as u32;
+			}
+			roll = roll - kind.weight;
+		}
+
+		unreachable!()
+	}
+}
+
+/// A kind of zombie which is still being read.
+struct Draft {
+	name:         String,
+	line_num:     usize,
+	sprite:       Option<String>,
+	sheet:        Option<String>,
+	frame:        Option<(units::Tile, units::Tile, units::Tile)>,
+	animation:    Option<(units::Frame, units::Fps)>,
+	accel:        Option<units::Acceleration>,
+	chase_accel:  Option<units::Acceleration>,
+	max_velocity: Option<units::Velocity>,
+	detection:    Option<units::Game>,
+	behaviour:    Option<Behaviour>,
+	spawn_every:  Option<u32>,
+	weight:       u32
+}
+
+impl Draft {
+	fn new(name: &str, line_num: usize) -> Draft {
+		Draft {
+			name:         name.to_string(),
+			line_num:     line_num,
+			sprite:       None,
+			sheet:        None,
+			frame:        None,
+			animation:    None,
+			accel:        None,
+			chase_accel:  None,
+			max_velocity: None,
+			detection:    None,
+			behaviour:    None,
+			spawn_every:  None,
+			weight:       1
+		}
+	}
+
+	/// Fails w/ the line the zombie started on if a required field is missing.
+	fn finish(self, file_path: &str) -> Result<Archetype, String> {
+		let missing = |field: &str| format!("{}:{}: zombie `{}` has no `{}`", file_path, self.line_num, self.name, field);
+		let looks = match self.sprite {
+			Some(ref name) => Looks::Named(name.clone()),
+			None => {
+				let sheet = match self.sheet { Some(ref sheet) => sheet.clone(), None => { return Err(missing("sprite` or `sheet")); } };
+				let (frame, west_row, east_row) = match self.frame { Some(frame) => frame, None => { return Err(missing("frame")); } };
+				let facing = |row: units::Tile| sprite::SpriteDef::strip(
+					sheet.clone(), (frame, row), (units::Tile(1), units::Tile(1)), self.animation
+				);
+				Looks::Sheet(facing(west_row), facing(east_row))
+			}
+		};
+		let accel = match self.accel { Some(accel) => accel, None => { return Err(missing("accel")); } };
+		let max_velocity = match self.max_velocity { Some(velocity) => velocity, None => { return Err(missing("max_velocity")); } };
+		let behaviour = match self.behaviour { Some(behaviour) => behaviour, None => { return Err(missing("behaviour")); } };
+		let spawn_every = match (behaviour, self.spawn_every) {
+			(Behaviour::Spawner, Some(0)) | (Behaviour::Spawner, None) => { return Err(missing("spawn_every")); },
+			(_, spawn_every) => spawn_every.unwrap_or(0)
+		};
+
+		Ok(Archetype {
+			name:         self.name.clone(),
+			looks:        looks,
+			accel:        accel,
+			chase_accel:  self.chase_accel.unwrap_or(accel),
+			max_velocity: max_velocity,
+			detection:    self.detection,
+			behaviour:    behaviour,
+			spawn_every:  spawn_every,
+			weight:       self.weight
+		})
+	}
+}
+
+fn parse_number(file_path: &str, line_num: usize, field: &str) -> Result<u32, String> {
+	match field.parse::<u32>() {
+		Ok(value) => { Ok(value) },
+		Err(_) => { Err(format!("{}:{}: expected a number, found `{}`", file_path, line_num, field)) }
+	}
+}
+
+fn parse_float(file_path: &str, line_num: usize, field: &str) -> Result<f64, String> {
+	match field.parse::<f64>() {
+		Ok(value) => { Ok(value) },
+		Err(_) => { Err(format!("{}:{}: expected a number, found `{}`", file_path, line_num, field)) }
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use std::env;
+	use std::fs;
+	use std::fs::File;
+	use std::io::Write;
+
+	use super::Archetypes;
+	use game::random;
+
+	static WEIGHTED: &'static str = "just-run zombies 1
+zombie common
+sprite       zombie.slow
+accel        0.00003
+max_velocity 0.15
+behaviour    chase
+weight       3
+
+zombie rare
+sprite       zombie.slow
+accel        0.00003
+max_velocity 0.15
+behaviour    chase
+
+zombie placed
+sprite       zombie.slow
+accel        0.00003
+max_velocity 0.15
+behaviour    chase
+weight       0
+";
+
+	#[test]
+	fn picks_in_proportion_to_weight() {
+		let path = env::temp_dir().join("just-run-weighted.cfg");
+		File::create(&path).unwrap().write_all(WEIGHTED.as_bytes()).unwrap();
+		let archetypes = Archetypes::load(path.to_str().unwrap());
+		fs::remove_file(&path).unwrap();
+		let archetypes = archetypes.ok().unwrap();
+
+		let mut rng = random::new_rng(42);
+		let mut picked = [0u32; 3];
+		for _ in 0.. 4000 {
+			picked[archetypes.pick(&mut rng) as usize] += 1;
+		}
+		// common is 3 in 4, rare 1 in 4 & a weight of 0 is never picked
+		assert!(picked[0] > 2800 && picked[0] < 3200, "{:?}", picked);
+		assert!(picked[1] > 800 && picked[1] < 1200, "{:?}", picked);
+		assert_eq!(picked[2], 0);
+	}
+}
@@ -1,9 +1,8 @@
 // Bring enemies into this crate's namespace
-pub use game::enemies::zombie::SlowZombie;
-pub use game::enemies::zombie::CrazyZombie;
-pub use game::enemies::zombie::RandomZombie;
-pub use game::enemies::zombie::CloudZombie;
+pub use game::enemies::zombie::ArchetypeZombie;
 pub use game::enemies::zombie::Zombie;
+pub use game::enemies::archetype::{Archetype, Archetypes, Behaviour, Looks};
 
 // Load enemy modules
+pub mod archetype;
 pub mod zombie;
@@ -1,496 +1,783 @@
 use std;
 use std::cmp;
-use std::fs::File;
-use std::io::Write;
-use std::io::Read;
-use std::path::Path;
+use std::fs;
+use std::path::PathBuf;
 use std::vec::Vec;
-use rand::Rng;
 use std::string::String;
 
 use sdl2;
-use sdl2::rect;
 use sdl2::timer;
 use sdl2::event::Event;
 use sdl2::keyboard::Keycode;
+use sdl2::controller::Button;
 use sdl2_ttf;
 
-use rand;
 use time::{Duration, PreciseTime};
 
-pub use game::units::{AsGame};
-
 pub use game::units;
-pub use game::enemies;
-pub use game::powerups;
-pub use game::traps;
-pub use game::map;
+pub use game::units::AsPixel;
+pub use game::camera;
 pub use game::input;
-pub use game::vehicle;
-pub use game::car;
-pub use game::heli;
-pub use game::player;
 pub use game::graphics;
+pub use game::text;
+pub use game::random;
+pub use game::replay;
+pub use game::save;
+pub use game::leaderboard;
+pub use game::settings;
+pub use game::storage;
+pub use game::packs;
+pub use game::controls;
+pub use game::controls::Action;
+pub use game::gamepad;
+pub use game::input::{KeyAction, Signal};
+pub use game::world;
 pub use game::collisions::Rectangle;
 
+// recordings & replays always step the world at this rate, whatever the cap
 const TARGET_FRAMERATE: units::Fps  =  60;
+
+// where a game in progress is kept between launches, in the data directory
+static SAVE_FILE: &'static str = "savegame.txt";
+
+// the rows of the options screen, in the order they're listed
+static OPTION_ROWS: usize = 8;
+static VOLUME_STEP: i32 = 16;
+static FRAMERATE_STEP: u32 = 15;
 static MAX_FRAME_TIME: units::Millis =  units::Millis(5 * (1000 / TARGET_FRAMERATE) as i64);
 
+// size of generated levels, map files bring their own
 pub static LEVEL_WIDTH:   units::Tile =  units::Tile(60);
 pub static SCREEN_WIDTH: units::Tile =  units::Tile(20);
 pub static LEVEL_HEIGHT: units::Tile =   units::Tile(60);
 pub static SCREEN_HEIGHT: units::Tile = units::Tile(20);
 
-pub static POSSIBLE_CHARACTER_TILES: u32 = 58;
-pub static MAX_ENEMIES:              u32 = 30;
-pub static MAX_POWERUPS:             u32 = 20;
-// pub static MAX_TRAPS:                uint = 5;
-
-pub static POSSIBLE_PART_RANGE: (u32, u32) = (20, 55);
-pub static LEVEL_PARTS:                 u32 = 3;
-pub static LEVEL_1_TIME:                 i32 = 5000;
-pub static LEVEL_1_CINEMATIC_FRAMES:     i32 = 300;
-pub static LEVEL_2_TIME:                 i32 = 2000;
-pub static LEVEL_2_CINEMATIC_FRAMES:     i32 = 300;
-
-pub static PLAYER_STARTING_X: units::Tile = units::Tile(1);
-pub static PLAYER_STARTING_Y: units::Tile = units::Tile(4);
-
-pub static VEHICLE_STARTING_X: units::Tile = units::Tile(1);
-pub static VEHICLE_STARTING_Y: units::Tile = units::Tile(1);
-
-// hadle the annoying Rect i32
-macro_rules! rect(
-    ($x:expr, $y:expr, $w:expr, $h:expr) => (
-        match rect::Rect::new($x as i32, $y as i32, $w as u32, $h as u32) {
-        	Ok(rect) => { rect.unwrap() },
-        	Err(msg) => { panic!(msg) }
-        }
-    )
-);
+// how each kind of text on the screens is drawn
+static TITLE_TEXT: text::Style = text::Style {
+	size: 72, color: (255, 0, 0), align: text::Align::Center, wrap: Some(600), effect: text::Effect::Outline((0, 0, 0), 3)
+};
+static HEADING_TEXT: text::Style = text::Style {
+	size: 40, color: (255, 0, 0), align: text::Align::Center, wrap: Some(600), effect: text::Effect::Shadow((0, 0, 0), 3)
+};
+static PROMPT_TEXT: text::Style = text::Style {
+	size: 22, color: (255, 0, 0), align: text::Align::Center, wrap: Some(520), effect: text::Effect::Shadow((0, 0, 0), 2)
+};
+static MENU_TEXT: text::Style = text::Style {
+	size: 24, color: (255, 0, 0), align: text::Align::Left, wrap: None, effect: text::Effect::Shadow((0, 0, 0), 2)
+};
+static STATUS_TEXT: text::Style = text::Style {
+	size: 20, color: (255, 0, 0), align: text::Align::Right, wrap: None, effect: text::Effect::Outline((0, 0, 0), 1)
+};
+// pixels between blocks of text & around the edge of the screen
+static TEXT_GAP: u32 = 24;
+
+/// How a game was asked to run from the command line.
+pub struct Options {
+	/// Replay every run from this seed instead of drawing a fresh one.
+	pub seed:          Option<u32>,
+	/// Record all key events to this replay file.
+	pub record_path:   Option<String>,
+	/// Play this replay file back in place of the keyboard.
+	pub replay_path:   Option<String>,
+	/// How many times faster than real time a replay runs (`0` is unthrottled).
+	pub replay_speed:  u32,
+	/// How the view follows the player.
+	pub camera_mode:   camera::CameraMode,
+}
+
+impl Options {
+	pub fn new() -> Options {
+		Options {
+			seed:          None,
+			record_path:   None,
+			replay_path:   None,
+			replay_speed:  1,
+			camera_mode:   camera::CameraMode::Follow,
+		}
+	}
+}
 
 /// An instance of the `just-run` game with its own event loop.
+///
+/// The game is a front-end to a `world::World`: it feeds the world input,
+/// plays the sounds it asks for and renders whatever state it is in.
 pub struct Game<'engine> {
-	player:     player::Player,
-	enemies:    Vec<Box<enemies::Zombie>>,
-	powerups:   Vec<Box<powerups::Powerup>>,
-	traps:      Vec<Box<traps::Trap>>,
-	killed:     Vec<Box<enemies::Zombie>>,
-	activated:  Vec<Box<powerups::Powerup>>,
-	tripped:    Vec<Box<traps::Trap>>,
-	parts:      Vec<Box<vehicle::Part>>,
-	coll_parts: Vec<Box<vehicle::Part>>,
-	vehicle:    Box<vehicle::Vehicle>,
-	map:        map::Map,
+	world:      world::World,
 
 	display:        graphics::Graphics<'engine>,
 	context:        &'engine sdl2::Sdl,
 	controller:     input::Input,
+	gamepads:       gamepad::Gamepads,
 	paused:         bool,
-	updates:        i32,
-	level:          i32, 
-	highscore:      i32,
-	score:          i32,
-	timer:          i32,
-	completed_lvl:  bool,
-	freeze_counter: i32, 
-	alt_control:    bool
+	leaderboard:    leaderboard::Leaderboard,
+	settings:       settings::Settings,
+	alt_control:    bool,
+	fixed_seed:     Option<u32>,
+	can_resume:     bool,
+	save_path:      PathBuf,
+
+	// who is playing the current run & where it sits on the leaderboard
+	run_name:       Option<String>,
+	run_entry:      Option<leaderboard::Entry>,
+
+	// input recording & playback
+	frame:          u32,
+	recorder:       Option<replay::Recorder>,
+	record_path:    Option<String>,
+	replay:         Option<replay::Replay>,
+	replay_speed:   u32
 }
 
 impl<'e> Game<'e> {
 	/// Starts running this games event loop, note that this will block indefinitely.
 	/// This function will return to the caller when the escape key is pressed.
-	pub fn new(context: &'e sdl2::Sdl) -> Game<'e> {
+	///
+	/// When a seed is supplied every run (incl. restarts) replays from it,
+	/// otherwise each run draws a fresh seed. Replays always use the seed
+	/// they were recorded with. Fails if the replay or the world can't be
+	/// loaded.
+	pub fn new(context: &'e sdl2::Sdl, options: Options) -> Result<Game<'e>, String> {
 		// initialize all major subsystems
 	    let _ttf_context = sdl2_ttf::init();
+		let settings    = settings::Settings::load();
+
+		let replay = match options.replay_path {
+			Some(ref path) => match replay::Replay::load(path) {
+				Ok(replay) => { println!("playing back replay {} (seed: {})", path, replay.seed); Some(replay) },
+				Err(msg) => { return Err(msg); }
+			},
+			None => None
+		};
+		// the levels a replay was recorded on come from its packs, so they
+		// have to be in place before anything is loaded
+		match replay {
+			Some(ref replay) => { packs::activate(&replay.packs); },
+			None => {}
+		}
+
 		// hide the mouse cursor in our drawing context
-		let mut display = graphics::Graphics::new(context);
-		let controller  = input::Input::new();
-		let mut rng = rand::thread_rng();
-		let enemies_vector: Vec<Box<enemies::Zombie>> = Vec::new();
-		let powerups_vector: Vec<Box<powerups::Powerup>> = Vec::new();
-		let traps_vector: Vec<Box<traps::Trap>> = Vec::new();
-		let killed_vector: Vec<Box<enemies::Zombie>> = Vec::new();
-		let activated_vector: Vec<Box<powerups::Powerup>> = Vec::new();
-		let tripped_vector: Vec<Box<traps::Trap>> = Vec::new();
-		let part_vector: Vec<Box<vehicle::Part>> = Vec::new();
-		let coll_part_vector: Vec<Box<vehicle::Part>> = Vec::new();
-
-		let mut game = Game {
-			map: map::Map::load_map(&mut display, 1 as i32),
-			player: player::Player::new(
-				&mut display,
-				PLAYER_STARTING_X.to_game(),
-				PLAYER_STARTING_Y.to_game()
-			),
-
-			enemies: enemies_vector,
-			powerups: powerups_vector,
-			traps: traps_vector,
-			killed: killed_vector,
-			activated: activated_vector,
-			tripped: tripped_vector,
-			parts: part_vector,
-			coll_parts: coll_part_vector,
-
-			vehicle: Box::new( heli::Helicopter::new(
-				&mut display, 
-				VEHICLE_STARTING_X.to_game(), 
-				VEHICLE_STARTING_Y.to_game()
-			) ) as Box<vehicle::Vehicle>,
+		let display     = graphics::Graphics::new(context, &settings);
+		let seed = match replay {
+			Some(ref replay) => Some(replay.seed),
+			None             => options.seed
+		};
+		let initial_seed = match seed {
+			Some(seed) => seed,
+			None       => random::random_seed()
+		};
+
+		// replays play on whatever the recording was made w/
+		let (camera_mode, difficulty, alt_control, bindings) = match replay {
+			Some(ref replay) => (replay.camera, replay.difficulty, replay.alt_control, replay.bindings.clone()),
+			None             => (options.camera_mode, settings.difficulty, settings.alt_control, controls::Bindings::load())
+		};
+
+		// a recording is only useful if restarts replay the same seed
+		let (seed, recorder) = match options.record_path {
+			Some(_) => {
+				let recorder = replay::Recorder::new(initial_seed, &packs::active(), camera_mode, difficulty, alt_control, &bindings);
+				(Some(initial_seed), Some(recorder))
+			},
+			None    => (seed, None)
+		};
+		let controller = input::Input::new(bindings);
+
+		let mut world = try!(world::World::with_difficulty(initial_seed, difficulty));
+		world.set_camera_mode(camera_mode);
+
+		// resuming would throw recordings & replays off their seed
+		let save_path = storage::data_dir().join(SAVE_FILE);
+		let can_resume = replay.is_none() && recorder.is_none() && fs::metadata(&save_path).is_ok();
+
+		Ok(Game {
+			world:          world,
 
 			display:        display,
 			context:        context,
 			controller:     controller, 
+			gamepads:       gamepad::Gamepads::new(context),
 			paused:         true,
-			updates:        0,
-			level:          1,
-			highscore:      Game::get_highscore(),
-			score:          0,
-			timer:          LEVEL_1_TIME,
-			completed_lvl:  false,
-			freeze_counter: 0, 
-			alt_control:    false
+			leaderboard:    leaderboard::Leaderboard::load(),
+			settings:       settings,
+			alt_control:    alt_control,
+			fixed_seed:     seed,
+			can_resume:     can_resume,
+			save_path:      save_path,
+
+			run_name:       None,
+			run_entry:      None,
+
+			frame:          0,
+			recorder:       recorder,
+			record_path:    options.record_path,
+			replay:         replay,
+			replay_speed:   options.replay_speed
+		})
+	}
+
+	pub fn start(&mut self) {
+		self.display.play_music();
+		self.draw_start_screen();
+		self.event_loop();
+	}
+
+	pub fn draw_start_screen(&mut self) {
+		self.display.clear_buffer();
+		self.world.map.draw_background(&mut self.display);
+		self.display.switch_buffers();
+		let score_string = match self.leaderboard.entries().first() {
+			Some(best) => String::from("HIGHSCORE: ") + &best.score.to_string() + " BY " + &best.name,
+			None => String::from("CURRENT HIGHSCORE: 0")
 		};
-		let number_of_zombies = rng.gen_range(20u32, MAX_ENEMIES);
-		for _ in 0.. number_of_zombies {
-		  	game.spawn_zombie(rng.gen_range(1u32, 5u32), (units::Game(0.0), units::Game(0.0)));
-		}
-		let number_of_powerups = rng.gen_range(0u32, MAX_POWERUPS);
-		for _ in 0.. number_of_powerups {
-			game.spawn_powerup(rng.gen_range(1u32, 7u32));
-		}
-		// let number_of_traps = rng.gen_range(0u, MAX_TRAPS);
-		// for _ in range(0, number_of_traps) {
-		// 	game.spawn_trap(1);
-		// }
-		for i in 0.. LEVEL_PARTS {
-			game.spawn_part(i);
+		let seed_string = String::from("SEED: ") + &self.world.seed.to_string();
+		let mut blocks = vec![
+			("JUST F&#%IN RUN!!!", &TITLE_TEXT),
+			(&score_string[..], &HEADING_TEXT),
+			(&seed_string[..], &PROMPT_TEXT),
+			("PRESS ENTER AND START RUNNING...", &PROMPT_TEXT)
+		];
+		if self.can_resume {
+			blocks.push(("OR PRESS R TO PICK UP WHERE YOU LEFT OFF", &PROMPT_TEXT));
 		}
+		self.draw_centered(&blocks);
+		self.display.switch_buffers();
+	}
 
-		game
+	pub fn draw_pause_screen(&mut self, saved: bool) {
+		self.display.clear_buffer();
+		self.draw();
+		self.draw_status_bar();
+		let prompt = if saved {
+			"GAME SAVED, PRESS ENTER TO KEEP RUNNING..."
+		} else {
+			"PRESS S TO SAVE OR ENTER TO KEEP RUNNING..."
+		};
+		self.draw_centered(&[("PAUSED", &TITLE_TEXT), (prompt, &PROMPT_TEXT), ("PRESS O FOR OPTIONS", &PROMPT_TEXT)]);
+		self.display.switch_buffers();
 	}
 
-	pub fn spawn_zombie(&mut self, kind: u32, location: (units::Game, units::Game)) {
-		let mut rng = rand::thread_rng();
-		let zombie = match kind {
-			1 => {
-				Box::new( enemies::SlowZombie::new(
-					&mut self.display, 
-					(units::Tile(rng.gen_range(1u32, POSSIBLE_CHARACTER_TILES))).to_game(),
-					(units::Tile(rng.gen_range(1u32, POSSIBLE_CHARACTER_TILES))).to_game()
-				) ) as Box<enemies::Zombie>
-			}
-			2 => {
-				Box::new( enemies::CrazyZombie::new(
-					&mut self.display, 
-					(units::Tile(rng.gen_range(1u32, POSSIBLE_CHARACTER_TILES))).to_game(),
-					(units::Tile(rng.gen_range(1u32, POSSIBLE_CHARACTER_TILES))).to_game()
-				) ) as Box<enemies::Zombie>
-			}
-			3 => {
-				Box::new( enemies::RandomZombie::new(
-					&mut self.display, 
-					(units::Tile(rng.gen_range(1u32, POSSIBLE_CHARACTER_TILES))).to_game(),
-					(units::Tile(rng.gen_range(1u32, POSSIBLE_CHARACTER_TILES))).to_game()
-				) ) as Box<enemies::Zombie>
-			}
-			_ => {
-				match location {
-					(units::Game(0.0), units::Game(0.0)) => Box::new( enemies::CloudZombie::new(
-																&mut self.display, 
-																(units::Tile(rng.gen_range(1u32, POSSIBLE_CHARACTER_TILES))).to_game(),
-																(units::Tile(rng.gen_range(1u32, POSSIBLE_CHARACTER_TILES))).to_game()
-															) ) as Box<enemies::Zombie>,
-					(x, y) => Box::new( enemies::CloudZombie::new(&mut self.display, x, y) ) as Box<enemies::Zombie>,
+	/// Lets the player go through the settings w/ the movement keys,
+	/// putting each change into effect as it's made. Leaving (w/ the pause
+	/// or quit keys) saves them. Blocks until the player leaves.
+	///
+	/// Settings which would throw a recording or replay off are locked.
+	fn draw_options_screen(&mut self, event_pump: &mut sdl2::EventPump) {
+		let mut selected = 0;
+		let mut leaving = false;
+		while !leaving {
+			let mut open_controls = false;
+			for event in event_pump.poll_iter() {
+				if self.handle_hotplug(&event) {
+					continue;
+				}
+				let (actions, down) = match event {
+					Event::KeyDown { keycode: Some(key), .. } => (self.controller.bindings().actions_for(key), true),
+					Event::KeyUp { keycode: Some(key), .. } => (self.controller.bindings().actions_for(key), false),
+					Event::ControllerButtonDown { button, .. } => (gamepad::actions_for(button), true),
+					Event::ControllerButtonUp { button, .. } => (gamepad::actions_for(button), false),
+					_ => { continue; }
+				};
+				for action in actions {
+					match (action, down) {
+						(Action::MoveUp, true) => { selected = (selected + OPTION_ROWS - 1) % OPTION_ROWS; },
+						(Action::MoveDown, true) => { selected = (selected + 1) % OPTION_ROWS; },
+						(Action::MoveLeft, true) => { self.change_option(selected, false); },
+						(Action::MoveRight, true) if selected == OPTION_ROWS - 1 => { open_controls = true; },
+						(Action::MoveRight, true) => { self.change_option(selected, true); },
+						(Action::Pause, false) | (Action::Quit, false) => { leaving = true; },
+						_ => {}
+					}
 				}
 			}
-		};
-		let colliding_tiles = self.map.get_colliding_tiles(&zombie.damage_rectangle());
-		let (old_x, old_y) = location;
-		let new_location = (old_x + units::Game(1.0), old_y + units::Game(1.0));
-		for tile in colliding_tiles.iter() {
-			if tile.tile_type == map::TileType::Wall {
-				self.spawn_zombie(kind, new_location);
-				return;
+			if open_controls && !self.gameplay_locked() {
+				self.draw_controls_screen(event_pump);
 			}
+
+			self.display.clear_buffer();
+			self.world.map.draw_background(&mut self.display);
+			let rows: Vec<String> = (0.. OPTION_ROWS).map(|row| self.option_label(row)).collect();
+			self.draw_menu("OPTIONS", &rows, selected, "LEFT & RIGHT TO CHANGE, PAUSE TO GO BACK");
+			self.display.switch_buffers();
+
+			std::thread::sleep_ms(1000 / TARGET_FRAMERATE as u32);
 		}
 
-		self.enemies.push(zombie);
+		match self.settings.save() {
+			Ok(_) => {},
+			Err(msg) => { println!("could not save settings: {}", msg); }
+		}
 	}
 
-	pub fn spawn_powerup(&mut self, kind: u32) {
-		let mut rng = rand::thread_rng();
-		let powerup = match kind {
-			1 => {
-				Box::new( powerups::CricketBat::new(
-					&mut self.display, 
-					(units::Tile(rng.gen_range(1u32, POSSIBLE_CHARACTER_TILES))).to_game(),
-					(units::Tile(rng.gen_range(1u32, POSSIBLE_CHARACTER_TILES))).to_game()
-				) ) as Box<powerups::Powerup>
+	/// Lets the player rebind every action. It always listens to the arrow
+	/// keys, `Return`, `Backspace` & `Escape`, so no binding can lock the
+	/// player out of it:
+	///
+	///- `Up` & `Down` pick an action
+	///- `Return` adds the next key pressed to the action's keys
+	///- `Backspace` replaces the action's keys w/ the next key pressed
+	///- `Escape` cancels waiting for a key, or else leaves & saves the bindings
+	fn draw_controls_screen(&mut self, event_pump: &mut sdl2::EventPump) {
+		let mut bindings = self.controller.bindings().clone();
+		let actions = controls::ACTIONS.len();
+		let mut selected = 0;
+		// waiting for a key to add to (false) or replace (true) the action's keys
+		let mut waiting: Option<bool> = None;
+		let mut leaving = false;
+		while !leaving {
+			for event in event_pump.poll_iter() {
+				if self.handle_hotplug(&event) {
+					continue;
+				}
+				let action = controls::ACTIONS[selected];
+				match (waiting, event) {
+					(Some(_), Event::KeyUp { keycode: Some(Keycode::Escape), .. }) => { waiting = None; },
+					(Some(replace), Event::KeyDown { keycode: Some(key), .. }) if key != Keycode::Escape => {
+						if replace {
+							bindings.bind(action, vec![key]);
+						} else {
+							bindings.add_key(action, key);
+						}
+						waiting = None;
+					},
+					(None, Event::KeyDown { keycode: Some(Keycode::Up), .. }) => { selected = (selected + actions - 1) % actions; },
+					(None, Event::KeyDown { keycode: Some(Keycode::Down), .. }) => { selected = (selected + 1) % actions; },
+					(None, Event::KeyUp { keycode: Some(Keycode::Return), .. }) => { waiting = Some(false); },
+					(None, Event::KeyUp { keycode: Some(Keycode::Backspace), .. }) => { waiting = Some(true); },
+					(None, Event::KeyUp { keycode: Some(Keycode::Escape), .. }) => { leaving = true; },
+					_ => {}
+				}
 			}
+
+			self.display.clear_buffer();
+			self.world.map.draw_background(&mut self.display);
+			let rows: Vec<String> = controls::ACTIONS.iter().map(|action| {
+				let keys: Vec<String> = bindings.keys(*action).iter().map(|key| controls::key_name(*key).to_uppercase()).collect();
+				action.name().replace("_", " ").to_uppercase() + ": " + &keys.join(", ")
+			}).collect();
+			let help = match waiting {
+				Some(_) => "PRESS A KEY, ESCAPE TO CANCEL...",
+				None    => "ENTER TO ADD A KEY, BACKSPACE TO REPLACE"
+			};
+			self.draw_menu("CONTROLS", &rows, selected, help);
+			self.display.switch_buffers();
+
+			std::thread::sleep_ms(1000 / TARGET_FRAMERATE as u32);
+		}
+
+		match bindings.save() {
+			Ok(_) => {},
+			Err(msg) => { println!("could not save controls: {}", msg); }
+		}
+		self.controller.set_bindings(bindings);
+	}
+
+	/// Whether settings which change how a run plays can't be touched.
+	fn gameplay_locked(&self) -> bool {
+		self.recorder.is_some() || self.replay.is_some()
+	}
+
+	/// The options screen's text for `row`.
+	fn option_label(&self, row: usize) -> String {
+		let on_off = |value: bool| if value { "ON" } else { "OFF" };
+		let locked = if self.gameplay_locked() { " (LOCKED)" } else { "" };
+		match row {
+			0 => format!("SCALE: {}X", self.settings.scale),
+			1 => format!("FULLSCREEN: {}", on_off(self.settings.fullscreen)),
+			2 => format!("MUSIC VOLUME: {}", self.settings.music_volume),
+			3 => format!("SOUND VOLUME: {}", self.settings.sound_volume),
+			4 => format!("FRAME RATE: {}", self.settings.framerate),
+			5 => format!("ALT CONTROLS: {}{}", on_off(self.settings.alt_control), locked),
+			6 => format!("DIFFICULTY: {}{}", self.settings.difficulty.name().to_uppercase(), locked),
+			_ => format!("REBIND KEYS...{}", locked)
+		}
+	}
+
+	/// Steps the setting on `row` up or down & puts it into effect.
+	fn change_option(&mut self, row: usize, up: bool) {
+		match row {
+			0 => {
+				self.settings.scale = if up { cmp::min(self.settings.scale + 1, 4) } else { cmp::max(self.settings.scale - 1, 1) };
+			},
+			1 => { self.settings.fullscreen = !self.settings.fullscreen; },
 			2 => {
-				Box::new( powerups::KillZombie::new(
-					&mut self.display, 
-					(units::Tile(rng.gen_range(1u32, POSSIBLE_CHARACTER_TILES))).to_game(),
-					(units::Tile(rng.gen_range(1u32, POSSIBLE_CHARACTER_TILES))).to_game()
-				) ) as Box<powerups::Powerup>
-			}
+				let volume = self.settings.music_volume + if up { VOLUME_STEP } else { -VOLUME_STEP };
+				self.settings.music_volume = cmp::max(cmp::min(volume, settings::MAX_VOLUME), 0);
+			},
 			3 => {
-				Box::new( powerups::WipeOut::new(
-					&mut self.display, 
-					(units::Tile(rng.gen_range(1u32, POSSIBLE_CHARACTER_TILES))).to_game(),
-					(units::Tile(rng.gen_range(1u32, POSSIBLE_CHARACTER_TILES))).to_game()
-				) ) as Box<powerups::Powerup>
-			}
+				let volume = self.settings.sound_volume + if up { VOLUME_STEP } else { -VOLUME_STEP };
+				self.settings.sound_volume = cmp::max(cmp::min(volume, settings::MAX_VOLUME), 0);
+			},
 			4 => {
-				Box::new( powerups::Freeze::new(
-					&mut self.display, 
-					(units::Tile(rng.gen_range(1u32, POSSIBLE_CHARACTER_TILES))).to_game(),
-					(units::Tile(rng.gen_range(1u32, POSSIBLE_CHARACTER_TILES))).to_game()
-				) ) as Box<powerups::Powerup>
-			}
-			5 => {
-				Box::new( powerups::Teleport::new(
-					&mut self.display, 
-					(units::Tile(rng.gen_range(1u32, POSSIBLE_CHARACTER_TILES))).to_game(),
-					(units::Tile(rng.gen_range(1u32, POSSIBLE_CHARACTER_TILES))).to_game()
-				) ) as Box<powerups::Powerup>
-			} 
-			_ => {
-				Box::new( powerups::Nuke::new(
-					&mut self.display, 
-					(units::Tile(rng.gen_range(1u32, POSSIBLE_CHARACTER_TILES))).to_game(),
-					(units::Tile(rng.gen_range(1u32, POSSIBLE_CHARACTER_TILES))).to_game()
-				) ) as Box<powerups::Powerup>
-			}
-		};
-		let colliding_tiles = self.map.get_colliding_tiles(&powerup.damage_rectangle());
-		for tile in colliding_tiles.iter() {
-			if tile.tile_type == map::TileType::Wall {
-				self.spawn_powerup(kind);
-				return;
-			}
+				self.settings.framerate = if up { cmp::min(self.settings.framerate + FRAMERATE_STEP, 240) } else { cmp::max(self.settings.framerate - FRAMERATE_STEP, 15) };
+			},
+			5 if !self.gameplay_locked() => {
+				self.settings.alt_control = !self.settings.alt_control;
+				self.alt_control = self.settings.alt_control;
+			},
+			6 if !self.gameplay_locked() => {
+				self.settings.difficulty = if up {
+					self.settings.difficulty.next()
+				} else {
+					self.settings.difficulty.prev()
+				};
+				self.world.set_difficulty(self.settings.difficulty);
+			},
+			_ => {}
 		}
 
-		self.powerups.push(powerup);
+		self.display.apply_settings(&self.settings);
 	}
 
-	// pub fn spawn_trap(&mut self, kind: uint) {
-	// 	let mut rng = task_rng();
-	// 	match kind {
-	// 		_ => {
-	// 			let trap = box traps::BearTrap::new(
-	// 							&mut self.display, 
-	// 							(units::Tile(rng.gen_range(1u, POSSIBLE_CHARACTER_TILES))).to_game(),
-	// 							(units::Tile(rng.gen_range(1u, POSSIBLE_CHARACTER_TILES))).to_game()
-	// 						);
-	// 			self.traps.push(trap as Box<traps::Trap>);
-	// 		}
-	// 	};
-	// }
-
-	pub fn spawn_part(&mut self, kind: u32) {
-		let mut rng = rand::thread_rng();
-		let (min, max) = POSSIBLE_PART_RANGE;
-		let mut x = rng.gen_range(0, max);
-		let mut y = rng.gen_range(0, max);
-		if x < 20 {
-			y = rng.gen_range(min, max);
-		} else if y < 20 {
-			x = rng.gen_range(min, max);
+	/// Draws the timer in the top right corner, & how many trap kits the
+	/// player has to the left of it.
+	pub fn draw_status_bar(&mut self) {
+		let units::Pixel(width) = SCREEN_WIDTH.to_pixel();
+		let right = width - (TEXT_GAP / 2) as i32;
+		let score_string = String::from("TIMER: ") + &self.world.timer.to_string();
+		self.display.draw_text(&score_string, right, 2, &STATUS_TEXT);
+		self.display.draw_health(self.world.player.get_health());
+		if self.world.player.trap_kits() > 0 {
+			let (timer_width, _) = self.display.measure_text(&score_string, &STATUS_TEXT);
+			let kits_string = String::from("TRAPS: ") + &self.world.player.trap_kits().to_string();
+			self.display.draw_text(&kits_string, right - (timer_width + TEXT_GAP) as i32, 2, &STATUS_TEXT);
 		}
-		let part = match self.vehicle.get_type() {
-			1 => {
-				match kind {
-					0 => {
-						Box::new( heli::Prop::new(
-							&mut self.display, 
-							units::Tile(x).to_game(),
-							units::Tile(y).to_game()
-						) ) as Box<vehicle::Part>
-					},
-					1 => {
-						Box::new( heli::Windshield::new(
-							&mut self.display, 
-							units::Tile(x).to_game(),
-							units::Tile(y).to_game()
-						) ) as Box<vehicle::Part>
-					},
-					_ => {
-						Box::new( heli::Bar::new(
-							&mut self.display, 
-							units::Tile(x).to_game(),
-							units::Tile(y).to_game()
-						) ) as Box<vehicle::Part>
-					}
+	}
+
+	/// Draws `blocks` of text, each in its own style, one under the other
+	/// & centered across the screen, w/ the whole lot centered down it.
+	fn draw_centered(&mut self, blocks: &[(&str, &text::Style)]) {
+		let units::Pixel(width) = SCREEN_WIDTH.to_pixel();
+		let units::Pixel(height) = SCREEN_HEIGHT.to_pixel();
+		let mut total = 0;
+		for &(block, style) in blocks.iter() {
+			let (_, block_height) = self.display.measure_text(block, style);
+			total = total + block_height + TEXT_GAP;
+		}
+
+		let mut y = (height - total as i32 + TEXT_GAP as i32) / 2;
+		for &(block, style) in blocks.iter() {
+			y = y + (self.display.draw_text(block, width / 2, y, style) + TEXT_GAP) as i32;
+		}
+	}
+
+	/// Draws a menu: `title` across the top, `rows` lined up under it w/
+	/// the `selected` one marked, & `help` along the bottom.
+	fn draw_menu(&mut self, title: &str, rows: &[String], selected: usize, help: &str) {
+		let units::Pixel(width) = SCREEN_WIDTH.to_pixel();
+		let units::Pixel(height) = SCREEN_HEIGHT.to_pixel();
+		let marked: Vec<String> = rows.iter().enumerate().map(|(row, label)| {
+			let marker = if row == selected { "> " } else { "  " };
+			String::from(marker) + label
+		}).collect();
+
+		// the rows start at the same place, w/ the widest of them centered
+		let mut widest = 0;
+		for row in marked.iter() {
+			let (row_width, _) = self.display.measure_text(row, &MENU_TEXT);
+			widest = cmp::max(widest, row_width);
+		}
+		let left = (width - widest as i32) / 2;
+
+		let mut y = TEXT_GAP as i32;
+		y = y + (self.display.draw_text(title, width / 2, y, &HEADING_TEXT) + TEXT_GAP) as i32;
+		for row in marked.iter() {
+			y = y + self.display.draw_text(row, left, y, &MENU_TEXT) as i32;
+		}
+		let (_, help_height) = self.display.measure_text(help, &PROMPT_TEXT);
+		self.display.draw_text(help, width / 2, height - (help_height + TEXT_GAP) as i32, &PROMPT_TEXT);
+	}
+
+	pub fn draw_game_over_screen(&mut self) {
+		let score_string = String::from("YOUR SCORE: ") + &self.world.score.to_string();
+		let seed_string = String::from("SEED: ") + &self.world.seed.to_string();
+		self.draw_centered(&[
+			("GAME OVER MAN!", &TITLE_TEXT),
+			(&score_string[..], &HEADING_TEXT),
+			(&seed_string[..], &PROMPT_TEXT),
+			("PRESS ENTER TO RUN SOME MORE...", &PROMPT_TEXT)
+		]);
+		self.display.switch_buffers();
+	}
+
+	/// The seed the next run should be played with.
+	fn next_seed(&self) -> u32 {
+		match self.fixed_seed {
+			Some(seed) => seed,
+			None       => random::random_seed()
+		}
+	}
+
+	pub fn draw_completion_screen(&mut self) {
+		let level_string = String::from("YOU BEAT LEVEL ") + &self.world.level.to_string() + "!";
+		let score_string = String::from("YOUR SCORE: ") + &self.world.score.to_string();
+		let is_best = self.run_entry.as_ref().map_or(false, |entry| entry.score == self.leaderboard.best() && entry.score == self.world.score);
+		let mut blocks = vec![(&level_string[..], &TITLE_TEXT), (&score_string[..], &HEADING_TEXT)];
+		if is_best {
+			blocks.push(("NEW HIGHSCORE!!", &HEADING_TEXT));
+		}
+		blocks.push(("PRESS ENTER TO RUN SOME MORE...", &PROMPT_TEXT));
+		self.draw_centered(&blocks);
+		self.display.switch_buffers();
+	}
+
+	/// Asks for a name to enter the run under, typed w/ letters, digits &
+	/// space and finished w/ `Return` or a controller's `A` or `Start`
+	/// (`Escape` keeps the default name), under the `title` of the screen
+	/// it was asked from. Blocks until the name is in.
+	fn draw_name_entry_screen(&mut self, event_pump: &mut sdl2::EventPump, title: &str) -> String {
+		let mut name = String::new();
+		loop {
+			for event in event_pump.poll_iter() {
+				if self.handle_hotplug(&event) {
+					continue;
 				}
-			},
-			_ => {
-				match kind {
-					0 => {
-						Box::new( car::Tire::new(
-							&mut self.display, 
-							units::Tile(x).to_game(),
-							units::Tile(y).to_game()
-						) ) as Box<vehicle::Part>
+				match event {
+					Event::KeyDown { keycode: Some(Keycode::Backspace), .. } => { name.pop(); },
+					Event::KeyDown { keycode: Some(key), .. } => {
+						let code = key as i32;
+						// keycodes for letters, digits & space are their (lowercase) ascii
+						let typed = if code >= 'a' as i32 && code <= 'z' as i32 {
+							Some((code as u8 - b'a' + b'A') as char)
+						} else if (code >= '0' as i32 && code <= '9' as i32) || code == ' ' as i32 {
+							Some(code as u8 as char)
+						} else {
+							None
+						};
+						match typed {
+							Some(letter) if name.len() < leaderboard::MAX_NAME_LENGTH => { name.push(letter); },
+							_ => {}
+						}
 					},
-					1 => {
-						Box::new( car::Door::new(
-							&mut self.display, 
-							units::Tile(x).to_game(),
-							units::Tile(y).to_game()
-						) ) as Box<vehicle::Part>
+					Event::KeyUp { keycode: Some(Keycode::Return), .. } |
+					Event::ControllerButtonUp { button: Button::A, .. } |
+					Event::ControllerButtonUp { button: Button::Start, .. } => {
+						let trimmed = name.trim().to_string();
+						return if trimmed.len() > 0 { trimmed } else { leaderboard::DEFAULT_NAME.to_string() };
 					},
-					_ => {
-						Box::new( car::Engine::new(
-							&mut self.display, 
-							units::Tile(x).to_game(),
-							units::Tile(y).to_game()
-						) ) as Box<vehicle::Part>
-					}
+					Event::KeyUp { keycode: Some(Keycode::Escape), .. } => { return leaderboard::DEFAULT_NAME.to_string(); },
+					_ => {}
 				}
 			}
-		};
-		let colliding_tiles = self.map.get_colliding_tiles(&part.damage_rectangle());
-		for tile in colliding_tiles.iter() {
-			if tile.tile_type == map::TileType::Wall {
-				self.spawn_part(kind);
-				return;
-			}
+
+			self.display.clear_buffer();
+			self.world.map.draw_background(&mut self.display);
+			let score_string = String::from("YOUR SCORE: ") + &self.world.score.to_string();
+			let name_string = String::from("NAME: ") + &name + "_";
+			self.draw_centered(&[
+				(title, &TITLE_TEXT),
+				("YOU MADE THE LEADERBOARD!", &HEADING_TEXT),
+				(&score_string[..], &HEADING_TEXT),
+				(&name_string[..], &HEADING_TEXT),
+				("TYPE YOUR NAME AND PRESS ENTER...", &PROMPT_TEXT)
+			]);
+			self.display.switch_buffers();
+
+			std::thread::sleep_ms(1000 / TARGET_FRAMERATE as u32);
 		}
-		self.parts.push(part);
 	}
 
-	pub fn start(&mut self) {
-		self.display.play_music();
-		self.draw_start_screen();
-		self.event_loop();
+	/// Puts the current run on the leaderboard if it scored well enough,
+	/// asking for a name (under `title`, the screen it's asked from) the
+	/// first time it does. A run that goes on to beat
+	/// more levels replaces its own entry. Replays never make the leaderboard.
+	fn record_score(&mut self, event_pump: &mut sdl2::EventPump, title: &str) {
+		if self.replay.is_some() || !self.leaderboard.qualifies(self.world.score) {
+			return;
+		}
+		if self.run_entry.as_ref().map_or(false, |entry| entry.score == self.world.score) {
+			return;
+		}
+
+		let known_name = self.run_name.clone();
+		let name = match known_name {
+			Some(name) => name,
+			None => self.draw_name_entry_screen(event_pump, title)
+		};
+		let entry = leaderboard::Entry::new(&name, self.world.score, self.world.level, self.world.seed);
+		let rank = self.leaderboard.submit(entry.clone(), self.run_entry.as_ref());
+		match rank {
+			Some(rank) => { println!("{} entered the leaderboard at #{} w/ {}", name, rank + 1, entry.score); },
+			None => {}
+		}
+		match self.leaderboard.save() {
+			Ok(_) => {},
+			Err(msg) => { println!("could not save leaderboard: {}", msg); }
+		}
+
+		self.run_name = Some(name);
+		self.run_entry = Some(entry);
 	}
 
-	pub fn draw_start_screen(&mut self) {
-		self.display.clear_buffer();
-		self.map.draw_background(&mut self.display);
-		self.display.switch_buffers();
-		self.display.draw_text("JUST F&#%IN RUN!!!", rect!(45, 50, 550, 200));
-		let score_string = String::from("CURRENT HIGHSCORE: ") + &self.highscore.to_string();
-		self.display.draw_text(&score_string, rect!(120, 300, 400, 100));
-		self.display.draw_text("PRESS ENTER AND START RUNNING...", rect!(160, 500, 300, 50));
-		self.display.switch_buffers();
+	/// Writes the run in progress to the save file. Nothing is saved while a
+	/// replay is playing or before the run has started.
+	fn save_game(&mut self) -> bool {
+		if self.replay.is_some() || self.world.updates == 0 {
+			return false;
+		}
+
+		match self.world.snapshot().save(&self.save_path) {
+			Ok(_) => {
+				println!("saved game to {} (level: {}, score: {})", self.save_path.display(), self.world.level, self.world.score);
+				true
+			},
+			Err(msg) => { println!("{}", msg); false }
+		}
 	}
 
-	pub fn draw_status_bar(&mut self) {
-		let score_string = String::from("TIMER: ") + &self.timer.to_string();
-		self.display.draw_text(&score_string, rect!(500, 0, 100, 30));
-		self.display.draw_health(self.player.get_health());
+	/// Deletes the saved game, if there is one.
+	fn discard_save(&self) {
+		match fs::remove_file(&self.save_path) {
+			Ok(_) => {},
+			Err(ref msg) if msg.kind() == std::io::ErrorKind::NotFound => {},
+			Err(msg) => { println!("could not delete save {}: {}", self.save_path.display(), msg); }
+		}
 	}
 
-	pub fn draw_game_over_screen(&mut self) {
-		self.display.draw_text("GAME OVER MAN!", rect!(45, 100, 550, 200));
-		let score_string = String::from("YOUR SCORE: ") + &self.score.to_string();
-		self.display.draw_text(&score_string, rect!(120, 300, 400, 100));
-		self.display.draw_text("PRESS ENTER TO RUN SOME MORE...", rect!(160, 500, 300, 50));
-		self.display.switch_buffers();
+	/// Replaces the current run w/ the one in the save file.
+	fn resume_game(&mut self) {
+		self.can_resume = false;
+		let saved = match save::SaveGame::load(&self.save_path) {
+			Ok(saved) => { saved },
+			Err(msg) => { println!("{}", msg); return; }
+		};
+
+		// a save is only good for picking the run up once
+		self.discard_save();
+		let world = match world::World::resume(&saved) {
+			Ok(world) => { world },
+			Err(msg) => { println!("{}: {}", self.save_path.display(), msg); return; }
+		};
+		let camera_mode = self.world.camera_mode;
+		self.world = world;
+		self.world.set_camera_mode(camera_mode);
+		println!("resumed game from {} (level: {}, score: {})", self.save_path.display(), self.world.level, self.world.score);
+		self.paused = false;
+		self.display.resume_music();
 	}
 
-	pub fn draw_completion_screen(&mut self) {
-		let level_string = String::from("YOU BEAT LEVEL ") + &self.level.to_string() + "!";
-		self.display.draw_text(&level_string, rect!(45, 100, 550, 200));
-		let score_string = String::from("YOUR SCORE: ") + &self.score.to_string();
-		self.display.draw_text(&score_string, rect!(120, 300, 400, 100));
-		if self.score > self.highscore {
-			self.display.draw_text("NEW HIGHSCORE!!", rect!(120, 400, 400, 60));
-			let score = self.score;
-			self.store_highscore(score);
+	/// Plays back whatever the world asked for during its last update.
+	fn handle_world_events(&mut self) {
+		for event in self.world.drain_events() {
+			match event {
+				world::Event::Sound(name, None) => { self.display.play_sound_effect(&name); },
+				world::Event::Sound(name, Some((x, y))) => {
+					let player = &self.world.player.character;
+					self.display.play_sound_at(&name, x - player.map_center_x(), y - player.map_center_y());
+				},
+				world::Event::LevelStarted => { self.display.free_unused_images(); }
+			}
 		}
-		self.display.draw_text("PRESS ENTER TO RUN SOME MORE...", rect!(160, 500, 300, 50));
-		self.display.switch_buffers();
 	}
 
-	pub fn set_score_and_timer(&mut self) {
-		self.score = self.score + (self.level * 1000) + (self.player.get_health() as i32 * 1000) + self.timer;
-		self.timer = self.timer + LEVEL_2_TIME;
+	/// Opens & closes controllers as they're plugged in & out. Responds
+	/// true if `event` was one of those.
+	fn handle_hotplug(&mut self, event: &Event) -> bool {
+		self.gamepads.handle_event(event)
 	}
 
-	pub fn restart(&mut self) {
-		println!("Restarting game...");
-		self.level = 0;
-		self.new_level(true);
-		self.paused = true;
-		self.score = 0;
-		self.timer = LEVEL_1_TIME;
-	}
+	/// Starts a new frame of input & hands it this frame's key & controller events.
+	///
+	/// While a replay is playing its events stand in for the keyboard &
+	/// controllers (only a real quit key is still honoured). Otherwise the
+	/// SDL event pump is drained and, if a recording is being made, every
+	/// key, button & stick movement is recorded.
+	fn poll_input(&mut self, event_pump: &mut sdl2::EventPump) {
+		self.controller.begin_new_frame();
+		let frame = self.frame;
+		self.frame = self.frame + 1;
+
+		let mut replay_finished = false;
+		let mut signals: Vec<Signal> = Vec::new();
+		for event in event_pump.poll_iter() {
+			if self.handle_hotplug(&event) {
+				continue;
+			}
+			match input::signal_for(event) {
+				Some(signal) => { signals.push(signal); },
+				None => {}
+			}
+		}
 
-	pub fn new_level(&mut self, restart: bool) {
-		println!("Starting new level...");
-		self.level = self.level + 1;
-
-		let mut rng = rand::thread_rng();
-		let enemies_vector: Vec<Box<enemies::Zombie>> = Vec::new();
-		let powerup_vector: Vec<Box<powerups::Powerup>> = Vec::new();
-		let traps_vector: Vec<Box<traps::Trap>> = Vec::new();
-		let killed_vector: Vec<Box<enemies::Zombie>> = Vec::new();
-		let activated_vector: Vec<Box<powerups::Powerup>> = Vec::new();
-		let tripped_vector: Vec<Box<traps::Trap>> = Vec::new();
-		let coll_part_vector: Vec<Box<vehicle::Part>> = Vec::new();
-		let part_vector: Vec<Box<vehicle::Part>> = Vec::new();
-
-		self.player = player::Player::new(
-				&mut self.display,
-				PLAYER_STARTING_X.to_game(),
-				PLAYER_STARTING_Y.to_game()
-			);
-
-		let vehicle_num = rng.gen_range(0, 2);
-		self.vehicle = match vehicle_num {
-			0 => {
-				Box::new( heli::Helicopter::new(
-					&mut self.display, 
-					VEHICLE_STARTING_X.to_game(), 
-					VEHICLE_STARTING_Y.to_game()
-				) ) as Box<vehicle::Vehicle>
-			}, 
-			_ => {
-				Box::new( car::Car::new(
-					&mut self.display, 
-					VEHICLE_STARTING_X.to_game(), 
-					VEHICLE_STARTING_Y.to_game()
-				) ) as Box<vehicle::Vehicle>
+		match self.replay {
+			Some(ref mut replay) => {
+				for event in replay.events_for_frame(frame) {
+					self.controller.apply(event.signal);
+				}
+				for signal in signals {
+					match signal {
+						Signal::Key(KeyAction::Up, key) if self.controller.bindings().keys(Action::Quit).contains(&key) => {
+							self.controller.key_up_event(key);
+						},
+						_ => {},
+					}
+				}
+				replay_finished = replay.is_finished();
+			},
+			None => {
+				for signal in signals {
+					self.controller.apply(signal);
+					match self.recorder {
+						Some(ref mut recorder) => { recorder.record(frame, signal); },
+						None => {}
+					}
+				}
 			}
-		};
+		}
 
-		self.enemies = enemies_vector;
-		self.powerups = powerup_vector;
-		self.traps = traps_vector;
-		self.killed = killed_vector;
-		self.activated = activated_vector;
-		self.tripped = tripped_vector;
-		self.coll_parts = coll_part_vector;
-		self.parts = part_vector;
-		let number_of_zombies = rng.gen_range(20u32, MAX_ENEMIES);
-		for _ in 0.. number_of_zombies {
-		  	self.spawn_zombie(rng.gen_range(1u32, 5u32), (units::Game(0.0), units::Game(0.0)));
+		// hand control back to the keyboard once the recording runs out
+		if replay_finished {
+			println!("replay finished at frame {}", frame);
+			self.replay = None;
+			self.controller.set_bindings(controls::Bindings::load());
 		}
-		let number_of_powerups = rng.gen_range(0u32, MAX_POWERUPS);
-		for _ in 0.. number_of_powerups {
-			self.spawn_powerup(rng.gen_range(1u32, 7u32));
+	}
+
+	/// How long a frame should take: as long as the frame rate cap asks,
+	/// except for recordings & replays which keep to `TARGET_FRAMERATE`.
+	fn frame_delay(&self) -> units::Millis {
+		if self.gameplay_locked() {
+			units::Millis(1000 / TARGET_FRAMERATE as i64)
+		} else {
+			units::Millis(self.settings.frame_millis())
 		}
-		// let number_of_traps = rng.gen_range(0u, MAX_TRAPS);
-		// for _ in range(0, number_of_traps) {
-		// 	self.spawn_trap(1);
-		// }
+	}
 
-		for i in 0.. LEVEL_PARTS {
-			self.spawn_part(i);
+	/// How much time the world is told has passed this frame.
+	///
+	/// Recordings & replays step the world by exactly one frame so the
+	/// same input always produces the same run.
+	fn step_time(&self, elapsed_time: units::Millis, frame_delay: units::Millis) -> units::Millis {
+		if self.recorder.is_some() || self.replay.is_some() {
+			frame_delay
+		} else {
+			cmp::min(elapsed_time, MAX_FRAME_TIME)
 		}
+	}
 
-		self.paused = false;
-		self.updates = 0;
-		self.freeze_counter = 0;
+	/// How long a frame should take on the wall clock.
+	/// Replays may run faster than real time.
+	fn frame_deadline(&self, frame_delay: units::Millis) -> units::Millis {
+		match self.replay {
+			Some(_) if self.replay_speed == 0 => units::Millis(0),
+			Some(_) => {
+				let units::Millis(fd) = frame_delay;
+				units::Millis(fd / self.replay_speed as i64)
+			},
+			None => frame_delay
+		}
+	}
 
-		// create new level map
-		let new_map = map::Map::load_map(&mut self.display, self.level);
-		self.map = new_map;
+	/// Writes out the recording, if one is being made.
+	fn save_recording(&self) {
+		match (&self.recorder, &self.record_path) {
+			(&Some(ref recorder), &Some(ref path)) => {
+				match recorder.save(path) {
+					Ok(_) => { println!("saved replay to {}", path); },
+					Err(msg) => { println!("{}", msg); }
+				}
+			},
+			_ => {}
+		}
 	}
 
 	/// Polls current input events & dispatches them to the engine.
@@ -499,7 +786,7 @@ impl<'e> Game<'e> {
 	/// until its next frame deadline.
 	fn event_loop(&mut self) {
 		// event loop control
-		let frame_delay = units::Millis(1000 / TARGET_FRAMERATE as i64);
+		let mut frame_delay = self.frame_delay();
 		let start_time = PreciseTime::now();
 		let mut last_update_time = units::Millis(start_time.to(PreciseTime::now()).num_milliseconds());
 		
@@ -510,68 +797,89 @@ impl<'e> Game<'e> {
 			Err(msg) => { panic!(msg) }
 		};
 
-		while running && !self.completed_lvl {
+		while running && !self.world.completed_lvl {
 			let start_time_ms = units::Millis(start_time.to(PreciseTime::now()).num_milliseconds());
-			self.controller.begin_new_frame();
-
-			// drain event queue once per frame
-			// ideally should do in separate task
-			for event in event_pump.poll_iter() {
-				match event {
-					Event::KeyDown { keycode, .. } => {
-						self.controller.key_down_event(keycode.unwrap());
-					},
-					Event::KeyUp { keycode, .. } => {
-						self.controller.key_up_event(keycode.unwrap());
-					},
-					_ => {},
-				}
-			}
+			self.poll_input(&mut event_pump);
 
-			// Handle exit game
-			if self.controller.was_key_released(Keycode::Escape) {
+			// Handle exit game, keeping the run to resume next time
+			if self.controller.was_released(Action::Quit) {
+				self.save_game();
 				running = false;
 			}
 
 			// Handle paused game
-			if self.controller.was_key_released(Keycode::Return) {
+			if self.controller.was_released(Action::Pause) {
 				if self.paused {
+					// once a new run is under way there's nothing to pick back up
+					self.can_resume = false;
 					self.paused = false;
 					self.display.resume_music();
 				} else {
 					self.paused = true;
 					self.display.pause_music();
+					self.draw_pause_screen(false);
+				}
+			}
+
+			// Handle the pause menu
+			if self.paused && self.controller.was_released(Action::Save) {
+				if self.save_game() {
+					self.draw_pause_screen(true);
+				}
+			}
+			if self.paused && self.can_resume && self.controller.was_released(Action::Resume) {
+				self.resume_game();
+			}
+			if self.paused && self.replay.is_none() && self.controller.was_released(Action::Options) {
+				self.draw_options_screen(&mut event_pump);
+				frame_delay = self.frame_delay();
+				if self.world.updates == 0 {
+					self.draw_start_screen();
+				} else {
+					self.draw_pause_screen(false);
 				}
 			}
 
 			// Handle alternate control method
-			if self.controller.was_key_released(Keycode::LShift) {
+			if self.controller.was_released(Action::ToggleControlMode) {
 				self.alt_control = !self.alt_control;
 			}
 
-			// Handle player movement
-			if self.controller.was_key_released(Keycode::Left) || self.controller.was_key_released(Keycode::Right) {
-				self.player.stop_moving_horizontally(self.alt_control);
+			// Handle dropping a trap kit
+			if !self.paused && self.controller.was_released(Action::UseItem) {
+				self.world.place_trap();
+			}
+
+			// Handle player movement, from the keys, D-pad or left stick
+			let (stick_x, stick_y) = self.controller.stick();
+			let (stick_released_x, stick_released_y) = self.controller.was_stick_released();
+			let player = &mut self.world.player;
+			if self.controller.was_released(Action::MoveLeft) || self.controller.was_released(Action::MoveRight) || stick_released_x {
+				player.stop_moving_horizontally(self.alt_control);
 			} 
-			if self.controller.is_key_held(Keycode::Left) && self.controller.is_key_held(Keycode::Right) {
-				self.player.stop_moving_horizontally(self.alt_control);
-			} else if self.controller.is_key_held(Keycode::Left) {
-				self.player.start_moving_left();
-			} else if self.controller.is_key_held(Keycode::Right) {
-				self.player.start_moving_right();
+			if self.controller.is_held(Action::MoveLeft) && self.controller.is_held(Action::MoveRight) {
+				player.stop_moving_horizontally(self.alt_control);
+			} else if self.controller.is_held(Action::MoveLeft) {
+				player.start_moving_left();
+			} else if self.controller.is_held(Action::MoveRight) {
+				player.start_moving_right();
+			} else if stick_x != 0.0 {
+				player.steer_horizontally(stick_x);
 			} else {
-				self.player.stop_moving_horizontally(self.alt_control);
+				player.stop_moving_horizontally(self.alt_control);
 			}
 
-			if self.controller.was_key_released(Keycode::Up) || self.controller.was_key_released(Keycode::Down) {
-				self.player.stop_moving_vertically(self.alt_control);
+			if self.controller.was_released(Action::MoveUp) || self.controller.was_released(Action::MoveDown) || stick_released_y {
+				player.stop_moving_vertically(self.alt_control);
 			} 
-			if self.controller.is_key_held(Keycode::Up) && self.controller.is_key_held(Keycode::Down) {
-				self.player.stop_moving_vertically(self.alt_control);
-			} else if self.controller.is_key_held(Keycode::Up) {
-				self.player.start_moving_up();
-			} else if self.controller.is_key_held(Keycode::Down) {
-				self.player.start_moving_down();
+			if self.controller.is_held(Action::MoveUp) && self.controller.is_held(Action::MoveDown) {
+				player.stop_moving_vertically(self.alt_control);
+			} else if self.controller.is_held(Action::MoveUp) {
+				player.start_moving_up();
+			} else if self.controller.is_held(Action::MoveDown) {
+				player.start_moving_down();
+			} else if stick_y != 0.0 {
+				player.steer_vertically(stick_y);
 			}
 
 			// inform actors of how much time has passed since last frame
@@ -580,75 +888,80 @@ impl<'e> Game<'e> {
 		
 			// only update if not in paused state
 			if !self.paused {
-				self.update(cmp::min(elapsed_time, MAX_FRAME_TIME));
+				let step_time = self.step_time(elapsed_time, frame_delay);
+				self.world.update(step_time);
+				self.handle_world_events();
 				last_update_time = current_time_ms;
 
-				// draw if game has been started
-				if self.updates != 0 {
+				if self.world.is_game_over() {
+					println!("game over at frame {} (seed: {}, score: {})", self.frame, self.world.seed, self.world.score);
+					// draw game over screen, store score from there and start a new game
+					self.discard_save();
+					self.display.clear_buffer();
+					self.draw();
+					self.draw_game_over_screen();
+					self.record_score(&mut event_pump, "GAME OVER MAN!");
+					self.display.clear_buffer();
+					self.draw();
+					self.draw_game_over_screen();
+					self.run_name = None;
+					self.run_entry = None;
+					let seed = self.next_seed();
+					self.world.restart(seed);
+					self.paused = true;
+				} else {
 					self.display.clear_buffer(); // clear back-buffer
 					self.draw();
 					self.draw_status_bar();
 					self.display.switch_buffers();
 				}
-
-				// decrement timer
-				self.timer = self.timer - 1;	
 			}
 
 			// throttle event-loop based on iteration time vs frame deadline
 			let iter_time = units::Millis(start_time.to(PreciseTime::now()).num_milliseconds()) - start_time_ms;
-			let next_frame_time: u64 = if frame_delay > iter_time { 
-				let (units::Millis(fd), units::Millis(it)) = (frame_delay, iter_time);
+			let frame_deadline = self.frame_deadline(frame_delay);
+			let next_frame_time: u64 = if frame_deadline > iter_time { 
+				let (units::Millis(fd), units::Millis(it)) = (frame_deadline, iter_time);
 				(fd - it) as u64
 			} else { 0 as u64 };
-			
-			self.updates = self.updates + 1;
 
 			std::thread::sleep_ms(next_frame_time as u32);
 
-			if (self.completed_lvl && running) {
-				let mut cinematic_counter = LEVEL_1_CINEMATIC_FRAMES;
-				while self.completed_lvl && running {
+			if (self.world.completed_lvl && running) {
+				println!("completed level {} at frame {} (seed: {}, score: {})", self.world.level, self.frame, self.world.seed, self.world.score);
+				let mut cinematic_counter = world::LEVEL_1_CINEMATIC_FRAMES;
+				while self.world.completed_lvl && running {
 					let start_time_ms = units::Millis(start_time.to(PreciseTime::now()).num_milliseconds());
 					// inform actors of how much time has passed since last frame
 					let current_time_ms = units::Millis(start_time.to(PreciseTime::now()).num_milliseconds());
 					let elapsed_time    = current_time_ms - last_update_time;
 					let mut show_completion_screen = true;
 
-					self.controller.begin_new_frame();
-
-					// drain event queue once per frame
-					// ideally should do in separate task
-					for event in event_pump.poll_iter() {
-						match event {
-							Event::KeyDown { keycode, .. } => {
-								self.controller.key_down_event(keycode.unwrap());
-							},
-							Event::KeyUp { keycode, .. } => {
-								self.controller.key_up_event(keycode.unwrap());
-							},
-							_ => {},
-						}
-					}
+					self.poll_input(&mut event_pump);
 
 					// Handle exit game
-					if self.controller.was_key_released(Keycode::Escape) {
-						self.completed_lvl = false;
+					if self.controller.was_released(Action::Quit) {
+						self.world.completed_lvl = false;
 					}
 
 					// Handle paused game
-					if self.controller.was_key_released(Keycode::Return) {
+					if self.controller.was_released(Action::Pause) {
 						if cinematic_counter < 0 {
-							self.completed_lvl = false;
-							self.new_level(false);
+							self.world.new_level(false);
+							self.paused = false;
 							break;
 						} 
 					}
 				
 					// only update if not in paused state
-					self.update_cinematic(cmp::min(elapsed_time, MAX_FRAME_TIME));
+					let step_time = self.step_time(elapsed_time, frame_delay);
+					self.world.update_cinematic(step_time);
 					last_update_time = current_time_ms;
 
+					if cinematic_counter == 0 {
+						let level_string = String::from("YOU BEAT LEVEL ") + &self.world.level.to_string() + "!";
+						self.record_score(&mut event_pump, &level_string);
+					}
 					if cinematic_counter > 0 {
 						self.display.clear_buffer(); // clear back-buffer
 						self.draw_cinematic(cinematic_counter);
@@ -660,8 +973,9 @@ impl<'e> Game<'e> {
 
 					// throttle event-loop based on iteration time vs frame deadline
 					let iter_time = units::Millis(start_time.to(PreciseTime::now()).num_milliseconds()) - start_time_ms;
-					let next_frame_time: u64 = if frame_delay > iter_time { 
-						let (units::Millis(fd), units::Millis(it)) = (frame_delay, iter_time);
+					let frame_deadline = self.frame_deadline(frame_delay);
+					let next_frame_time: u64 = if frame_deadline > iter_time { 
+						let (units::Millis(fd), units::Millis(it)) = (frame_deadline, iter_time);
 						(fd - it) as u64
 					} else { 0 as u64 };
 					
@@ -681,434 +995,82 @@ impl<'e> Game<'e> {
 			
 		}
 
+		self.save_recording();
 	}
 
 	// Instructs our actors to draw their current state to the screen.
 	fn draw(&mut self) {
+		let world = &self.world;
+		let camera = world.map.get_camera();
+		let view = world.map.view_rectangle();
+
 		// background
-		self.map.draw_background(&mut self.display);
+		world.map.draw_background(&mut self.display);
 
 		// foreground
-		if self.map.on_screen(self.vehicle.get_map_x(), self.vehicle.get_map_y()) {
-			self.vehicle.draw(&mut self.display);
-		}
-		for part in self.parts.iter() {
-			if self.map.on_screen(part.get_map_x(), part.get_map_y()) { 
-				part.draw(&mut self.display); 
-			} 
+		if world.map.on_screen(world.vehicle.get_map_x(), world.vehicle.get_map_y()) {
+			world.vehicle.draw(&mut self.display, camera);
 		}
-		for powerup in self.powerups.iter() { 
-			if self.map.on_screen(powerup.get_map_x(), powerup.get_map_y()) { 
-				powerup.draw(&mut self.display); 
+		for i in world.part_grid.query(&view) {
+			let part = &world.parts[i];
+			if world.map.on_screen(part.get_map_x(), part.get_map_y()) { 
+				part.draw(&mut self.display, camera); 
 			} 
 		}
-		for trap in self.traps.iter() { 
-			if self.map.on_screen(trap.get_map_x(), trap.get_map_y()) { 
-				trap.draw(&mut self.display); 
+		for i in world.powerup_grid.query(&view) { 
+			let powerup = &world.powerups[i];
+			if world.map.on_screen(powerup.get_map_x(), powerup.get_map_y()) { 
+				powerup.draw(&mut self.display, camera); 
 			} 
 		}
-		self.draw_zombies();
-		self.player.draw(&mut self.display);
-		let mut kill_list: Vec<Box<enemies::Zombie>> = Vec::new();
-		let mut active_list: Vec<Box<powerups::Powerup>> = Vec::new();
-		let mut tripped_list: Vec<Box<traps::Trap>> = Vec::new();
-		for _ in 0.. self.activated.len() { 
-			match self.activated.pop() {
-				Some(activated) => {
-					let mut mut_activated = activated;
-					// draw 'bullet' for kill zombie 
-					if mut_activated.get_type() == 2 {
-						let killed_enemy = self.killed.get(self.killed.len() - 1).unwrap();
-						let (units::Game(player_x), units::Game(player_y)) = (self.player.character.center_x(), self.player.character.center_y());
-						let (units::Game(enemy_x), units::Game(enemy_y)) = (killed_enemy.get_map_x(), killed_enemy.get_map_y());
-						self.display.draw_line( (player_x as i32, player_y as i32), (enemy_x as i32, enemy_y as i32) );
-					} else {
-						mut_activated.draw(&mut self.display);
-					}
-					if !mut_activated.is_finished() {
-						active_list.push(mut_activated);
-					}
-				},
-				None => {}
-			}; 
-		}
-		for _ in 0.. self.tripped.len() { 
-			match self.tripped.pop() {
-				Some(tripped) => {
-					let mut mut_tripped = tripped;
-					mut_tripped.draw(&mut self.display);
-					if !mut_tripped.is_finished() {
-						tripped_list.push(mut_tripped);
-					}
-				},
-				None => {}
-			}; 
-		}
-		for _ in 0.. self.killed.len() { 
-			match self.killed.pop() {
-				Some(killed) => {
-					let mut mut_killed = killed;
-					if self.map.on_screen(mut_killed.get_map_x(), mut_killed.get_map_y()) {
-						mut_killed.draw(&mut self.display);
-					}
-					if !mut_killed.is_killed() {
-						kill_list.push(mut_killed);
-					}
-				},
-				None => {}
-			}; 
-		}
-		self.killed = kill_list;
-		self.activated = active_list;
-		self.tripped = tripped_list;
-		self.map.draw(&mut self.display);
-	}
-
-	fn draw_zombies(&mut self) {
-		for enemy in self.enemies.iter() { 
-			if self.map.on_screen(enemy.get_map_x(), enemy.get_map_y()) {
-			 	enemy.draw(&mut self.display); 
+		for i in world.trap_grid.query(&view) { 
+			let trap = &world.traps[i];
+			if world.map.on_screen(trap.get_map_x(), trap.get_map_y()) { 
+				trap.draw(&mut self.display, camera); 
 			} 
 		}
-	}
-
-	fn draw_cinematic(&mut self, counter: i32) {
-		// background
-		self.map.draw_background(&mut self.display);
-		self.map.draw(&mut self.display);
-		self.vehicle.update_for_cinematic();
-		self.vehicle.draw(&mut self.display);
-	}
-
-	/// Passes the current time in milliseconds to our underlying actors.
-	fn update(&mut self, elapsed_time: units::Millis) {
-		self.map.update(elapsed_time);
-		if self.freeze_counter == 0 {
-			for i in 0u32.. self.enemies.len() as u32 { 
-				let enemy = self.enemies.get_mut(i as usize).unwrap();
-				let (player_x, player_y) = self.player.get_follow_coords();
-				enemy.set_acceleration(player_x, player_y); 
-				enemy.update(elapsed_time, &self.map); 
-			}
-		} else {
-			self.freeze_counter = self.freeze_counter - 1;
-		}
-		self.player.update(elapsed_time, &self.map);
-		self.map.set_page(self.player.character.map_center_x(), self.player.character.map_center_y());
-		for i in 0.. self.killed.len() { self.killed.get_mut(i).unwrap().update(elapsed_time, &self.map) }
-		for i in 0.. self.activated.len() { self.activated.get_mut(i).unwrap().update(elapsed_time, &self.map) }
-		for i in 0.. self.tripped.len() { self.tripped.get_mut(i).unwrap().update(elapsed_time, &self.map) }
-		for i in 0.. self.powerups.len() {
-			let powerup = self.powerups.get_mut(i);
-		    // change debuff status every 10 updates
-			if self.updates % 20 == 0 {
-				powerup.unwrap().toggle_debuff();
-			}
-		}
-		self.vehicle.update(elapsed_time);
-
-		let mut collidedWithZombie = false;
-		if !self.player.is_immune() {
-			for i in 0.. self.enemies.len() { 
-				if self.enemies.get(i).unwrap().damage_rectangle().collides_with_player(&self.player.character.damage_rectangle()) {
-					if self.player.has_bat() || self.player.is_teleporting() {
-						let enemy = self.enemies.remove(i);
-						self.display.play_sound_effect(6);
-						let mut mut_enemy = enemy;
-						mut_enemy.kill_zombie();
-						self.killed.push(mut_enemy);
-						self.player.take_bat();
-						self.timer = self.timer + 100;
-					}
-				 	else {
-				 		collidedWithZombie = true;
-				 	}
-				 	break;
-				}
-			}
-		}
-
-		// Collect part
-		if self.coll_parts.len() == 0 {
-			for i in 0.. self.parts.len() { 
-				if self.parts.get(i).unwrap().damage_rectangle().collides_with(&self.player.character.damage_rectangle()) {
-					let part = self.parts.remove(i);
-					self.display.play_sound_effect(7);
-					self.coll_parts.push(part);
-					break;
-				}
-			}
+		for i in world.enemies_on_screen() { 
+		 	world.enemies[i].draw(&mut self.display, camera); 
 		}
-
-		// Apply parts to vehicle
-		if self.vehicle.damage_rectangle().collides_with(&self.player.character.damage_rectangle()) {
-			match self.coll_parts.pop() {
-				Some(part) => { self.vehicle.add_part(part.part_type()); },
-				None       => {}
-			};
-		}
-
-		// Apply powerup
-		let mut counter = 0;
-		let mut hit_powerup = false;
-		for powerup in self.powerups.iter_mut() { 
-			if powerup.damage_rectangle().collides_with(&self.player.character.damage_rectangle()) {
-				hit_powerup = true;
-			 	break;
-			}
-			counter = counter + 1;
-		}
-		if hit_powerup {
-			self.apply_powerup(counter);
-		}
-
-		// Activate traps
-		let mut counter = 0;
-		let mut player_hit_trap = false;
-		let mut zombie_hit_trap = false;
-		for trap in self.traps.iter_mut() { 
-			// check if player hit trap
-			if trap.damage_rectangle().collides_with_player(&self.player.character.damage_rectangle()) {
-				player_hit_trap = true;
-				break;
-			}
-			// check if zombies hit trap
-			for i in 0.. self.enemies.len() {
-				if self.enemies.get(i).unwrap().damage_rectangle().collides_with(&trap.damage_rectangle()) {
-					let enemy = self.enemies.remove(i as usize);
-					let mut mut_enemy = enemy;
-					mut_enemy.kill_zombie();
-					self.killed.push(mut_enemy);
-					zombie_hit_trap = true;
-					break;
+		world.player.draw(&mut self.display, camera);
+		for activated in world.activated.iter() {
+			// draw 'bullet' to the zombie a tracer powerup killed
+			if world.powerup_defs.get(activated.get_type() as u32).map_or(false, |def| def.tracer) {
+				match world.killed.last() {
+					Some(killed_enemy) => {
+						let (units::Game(player_x), units::Game(player_y)) = camera.to_screen(world.player.character.map_center_x(), world.player.character.map_center_y());
+						let (units::Game(enemy_x), units::Game(enemy_y)) = camera.to_screen(killed_enemy.get_map_x(), killed_enemy.get_map_y());
+						self.display.draw_line( (player_x as i32, player_y as i32), (enemy_x as i32, enemy_y as i32) );
+					},
+					None => {}
 				}
+			} else {
+				activated.draw(&mut self.display, camera);
 			}
-			if zombie_hit_trap {
-				break;
-			}
-			counter = counter + 1;
 		}
-		if player_hit_trap || zombie_hit_trap {
-			self.activate_trap(counter);
-		}
-
-		if self.vehicle.is_built() {
-			self.set_score_and_timer();
-			self.completed_lvl = true;
-		}
-
-		// ran out of time
-		if self.timer == 0 {
-			self.draw_game_over_screen();
-			self.restart();
-		}
-
-		if collidedWithZombie || player_hit_trap {
-			self.display.play_sound_effect(6);
-			match self.player.get_health() {
-				health if health > 1 => {
-					self.player.hit_player();
-					self.player.start_immunity();
-				},
-				_ => {
-					self.player.character.kill_character();
-					self.draw();
-					// draw game over screen store score and start a new game
-					self.draw_game_over_screen();
-					self.restart();
-				}
-			}
+		for tripped in world.tripped.iter() {
+			tripped.draw(&mut self.display, camera);
 		}
-
-		// populate cloud zombies
-		if self.updates != 0 {
-			let mut new_zombies = false;
-			let mut zombie_location: (units::Game, units::Game) = (units::Game(0.0), units::Game(0.0));
-			for enemy in self.enemies.iter() { 
-				if enemy.zombie_type() == 4 && self.updates % 300 == 0 {
-				  new_zombies = true;
-				  zombie_location = enemy.get_target();
-				  break;
-				}
-			}
-			if new_zombies {
-				self.spawn_zombie(4, zombie_location);
+		for killed in world.killed.iter() {
+			if world.map.on_screen(killed.get_map_x(), killed.get_map_y()) {
+				killed.draw(&mut self.display, camera);
 			}
 		}
+		world.map.draw(&mut self.display);
 	}
 
-	fn update_cinematic(&mut self, elapsed_time: units::Millis) {
-		self.map.update(elapsed_time);
-		for i in 0u32.. self.enemies.len() as u32 { 
-			let enemy = self.enemies.get_mut(i as usize).unwrap();
-			let (player_x, player_y) = self.player.get_follow_coords();
-			enemy.set_acceleration(player_x, player_y); 
-			enemy.update(elapsed_time, &self.map); 
-		}
-		self.vehicle.update(elapsed_time);
-	}
-
-	fn apply_powerup(&mut self, index: u32) {
-		let powerup = self.powerups.remove(index as usize);
-		let kind = powerup.get_type();
-		let length = self.enemies.len();
-		match kind {
-			// kill next zombie you touch without dying
-			1 => { 
-				println!("CRICKET BAT"); 
-				self.display.play_sound_effect(3); 
-				self.player.give_bat(); 
-			},
-			// kill random zombie
-			2 => { 
-				println!("KILL ZOMBIE"); 
-				if self.enemies.len() > 0 {
-					self.display.play_sound_effect(0);
-					let mut rng = rand::thread_rng(); 
-					let killed = self.enemies.remove( rng.gen_range(0u32, length as u32) as usize );
-					let mut mut_enemy = killed;
-					self.activated.push(powerup);
-					mut_enemy.kill_zombie();
-					self.killed.push(mut_enemy);
-				}
-				self.timer = self.timer + 100;
-			},
-			// wipe out all zombies in given range
-			3 => { 
-				println!("WIPE OUT");
-				self.display.play_sound_effect(1); 
-				let mut new_enemies: Vec<Box<enemies::Zombie>> = Vec::new();
-				for _ in 0.. self.enemies.len() { 
-					let enemy = self.enemies.pop();
-					match enemy {
-						Some(enemy) => {
-							if self.player.character.distance( enemy.get_map_x(), enemy.get_map_y() ) > 200.0 {
-						    	new_enemies.push(enemy);
-						    } else {
-						    	let mut mut_enemy = enemy;
-						    	mut_enemy.kill_zombie();
-						    	self.killed.push(mut_enemy);
-						    }
-						},
-						None => {}
-					}
-				}
-				self.timer = self.timer + (self.killed.len() as i32 * 500);
-				self.enemies = new_enemies;
-				let mut mut_powerup = powerup;
-				mut_powerup.set_timer();
-				self.activated.push(mut_powerup);
-			},
-			// freeze all zombies
-			4 => { 
-				println!("FREEZE"); 
-				self.display.play_sound_effect(3);
-				self.freeze_counter = 300; 
-			},
-			5 => { 
-				println!("TELEPORT"); 
-				self.display.play_sound_effect(3); 
-				// teleport player to helicopter
-				self.player.character.map_x = self.vehicle.get_map_x(); 
-				self.player.character.map_y = self.vehicle.get_map_y() + units::Tile(2).to_game(); 
-				self.player.start_teleport_timer();
-				let mut mut_powerup = powerup;
-				mut_powerup.set_timer();
-				self.activated.push(mut_powerup);
-			},
-			_ => { 
-				if powerup.is_debuff() {
-					println!("SUCKS TO BE YOU"); 
-					self.display.play_sound_effect(4);
-					let mut rng = rand::thread_rng();
-					let mut new_enemies: Vec<Box<enemies::Zombie>> = Vec::new();
-					for _ in 0.. self.enemies.len() { 
-						let enemy = self.enemies.pop();
-						match enemy {
-							Some(enemy) => {
-								if rng.gen_range(1u32, 11u32) >= 3 {
-									let crazy_zombie = Box::new( enemies::CrazyZombie::new(
-										&mut self.display, 
-										enemy.get_map_x(),
-										enemy.get_map_y()
-									) );
-							    	new_enemies.push(crazy_zombie);
-							    } else {
-							    	new_enemies.push(enemy);
-							    }
-							},
-							None => {}
-						}
-					}
-					self.enemies = new_enemies;
-				} else {
-					println!("NUKE"); 
-					self.display.play_sound_effect(2);
-					let mut new_enemies: Vec<Box<enemies::Zombie>> = Vec::new();
-					for _ in 0.. self.enemies.len() {
-						match self.enemies.pop() {
-							Some(enemy) => {
-								if self.map.on_screen(enemy.get_map_x(), enemy.get_map_y()) {
-									let mut mut_enemy = enemy;
-									mut_enemy.kill_zombie();
-									self.killed.push(mut_enemy);
-								} else {
-									new_enemies.push(enemy);
-								}
-							},
-							None => {}
-						};
-					}
-					let mut mut_powerup = powerup;
-					mut_powerup.set_timer();
-					self.activated.push(mut_powerup);
-					self.timer = self.timer + (self.killed.len() as i32 * 100);
-					self.enemies = new_enemies;
-				} 
-			}
-		};
-	}
-
-	fn activate_trap(&mut self, index: u32) {
-		let trap = self.traps.remove(index as usize);
-		let kind = trap.get_type();
-		match kind {
-			// Activate bear trap
-			_ => { 
-				println!("BEAR TRAP");
-				self.display.play_sound_effect(5);
-				let mut mut_trap = trap;
-				mut_trap.set_timer();
-				self.tripped.push(mut_trap);
-			}
-		};
-	}
-
-	fn get_highscore() -> i32 {
-		match File::open(&Path::new("highscore.txt")) {
-		    Ok(file) => { 
-		    	let mut score = String::new();
-		    	let mut f = file;
-		    	f.read_to_string(&mut score);
-		    	match score.parse::<i32>() {
-		    		Ok(s) => { s },
-		    		Err(msg) => { panic!(msg) }
-		    	}
-		    }, // succeeded
-		    Err(e) => { println!("failed to get highscore: {}", e); 0 }
+	fn draw_zombies(&mut self) {
+		let camera = self.world.map.get_camera();
+		for i in self.world.enemies_on_screen() { 
+		 	self.world.enemies[i].draw(&mut self.display, camera); 
 		}
 	}
 
-	fn store_highscore(&mut self, new_score: i32) {
-		if new_score > self.highscore {
-			match File::create(&Path::new("highscore.txt")) {
-			    Ok(file) => { 
-			    	let mut f = file;
-			    	f.write_all(new_score.to_string().as_bytes());
-			    	self.highscore = new_score;
-			    }, // succeeded
-			    Err(e) => println!("failed to write highscore: {}", e)
-			}
-		}
+	#[allow(unused_variable)]
+	fn draw_cinematic(&mut self, counter: i32) {
+		// background
+		self.world.map.draw_background(&mut self.display);
+		self.world.map.draw(&mut self.display);
+		self.world.vehicle.draw(&mut self.display, self.world.map.get_camera());
 	}
 }
@@ -0,0 +1,258 @@
+use std::vec::Vec;
+
+use sdl2;
+use sdl2::controller::{Axis, Button, GameController};
+use sdl2::event::Event;
+
+use game::controls::Action;
+
+/// How far, out of `i16::MAX`, a stick has to be pushed before it counts.
+pub static DEADZONE: i32 = 8000;
+
+// the stick's full travel
+static AXIS_MAX: i32 = 32767;
+
+/// What each button does. Buttons aren't rebindable: the D-pad moves,
+/// `Start` pauses, `A` uses an item (& confirms in menus), `Y` toggles the
+/// control method & `Back` brings up the options.
+pub static BUTTON_ACTIONS: [(Button, Action); 8] = [
+	(Button::DPadLeft,  Action::MoveLeft),
+	(Button::DPadRight, Action::MoveRight),
+	(Button::DPadUp,    Action::MoveUp),
+	(Button::DPadDown,  Action::MoveDown),
+	(Button::Start,     Action::Pause),
+	(Button::A,         Action::UseItem),
+	(Button::Y,         Action::ToggleControlMode),
+	(Button::Back,      Action::Options)
+];
+
+/// Every action `button` sets off.
+pub fn actions_for(button: Button) -> Vec<Action> {
+	BUTTON_ACTIONS.iter()
+		.filter(|&&(bound, _)| bound == button)
+		.map(|&(_, action)| action)
+		.collect()
+}
+
+/// The buttons bound to `action`.
+pub fn buttons_for(action: Action) -> Vec<Button> {
+	BUTTON_ACTIONS.iter()
+		.filter(|&&(_, bound)| bound == action)
+		.map(|&(button, _)| button)
+		.collect()
+}
+
+/// How far an axis reading of `value` is pushed, from -1.0 to 1.0, once
+/// the deadzone is taken out. Readings inside the deadzone are 0.0.
+pub fn axis_throttle(value: i16) -> f64 {
+	let value = value as i32;
+	if value.abs() <= DEADZONE {
+		return 0.0;
+	}
+
+	let travel = (value.abs() - DEADZONE) as f64 / (AXIS_MAX - DEADZONE) as f64;
+	travel.min(1.0) * value.signum() as f64
+}
+
+// buttons & axes by the names replay files use for them
+static BUTTON_NAMES: [(&'static str, Button); 15] = [
+	("a", Button::A), ("b", Button::B), ("x", Button::X), ("y", Button::Y),
+	("back", Button::Back), ("guide", Button::Guide), ("start", Button::Start),
+	("leftstick", Button::LeftStick), ("rightstick", Button::RightStick),
+	("leftshoulder", Button::LeftShoulder), ("rightshoulder", Button::RightShoulder),
+	("dpup", Button::DPadUp), ("dpdown", Button::DPadDown),
+	("dpleft", Button::DPadLeft), ("dpright", Button::DPadRight)
+];
+
+static AXIS_NAMES: [(&'static str, Axis); 6] = [
+	("leftx", Axis::LeftX), ("lefty", Axis::LeftY),
+	("rightx", Axis::RightX), ("righty", Axis::RightY),
+	("lefttrigger", Axis::TriggerLeft), ("righttrigger", Axis::TriggerRight)
+];
+
+pub fn button_name(button: Button) -> &'static str {
+	BUTTON_NAMES.iter().find(|&&(_, named)| named == button).map_or("?", |&(name, _)| name)
+}
+
+pub fn button_from_name(name: &str) -> Option<Button> {
+	BUTTON_NAMES.iter().find(|&&(named, _)| named == name).map(|&(_, button)| button)
+}
+
+pub fn axis_name(axis: Axis) -> &'static str {
+	AXIS_NAMES.iter().find(|&&(_, named)| named == axis).map_or("?", |&(name, _)| name)
+}
+
+pub fn axis_from_name(name: &str) -> Option<Axis> {
+	AXIS_NAMES.iter().find(|&&(named, _)| named == name).map(|&(_, axis)| axis)
+}
+
+/// What `Gamepads` needs of an open controller.
+pub trait Pad {
+	fn name(&self) -> String;
+	/// Responds false once the controller has been unplugged.
+	fn attached(&self) -> bool;
+}
+
+impl Pad for GameController {
+	fn name(&self) -> String {
+		GameController::name(self).to_string()
+	}
+
+	fn attached(&self) -> bool {
+		GameController::attached(self)
+	}
+}
+
+/// Opens the controller at a device index.
+pub type Opener<P> = Box<FnMut(u32) -> Result<P, String>>;
+
+/// The game controllers plugged in. SDL reports every controller already
+/// plugged in at start-up as added, so they all arrive through `added()`.
+pub struct Gamepads<P = GameController> {
+	open:        Option<Opener<P>>,
+	controllers: Vec<P>
+}
+
+impl Gamepads<GameController> {
+	/// Starts listening for controllers. Without the subsystem the game
+	/// carries on w/ just the keyboard.
+	pub fn new(context: &sdl2::Sdl) -> Gamepads<GameController> {
+		match context.game_controller() {
+			Ok(subsystem) => {
+				Gamepads::with_opener(Box::new(move |index: u32| {
+					subsystem.open(index).map_err(|msg| msg.to_string())
+				}))
+			},
+			Err(msg) => {
+				println!("game controllers are unavailable: {}", msg);
+				Gamepads { open: None, controllers: Vec::new() }
+			}
+		}
+	}
+}
+
+impl<P: Pad> Gamepads<P> {
+	/// Listens for controllers, opening them w/ `open`.
+	pub fn with_opener(open: Opener<P>) -> Gamepads<P> {
+		Gamepads { open: Some(open), controllers: Vec::new() }
+	}
+
+	/// Opens & closes controllers as they're plugged in & out. Responds
+	/// true if `event` was one of those.
+	pub fn handle_event(&mut self, event: &Event) -> bool {
+		match *event {
+			Event::ControllerDeviceAdded { which, .. } => { self.added(which); true },
+			Event::ControllerDeviceRemoved { .. } => { self.removed(); true },
+			_ => { false }
+		}
+	}
+
+	/// How many controllers are open.
+	pub fn connected(&self) -> usize {
+		self.controllers.len()
+	}
+
+	/// Opens the controller at device `index`, which was just plugged in.
+	pub fn added(&mut self, index: i32) {
+		let opened = match self.open {
+			Some(ref mut open) => { open(index as u32) },
+			None => { return; }
+		};
+		match opened {
+			Ok(controller) => {
+				println!("controller connected: {}", controller.name());
+				self.controllers.push(controller);
+			},
+			Err(msg) => { println!("could not open controller {}: {}", index, msg); }
+		}
+	}
+
+	/// Lets go of whichever controllers were unplugged.
+	pub fn removed(&mut self) {
+		let before = self.controllers.len();
+		self.controllers.retain(|controller| controller.attached());
+		if self.controllers.len() < before {
+			println!("controller disconnected, {} left", self.controllers.len());
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use std::cell::{Cell, RefCell};
+	use std::rc::Rc;
+
+	use sdl2::controller::Button;
+	use sdl2::event::Event;
+
+	use game::controls::Action;
+	use super::{actions_for, axis_throttle, Gamepads, Pad, DEADZONE};
+
+	struct FakePad {
+		attached: Rc<Cell<bool>>
+	}
+
+	impl Pad for FakePad {
+		fn name(&self) -> String {
+			"fake pad".to_string()
+		}
+
+		fn attached(&self) -> bool {
+			self.attached.get()
+		}
+	}
+
+	#[test]
+	fn deadzone_is_ignored() {
+		assert_eq!(axis_throttle(0), 0.0);
+		assert_eq!(axis_throttle(DEADZONE as i16), 0.0);
+		assert_eq!(axis_throttle(-DEADZONE as i16), 0.0);
+	}
+
+	#[test]
+	fn throttle_scales_past_the_deadzone() {
+		assert_eq!(axis_throttle(32767), 1.0);
+		assert_eq!(axis_throttle(-32768), -1.0);
+
+		let halfway = (DEADZONE + (32767 - DEADZONE) / 2) as i16;
+		assert!((axis_throttle(halfway) - 0.5).abs() < 0.001);
+		assert!((axis_throttle(-halfway) + 0.5).abs() < 0.001);
+	}
+
+	#[test]
+	fn buttons_set_off_actions() {
+		assert_eq!(actions_for(Button::DPadLeft), vec![Action::MoveLeft]);
+		assert_eq!(actions_for(Button::DPadRight), vec![Action::MoveRight]);
+		assert_eq!(actions_for(Button::DPadUp), vec![Action::MoveUp]);
+		assert_eq!(actions_for(Button::DPadDown), vec![Action::MoveDown]);
+		assert_eq!(actions_for(Button::Start), vec![Action::Pause]);
+		assert_eq!(actions_for(Button::A), vec![Action::UseItem]);
+		assert_eq!(actions_for(Button::B), vec![]);
+	}
+
+	#[test]
+	fn hotplug_events_open_and_close_controllers() {
+		let plugged: Rc<RefCell<Vec<Rc<Cell<bool>>>>> = Rc::new(RefCell::new(Vec::new()));
+		let opened = plugged.clone();
+		let mut gamepads = Gamepads::with_opener(Box::new(move |index: u32| {
+			if index > 1 {
+				return Err(format!("no controller at {}", index));
+			}
+			let attached = Rc::new(Cell::new(true));
+			opened.borrow_mut().push(attached.clone());
+			Ok(FakePad { attached: attached })
+		}));
+
+		assert!(gamepads.handle_event(&Event::ControllerDeviceAdded { timestamp: 0, which: 0 }));
+		assert!(gamepads.handle_event(&Event::ControllerDeviceAdded { timestamp: 0, which: 1 }));
+		assert!(gamepads.handle_event(&Event::ControllerDeviceAdded { timestamp: 0, which: 2 }));
+		assert_eq!(gamepads.connected(), 2);
+
+		plugged.borrow()[0].set(false);
+		assert!(gamepads.handle_event(&Event::ControllerDeviceRemoved { timestamp: 0, which: 0 }));
+		assert_eq!(gamepads.connected(), 1);
+
+		assert!(!gamepads.handle_event(&Event::Quit { timestamp: 0 }));
+		assert_eq!(gamepads.connected(), 1);
+	}
+}
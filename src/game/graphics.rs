@@ -1,142 +1,169 @@
-use std::rc::Rc;
-
-use std::collections::hash_map::{HashMap, Entry};
-use std::path::Path;
 use std::string;
 
 use game;
+use game::assets;
+use game::packs;
+use game::settings;
+use game::sounds;
+use game::text;
 use game::units;
-use game::units::{AsPixel};
+use game::units::{AsGame, AsPixel};
 
 use sdl2;
 use sdl2::rect;
-use sdl2::surface;
 use sdl2::render;
 use sdl2::video;
 use sdl2::mouse;
 use sdl2::pixels;
 
 use sdl2_mixer;
-use sdl2_ttf;
 
 static MAX_HP: u32 = 3;
 
-// fail when error
-macro_rules! trying(
-    ($e:expr) => (match $e { Ok(e) => e, Err(e) => panic!("failed: {}", e) })
-);
+// the sound effects, by name
+static SOUND_MANIFEST: &'static str = "assets/sounds.cfg";
+
+// sounds further than this from the player aren't heard at all
+static HEARING_RANGE: units::Tile = units::Tile(24);
 
 /// Acts as a buffer to the underlying display
 pub struct Graphics<'g> {
 	screen:   Box<render::Renderer<'g>>,
 	music:    sdl2_mixer::Music,
-	pub sound_effects: Vec<sdl2_mixer::Chunk>,
-	pub sprite_cache:  HashMap<string::String, render::Texture>,
+	pub sounds:   sounds::SoundBank,
+	text:     text::TextCache,
+	images:   assets::Textures,
 }
 
 impl<'g> Graphics<'g> {
-	/// Prepare the display for rendering
+	/// Prepare the display for rendering, sized & mixed as `settings` ask
 	#[allow(unused_must_use)]
-	pub fn new(context: &sdl2::Sdl) -> Graphics<'g> {
+	pub fn new(context: &sdl2::Sdl, settings: &settings::Settings) -> Graphics<'g> {
 		let (units::Pixel(w), units::Pixel(h)) = 
 			(game::game::SCREEN_WIDTH.to_pixel(), game::game::SCREEN_HEIGHT.to_pixel());
 
 	    let video_subsystem = context.video().unwrap();
 
-	    let window = video_subsystem.window("rust-sdl2 demo: Video", w as u32, h as u32)
+	    let window = video_subsystem.window("rust-sdl2 demo: Video", w as u32 * settings.scale, h as u32 * settings.scale)
 	        .position_centered()
 	        .opengl()
 	        .build()
 	        .unwrap();
 
 	    let mut render_context = window.renderer().build().unwrap();
+		// draw at the game's resolution, whatever size the window is
+		render_context.set_logical_size(w as u32, h as u32);
 
 		// setup background music
 		sdl2_mixer::open_audio(sdl2_mixer::DEFAULT_FREQUENCY, 0x8010u16, 2, 1024);
-		sdl2_mixer::allocate_channels(2);
 		sdl2_mixer::init(sdl2_mixer::INIT_MP3 | sdl2_mixer::INIT_FLAC | sdl2_mixer::INIT_MOD | sdl2_mixer::INIT_FLUIDSYNTH | sdl2_mixer::INIT_MODPLUG | sdl2_mixer::INIT_OGG);
-		let music = sdl2_mixer::Music::from_file( &Path::new("assets/background.wav") ).unwrap();
+		let music = sdl2_mixer::Music::from_file( &packs::locate("assets/background.wav") ).unwrap();
 
 		// setup sound effects
-		let mut sound_effect_vec: Vec<sdl2_mixer::Chunk> = Vec::new();
-		let bullet = sdl2_mixer::Chunk::from_file( &Path::new("assets/bullet.wav") ).unwrap();
-		sound_effect_vec.push(bullet);
-		let wipeout = sdl2_mixer::Chunk::from_file( &Path::new("assets/wipeout.wav") ).unwrap();
-		sound_effect_vec.push(wipeout);
-		let nuke = sdl2_mixer::Chunk::from_file( &Path::new("assets/nuke.wav") ).unwrap();
-		sound_effect_vec.push(nuke);
-		let powerup = sdl2_mixer::Chunk::from_file( &Path::new("assets/powerup.wav") ).unwrap();
-		sound_effect_vec.push(powerup);
-		let debuff = sdl2_mixer::Chunk::from_file( &Path::new("assets/debuff.wav") ).unwrap();
-		sound_effect_vec.push(debuff);
-		let trap = sdl2_mixer::Chunk::from_file( &Path::new("assets/trap.wav") ).unwrap();
-		sound_effect_vec.push(trap);
-		let hit = sdl2_mixer::Chunk::from_file( &Path::new("assets/hit.wav") ).unwrap();
-		sound_effect_vec.push(hit);
-		let goal = sdl2_mixer::Chunk::from_file( &Path::new("assets/goal.wav") ).unwrap();
-		sound_effect_vec.push(goal);
-
-		let graphics: Graphics = 
+		let sound_defs = match sounds::load_manifest(SOUND_MANIFEST) {
+			Ok(defs) => { defs },
+			Err(msg) => { println!("{}, using the default sounds", msg); sounds::default_manifest() }
+		};
+		let sound_bank = sounds::SoundBank::load(&sound_defs);
+
+		let mut graphics: Graphics = 
 			Graphics {
 				screen:        Box::new(render_context),
-				sprite_cache:  HashMap::<string::String, render::Texture>::new(),
+				// missing images are only forgiven while developing
+				images:        assets::Textures::new(cfg!(debug_assertions)),
 				music:         music, 
-				sound_effects: sound_effect_vec
+				sounds:        sound_bank,
+				text:          text::TextCache::new()
 			};
+		graphics.apply_settings(settings);
 		
 		return graphics;
 	}
 
-	/// Loads a bitmap which resides at `file_path` and returns a handle
-	/// This handle can safely be used in any of the graphics subsystem's rendering
-	/// contexts.
-	pub fn load_image(&mut self, 
-	                  file_path: string::String, 
-	                  transparent_black: bool) {
-		
-		// Retrieve a handle or generate a new one if it exists already.
-		// Load sprite
-		let sprite_path = Path::new(&file_path[..]);
-		let sprite_window = surface::Surface::load_bmp(&sprite_path);
-
-		// Store sprite
-		let mut sprite_surface = match sprite_window {
-			Ok(surface) => surface,
-			Err(msg) => panic!("sprite could not be loaded to a surface: {}", msg),
-		};
-
-		// wrap surface in texture and store it
-		if transparent_black {
-			match sprite_surface.set_color_key(true, pixels::Color::RGB(0,0,0)) {
-				Ok(_) => {},
-				Err(msg) => panic!("Failed to key sprite: {}", msg),
-			}
-		}
+	/// Resizes the window & sets the mixer up as `settings` ask.
+	/// Can be called at any time; the change shows on the next frame.
+	#[allow(unused_must_use)]
+	pub fn apply_settings(&mut self, settings: &settings::Settings) {
+		let (units::Pixel(w), units::Pixel(h)) = 
+			(game::game::SCREEN_WIDTH.to_pixel(), game::game::SCREEN_HEIGHT.to_pixel());
 
-		match self.sprite_cache.entry(file_path.clone()) {
-			Entry::Vacant(entry) => {
-				match self.screen.create_texture_from_surface(&sprite_surface) {
-					Ok(texture) => { entry.insert(texture); },
-					Err(msg) => panic!("sprite could not be rendered: {}", msg)
+		match self.screen.window_mut() {
+			Some(window) => {
+				if settings.fullscreen {
+					window.set_fullscreen(video::FullscreenType::Desktop);
+				} else {
+					window.set_fullscreen(video::FullscreenType::Off);
+					window.set_size(w as u32 * settings.scale, h as u32 * settings.scale);
 				}
 			},
+			None => {}
+		}
 
-			_ => {},
-		};
+		sdl2_mixer::allocate_channels(settings.channels as isize);
+		self.sounds.set_channels(settings.channels);
+		sdl2_mixer::Music::set_volume(settings.music_volume as isize);
+		sdl2_mixer::Channel::all().set_volume(settings.sound_volume as isize);
 	}
 
-	pub fn remove_image(&mut self, file_path: string::String) {
-		self.sprite_cache.remove(&file_path);
+	/// A handle to the bitmap at `file_path`, loaded if it isn't already.
+	/// Handles are cheap to clone & keep the image around until the last of
+	/// them is dropped (& `free_unused_images()` is called).
+	///
+	/// Fails w/ a message naming the file if it can't be loaded; in debug
+	/// builds it's drawn as a magenta placeholder instead.
+	pub fn load_image(&mut self, 
+	                  file_path: &str, 
+	                  transparent_black: bool) -> Result<assets::TextureHandle, string::String> {
+		self.images.load(&self.screen, file_path, transparent_black)
+	}
+
+	/// Like `load_image`, but an image which can't be loaded is reported &
+	/// drawn as a placeholder, for drawing code w/ no one to fail to.
+	/// Responds w/ nothing only if not even a placeholder could be made.
+	pub fn image_or_placeholder(&mut self,
+	                            file_path: &str,
+	                            transparent_black: bool) -> Option<assets::TextureHandle> {
+		match self.load_image(file_path, transparent_black) {
+			Ok(image) => { Some(image) },
+			Err(msg) => {
+				println!("{}, drawing a placeholder", msg);
+				match self.images.placeholder(&self.screen, file_path) {
+					Ok(image) => { Some(image) },
+					Err(msg) => { println!("{}", msg); None }
+				}
+			}
+		}
+	}
+
+	/// Lets go of every image no handle refers to any more.
+	pub fn free_unused_images(&mut self) {
+		self.images.free_unused();
 	}
 	
 	#[allow(unused_must_use)]
 	pub fn blit_surface(&mut self,
-	                    src_id: &str,
+	                    image: &assets::TextureHandle,
 	                    src_rect:  &rect::Rect,
 	                    dest_rect: &rect::Rect) {
 		
-		let src = &mut self.sprite_cache.get_mut(src_id).unwrap();
-		let _ = self.screen.copy(src, Some(*src_rect), Some(*dest_rect));
+		// a placeholder is stretched over the whole of where the image goes,
+		// & art drawn at another tile size is picked out at that size
+		let (texture, scale) = self.images.get(image);
+		let source = match scale {
+			Some(scale) if scale != 1.0 => {
+				let scaled = |value: i32| (value as f64 * scale).round() as i32;
+				match rect::Rect::new(scaled(src_rect.x()), scaled(src_rect.y()), scaled(src_rect.width() as i32) as u32, scaled(src_rect.height() as i32) as u32) {
+					Ok(rect) => { rect },
+					Err(msg) => {
+						println!("could not scale the {}x{} rectangle at ({}, {}) to draw it: {}", src_rect.width(), src_rect.height(), src_rect.x(), src_rect.y(), msg);
+						return;
+					}
+				}
+			},
+			Some(_) => { Some(*src_rect) },
+			None => { None }
+		};
+		let _ = self.screen.copy(texture, source, Some(*dest_rect));
 	}
 
 	pub fn switch_buffers(&mut self) {
@@ -158,20 +185,34 @@ impl<'g> Graphics<'g> {
 	pub fn resume_music(&self) {
 		sdl2_mixer::Music::resume();
 	}
-	#[allow(unused_must_use)]
-	pub fn play_sound_effect(&self, index: u32) {
-		let channel = sdl2_mixer::Channel::all();
-		let chunk = self.sound_effects.get(index as usize).unwrap() as &sdl2_mixer::Chunk; 
-		channel.play(chunk, 0);
+	pub fn play_sound_effect(&mut self, name: &str) {
+		self.sounds.play(name);
 	}
 
-	#[allow(unused_must_use)]
-	pub fn draw_text(&mut self, text: &str, dest_rect: rect::Rect) {
-		let font = trying!(sdl2_ttf::Font::from_file(&Path::new("assets/font.ttf"), 128));
-		// render a surface, and convert it to a texture bound to the renderer
-	    let surface = trying!(font.render(text, sdl2_ttf::blended(pixels::Color::RGBA(255, 0, 0, 255))));
-	    let texture = trying!(self.screen.create_texture_from_surface(&surface));
-    	self.screen.copy(&texture, None, Some(dest_rect));
+	/// Plays a sound which happened (`dx`, `dy`) away from the player on
+	/// the map: panned all the way by the edge of the screen & fading out
+	/// toward `HEARING_RANGE`.
+	pub fn play_sound_at(&mut self, name: &str, dx: units::Game, dy: units::Game) {
+		let (units::Game(dx), units::Game(dy)) = (dx, dy);
+		let units::Game(half_width) = game::SCREEN_WIDTH.to_game() / units::Game(2.0);
+		let units::Game(range) = HEARING_RANGE.to_game();
+		let distance = (dx * dx + dy * dy).sqrt() / range;
+		if distance > 1.0 {
+			return;
+		}
+
+		self.sounds.play_at(name, dx / half_width, distance);
+	}
+
+	/// Draws `text` in `style` w/ the top of its first line at `y`, lined
+	/// up on `x` as the style says. Responds w/ the height it took up.
+	pub fn draw_text(&mut self, text: &str, x: i32, y: i32, style: &text::Style) -> u32 {
+		self.text.draw(&mut self.screen, text, x, y, style)
+	}
+
+	/// The width & height `draw_text` would take up w/ `text` in `style`.
+	pub fn measure_text(&mut self, text: &str, style: &text::Style) -> (u32, u32) {
+		self.text.measure(text, style)
 	}
 
 	#[allow(unused_must_use)]
@@ -186,8 +227,10 @@ impl<'g> Graphics<'g> {
 
 	#[allow(unused_must_use)]
 	pub fn draw_health(&mut self, hp: u32) {
-		let heart_sprites = "assets/base/heart.bmp"; 
-		self.load_image(String::from(heart_sprites), true);
+		let heart_sprites = match self.image_or_placeholder("assets/base/heart.bmp", true) {
+			Some(image) => { image },
+			None => { return; }
+		};
 		let full_source = match rect::Rect::new(0, 0, 18, 18) {
 			Ok(rect) => { rect.unwrap() },
 			Err(msg) => { panic!(msg) }
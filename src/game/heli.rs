@@ -2,35 +2,18 @@ use std::collections::HashMap;
 
 use game::collisions::Rectangle;
 use game::sprite;
+use game::camera;
 use game::graphics;
-use game::common;
 use game::common::Character;
 use game::vehicle::Vehicle;
 use game::vehicle::Part;
 
 use game::units;
 
-static SPRITE_NUM_FRAMES:   units::Frame  = 4;
-static SPRITE_FPS:          units::Fps    = 20;
-
-static COMPLETE_X_OFFSET: units::Tile = units::Tile(0);
-static SCRAPPED_X_OFFSET: units::Tile = units::Tile(0);
-static SCRAPPED_Y_OFFSET: units::Tile = units::Tile(3);
-static PERM_1_OFFSET:     units::Tile = units::Tile(8);
-static PERM_2_OFFSET:     units::Tile = units::Tile(4);
-static PERM_3_OFFSET:     units::Tile = units::Tile(0);
-static PERM_4_OFFSET:     units::Tile = units::Tile(4);
-static PERM_5_OFFSET:     units::Tile = units::Tile(8);
-static PERM_6_OFFSET:     units::Tile = units::Tile(0);
-static PERM1_Y_OFFSET:    units::Tile = units::Tile(3);
-static PERM2_Y_OFFSET:    units::Tile = units::Tile(6);
-static PARTS_Y_OFFSET:    units::Tile = units::Tile(9);
-static PROP_OFFSET:       units::Tile = units::Tile(0);
-static WIND_OFFSET:       units::Tile = units::Tile(7);
-static BAR_OFFSET:        units::Tile = units::Tile(4);
+// each part's name in the sprite manifest, by part type (from 1)
+static PART_NAMES: [&'static str; 3] = ["prop", "windshield", "bar"];
 
 static NUM_PARTS:                u32 = 3;
-static POSSIBLE_CONFIGS:         u32 = 8;
 
 pub struct Helicopter {
 	pub x: units::Game,
@@ -38,7 +21,7 @@ pub struct Helicopter {
 	pub map_x: units::Game, 
 	pub map_y: units::Game,
 
-	sprites:  HashMap<u32, Box<sprite::Updatable<units::Game>>>,
+	sprites:  HashMap<String, Box<sprite::Updatable<units::Game>>>,
 	parts:    HashMap<u32, u32>
 }
 
@@ -55,10 +38,9 @@ pub struct Bar {
 }
 
 impl Helicopter {
-	pub fn new(display: &mut graphics::Graphics,
-	           x: units::Game, y: units::Game) -> Helicopter {
+	pub fn new(x: units::Game, y: units::Game) -> Helicopter {
 		
-		let sprite_map = HashMap::<u32, Box<sprite::Updatable<_>>>::new();
+		let sprite_map = HashMap::<String, Box<sprite::Updatable<_>>>::new();
 		let part_map = HashMap::<u32, u32>::new();
 
 		let mut new_helicopter = Helicopter { 
@@ -69,144 +51,37 @@ impl Helicopter {
 			parts: part_map
 		};
 
-		new_helicopter.load_sprites(display);
+		new_helicopter.load_sprites();
 
 		new_helicopter
 	}
 
-	fn load_sprites(&mut self, 
-	               display: &mut graphics::Graphics) {
-
-		for i in 0.. POSSIBLE_CONFIGS {
-			match i {
-				0 => {
-					self.sprites.insert(0u32,
-						{
-							let asset_path = "assets/base/heli.bmp".to_string();
-							let sprite_x = SCRAPPED_X_OFFSET;
-							let sprite_y = SCRAPPED_Y_OFFSET;
-
-							Box::new( sprite::Sprite::new(
-								display, 
-								(sprite_x, sprite_y), 
-								(units::Tile(4), units::Tile(3)),
-								asset_path
-							) ) as Box<sprite::Updatable<_>>
-						}
-					);
-				},
-				1 => {
-					self.sprites.insert(1u32,
-						{
-							let asset_path = "assets/base/heli.bmp".to_string();
-							let sprite_x = PERM_1_OFFSET;
-							let sprite_y = PERM2_Y_OFFSET;
-
-							Box::new( sprite::Sprite::new(
-								display, 
-								(sprite_x, sprite_y), 
-								(units::Tile(4), units::Tile(3)),
-								asset_path
-							) ) as Box<sprite::Updatable<_>>
-						}
-					);
-				},
-				2 => {
-					self.sprites.insert(2u32,
-						{
-							let asset_path = "assets/base/heli.bmp".to_string();
-							let sprite_x = PERM_2_OFFSET;
-							let sprite_y = PERM2_Y_OFFSET;
-
-							Box::new( sprite::Sprite::new(
-								display, 
-								(sprite_x, sprite_y), 
-								(units::Tile(4), units::Tile(3)),
-								asset_path
-							) ) as Box<sprite::Updatable<_>>
-						}
-					);
-				},
-				3 => {
-					self.sprites.insert(3u32,
-						{
-							let asset_path = "assets/base/heli.bmp".to_string();
-							let sprite_x = PERM_3_OFFSET;
-							let sprite_y = PERM1_Y_OFFSET;
-
-							Box::new( sprite::Sprite::new(
-								display, 
-								(sprite_x, sprite_y), 
-								(units::Tile(4), units::Tile(3)),
-								asset_path
-							) ) as Box<sprite::Updatable<_>>
-						}
-					);
-				},
-				4 => {
-					self.sprites.insert(4u32,
-						{
-							let asset_path = "assets/base/heli.bmp".to_string();
-							let sprite_x = PERM_4_OFFSET;
-							let sprite_y = PERM1_Y_OFFSET;
-
-							Box::new( sprite::Sprite::new(
-								display, 
-								(sprite_x, sprite_y), 
-								(units::Tile(4), units::Tile(3)),
-								asset_path
-							) ) as Box<sprite::Updatable<_>>
-						}
-					);
-				},
-				5 => {
-					self.sprites.insert(5u32,
-						{
-							let asset_path = "assets/base/heli.bmp".to_string();
-							let sprite_x = PERM_5_OFFSET;
-							let sprite_y = PERM1_Y_OFFSET;
-
-							Box::new( sprite::Sprite::new(
-								display, 
-								(sprite_x, sprite_y), 
-								(units::Tile(4), units::Tile(3)),
-								asset_path
-							) ) as Box<sprite::Updatable<_>>
-						}
-					);
-				},
-				6 => {
-					self.sprites.insert(6u32,
-						{
-							let asset_path = "assets/base/heli.bmp".to_string();
-							let sprite_x = PERM_6_OFFSET;
-							let sprite_y = PERM2_Y_OFFSET;
-
-							Box::new( sprite::Sprite::new(
-								display, 
-								(sprite_x, sprite_y), 
-								(units::Tile(4), units::Tile(3)),
-								asset_path
-							) ) as Box<sprite::Updatable<_>>
-						}
-					);
-				},
-				_ => {
-					self.sprites.insert(7u32,
-						{
-							let asset_path = "assets/base/heli.bmp".to_string();
-							let sprite_x = COMPLETE_X_OFFSET;
-							let sprite_y = COMPLETE_X_OFFSET;
-
-							Box::new( sprite::AnimatedSprite::new(
-								display, asset_path,
-								(sprite_x, sprite_y), 
-								(units::Tile(4), units::Tile(3)),
-								SPRITE_NUM_FRAMES, SPRITE_FPS
-							).unwrap() ) as Box<sprite::Updatable<_>>
-						}
-					);
-				}
+	/// Loads a sprite for every set of parts the helicopter can have on.
+	fn load_sprites(&mut self) {
+		let mut configs = vec![String::from("scrapped"), String::from("complete")];
+		for first in 0.. PART_NAMES.len() {
+			configs.push(format!("config.{}", PART_NAMES[first]));
+			for second in first + 1.. PART_NAMES.len() {
+				configs.push(format!("config.{}+{}", PART_NAMES[first], PART_NAMES[second]));
+			}
+		}
+
+		for config in configs.into_iter() {
+			let sprite = sprite::named(&format!("heli.{}", config));
+			self.sprites.insert(config, sprite);
+		}
+	}
+
+	/// The sprite for the parts on so far: `scrapped`, `complete`, or
+	/// `config.` & the parts' names in order, joined by `+`.
+	fn config(&self) -> String {
+		let parts = self.parts();
+		match parts.len() {
+			0 => String::from("scrapped"),
+			n if n == NUM_PARTS as usize => String::from("complete"),
+			_ => {
+				let names: Vec<&str> = parts.iter().map(|part| PART_NAMES[*part as usize - 1]).collect();
+				format!("config.{}", names.join("+"))
 			}
 		}
 	}
@@ -222,7 +97,7 @@ impl Vehicle for Helicopter {
 	}
 
 	fn update(&mut self, elapsed_time: units::Millis) {
-		let sprite_ref = self.sprites.get_mut(&7u32);
+		let sprite_ref = self.sprites.get_mut("complete");
 		sprite_ref.unwrap().update(elapsed_time);
 	}
 
@@ -230,30 +105,22 @@ impl Vehicle for Helicopter {
 		self.y = self.y - units::Game(1.0);
 	}
 
-	fn draw(&self, display: &mut graphics::Graphics) {
-		let mut config = 0u32;
-		if self.parts.len() == NUM_PARTS as usize {
-			config = 7u32;
-		} else if self.parts.contains_key(&1u32) && self.parts.contains_key(&3u32) {
-			config = 1u32;
-		} else if self.parts.contains_key(&2u32) && self.parts.contains_key(&3u32) {
-			config = 2u32;
-		} else if self.parts.contains_key(&1u32) && self.parts.contains_key(&2u32) {
-			config = 6u32;
-		} else if self.parts.contains_key(&3u32) {
-			config = 3u32;
-		} else if self.parts.contains_key(&2u32) {
-			config = 4u32;
-		} else if self.parts.contains_key(&1u32) {
-			config = 5u32;
-		}
-		self.sprites.get(&config).unwrap().draw(display, (self.x, self.y));
+	fn draw(&self, display: &mut graphics::Graphics, camera: &camera::Camera) {
+		let config = self.config();
+		self.sprites.get(&config).unwrap().draw(display, camera.to_screen(self.x, self.y));
 	}
 
 	fn add_part(&mut self, part_num: u32) {
 		self.parts.insert(part_num, 1);
 	}
 
+	/// The parts added so far, in order of their type.
+	fn parts(&self) -> Vec<u32> {
+		let mut parts: Vec<u32> = self.parts.keys().cloned().collect();
+		parts.sort();
+		parts
+	}
+
 	fn is_built(&self) -> bool {
 		if self.parts.len() == NUM_PARTS as usize {
 			true
@@ -284,8 +151,7 @@ impl Vehicle for Helicopter {
 }
 
 impl Prop {
-	pub fn new(display: &mut graphics::Graphics,
-	           x: units::Game, y: units::Game) -> Prop {
+	pub fn new(x: units::Game, y: units::Game) -> Prop {
 
 		let mut new_prop = Prop { 
 			character: Character::new(x, y)
@@ -293,44 +159,28 @@ impl Prop {
 
 		for motion in sprite::MOTIONS.iter() {
 			for facing in sprite::FACINGS.iter() {
-				new_prop.load_sprite(display, (*motion, *facing));
+				new_prop.load_sprite((*motion, *facing));
 			}
 		}
 
 		new_prop
 	}
 
-	pub fn load_sprite(&mut self, 
-	               display: &mut graphics::Graphics,
-	               movement: (sprite::Motion, sprite::Facing)) {
-
-		self.character.sprites.insert(movement, {
-			let asset_path = "assets/base/heli.bmp".to_string();
-			let motion_frame = PROP_OFFSET;
-
-			let facing_frame = PARTS_Y_OFFSET;
-
-			Box::new( sprite::Sprite::new(
-				display,
-				(motion_frame, facing_frame),
-				(units::Tile(3), units::Tile(1)),
-				asset_path
-			) ) as Box<sprite::Updatable<_>>
-		});
+	pub fn load_sprite(&mut self, movement: (sprite::Motion, sprite::Facing)) {
+		self.character.sprites.insert(movement, sprite::named("heli.part.prop"));
 	}
 }
 
 impl Part for Prop {
     fn damage_rectangle(&self) -> Rectangle {
 		Rectangle {
-			x: self.character.x + units::Tile(1), y: self.character.y + units::HalfTile(1),
+			x: self.character.map_x + units::Tile(1), y: self.character.map_y + units::HalfTile(1),
 			width: units::Game(32.0 * 2.0), height: units::Game(16.0),
 		}
 	}
 
-	fn draw(&self, display: &mut graphics::Graphics) {
-		let correction_x = self.character.map_x % common::SCREEN_CORRECTION;
-		let correction_y = self.character.map_y % common::SCREEN_CORRECTION;
+	fn draw(&self, display: &mut graphics::Graphics, camera: &camera::Camera) {
+		let (correction_x, correction_y) = camera.to_screen(self.character.map_x, self.character.map_y);
 		self.character.sprites.get(&self.character.movement).unwrap().draw(display, (correction_x, correction_y));
 	}
 
@@ -338,14 +188,6 @@ impl Part for Prop {
 		1
 	}
 
-    fn get_x(&self) -> units::Game {
-		self.character.center_x()
-	}
-
-	fn get_y(&self) -> units::Game {
-		self.character.center_y()
-	}
-
 	fn get_map_x(&self) -> units::Game {
 		self.character.map_center_x()
 	}
@@ -356,8 +198,7 @@ impl Part for Prop {
 }
 
 impl Windshield {
-	pub fn new(display: &mut graphics::Graphics,
-	           x: units::Game, y: units::Game) -> Windshield {
+	pub fn new(x: units::Game, y: units::Game) -> Windshield {
 
 		let mut new_wind = Windshield { 
 			character: Character::new(x, y)
@@ -365,44 +206,28 @@ impl Windshield {
 
 		for motion in sprite::MOTIONS.iter() {
 			for facing in sprite::FACINGS.iter() {
-				new_wind.load_sprite(display, (*motion, *facing));
+				new_wind.load_sprite((*motion, *facing));
 			}
 		}
 
 		new_wind
 	}
 
-	pub fn load_sprite(&mut self, 
-	               display: &mut graphics::Graphics,
-	               movement: (sprite::Motion, sprite::Facing)) {
-
-		self.character.sprites.insert(movement, {
-			let asset_path = "assets/base/heli.bmp".to_string();
-			let motion_frame = WIND_OFFSET;
-
-			let facing_frame = PARTS_Y_OFFSET;
-
-			Box::new( sprite::Sprite::new(
-				display,
-				(motion_frame, facing_frame),
-				(units::Tile(1), units::Tile(1)),
-				asset_path
-			) ) as Box<sprite::Updatable<_>>
-		});
+	pub fn load_sprite(&mut self, movement: (sprite::Motion, sprite::Facing)) {
+		self.character.sprites.insert(movement, sprite::named("heli.part.windshield"));
 	}
 }
 
 impl Part for Windshield {
 	fn damage_rectangle(&self) -> Rectangle {
 		Rectangle {
-			x: self.character.x + units::HalfTile(1), y: self.character.y + units::HalfTile(1),
+			x: self.character.map_x + units::HalfTile(1), y: self.character.map_y + units::HalfTile(1),
 			width: units::Game(16.0), height: units::Game(16.0),
 		}
 	}
 
-	fn draw(&self, display: &mut graphics::Graphics) {
-		let correction_x = self.character.map_x % common::SCREEN_CORRECTION;
-		let correction_y = self.character.map_y % common::SCREEN_CORRECTION;
+	fn draw(&self, display: &mut graphics::Graphics, camera: &camera::Camera) {
+		let (correction_x, correction_y) = camera.to_screen(self.character.map_x, self.character.map_y);
 		self.character.sprites.get(&self.character.movement).unwrap().draw(display, (correction_x, correction_y));
 	}
 
@@ -410,14 +235,6 @@ impl Part for Windshield {
 		2
 	}
 
-	fn get_x(&self) -> units::Game {
-		self.character.center_x()
-	}
-
-	fn get_y(&self) -> units::Game {
-		self.character.center_y()
-	}
-
 	fn get_map_x(&self) -> units::Game {
 		self.character.map_center_x()
 	}
@@ -428,8 +245,7 @@ impl Part for Windshield {
 }
 
 impl Bar {
-	pub fn new(display: &mut graphics::Graphics,
-	           x: units::Game, y: units::Game) -> Bar {
+	pub fn new(x: units::Game, y: units::Game) -> Bar {
 
 		let mut new_bar = Bar { 
 			character: Character::new(x, y)
@@ -437,44 +253,28 @@ impl Bar {
 
 		for motion in sprite::MOTIONS.iter() {
 			for facing in sprite::FACINGS.iter() {
-				new_bar.load_sprite(display, (*motion, *facing));
+				new_bar.load_sprite((*motion, *facing));
 			}
 		}
 
 		new_bar
 	}
 
-	pub fn load_sprite(&mut self, 
-	               display: &mut graphics::Graphics,
-	               movement: (sprite::Motion, sprite::Facing)) {
-
-		self.character.sprites.insert(movement, {
-			let asset_path = "assets/base/heli.bmp".to_string();
-			let motion_frame = BAR_OFFSET;
-
-			let facing_frame = PARTS_Y_OFFSET;
-
-			Box::new( sprite::Sprite::new(
-				display,
-				(motion_frame, facing_frame),
-				(units::Tile(2), units::Tile(1)),
-				asset_path
-			) ) as Box<sprite::Updatable<_>>
-		});
+	pub fn load_sprite(&mut self, movement: (sprite::Motion, sprite::Facing)) {
+		self.character.sprites.insert(movement, sprite::named("heli.part.bar"));
 	}
 }
 
 impl Part for Bar {
 	fn damage_rectangle(&self) -> Rectangle {
 		Rectangle {
-			x: self.character.x + units::Tile(1), y: self.character.y + units::HalfTile(1),
+			x: self.character.map_x + units::Tile(1), y: self.character.map_y + units::HalfTile(1),
 			width: units::Game(32.0), height: units::Game(16.0),
 		}
 	}
 
-	fn draw(&self, display: &mut graphics::Graphics) {
-		let correction_x = self.character.map_x % common::SCREEN_CORRECTION;
-		let correction_y = self.character.map_y % common::SCREEN_CORRECTION;
+	fn draw(&self, display: &mut graphics::Graphics, camera: &camera::Camera) {
+		let (correction_x, correction_y) = camera.to_screen(self.character.map_x, self.character.map_y);
 		self.character.sprites.get(&self.character.movement).unwrap().draw(display, (correction_x, correction_y));
 	}
 
@@ -482,14 +282,6 @@ impl Part for Bar {
 		3
 	}
 
-	fn get_x(&self) -> units::Game {
-		self.character.center_x()
-	}
-
-	fn get_y(&self) -> units::Game {
-		self.character.center_y()
-	}
-
 	fn get_map_x(&self) -> units::Game {
 		self.character.map_center_x()
 	}
@@ -1,32 +1,109 @@
+use sdl2::controller::{Axis, Button};
+use sdl2::event::Event;
 use sdl2::keyboard::Keycode;
 
 use std::collections::HashMap;
 
-/// Responds to inquiries regarding three sets of keyboard input.
+use game::controls::{Action, Bindings};
+use game::gamepad;
+
+/// Whether a key or button went down or came back up.
+#[derive(PartialEq,Eq,Clone,Copy)]
+pub enum KeyAction {
+	Down,
+	Up
+}
+
+/// One thing the keyboard or a game controller did.
+#[derive(Clone,Copy)]
+pub enum Signal {
+	Key(KeyAction, Keycode),
+	Button(KeyAction, Button),
+	/// An axis moved to a raw reading, from `-32768` to `32767`.
+	Axis(Axis, i16)
+}
+
+/// What the keyboard or a controller did, as an `Input` takes it.
+pub fn signal_for(event: Event) -> Option<Signal> {
+	match event {
+		Event::KeyDown { keycode: Some(key), .. } => Some(Signal::Key(KeyAction::Down, key)),
+		Event::KeyUp { keycode: Some(key), .. } => Some(Signal::Key(KeyAction::Up, key)),
+		Event::ControllerButtonDown { button, .. } => Some(Signal::Button(KeyAction::Down, button)),
+		Event::ControllerButtonUp { button, .. } => Some(Signal::Button(KeyAction::Up, button)),
+		Event::ControllerAxisMotion { axis, value, .. } => Some(Signal::Axis(axis, value)),
+		_ => None
+	}
+}
+
+/// Responds to inquiries regarding three sets of keyboard & controller
+/// button input.
 ///
 ///- Pressed keys
 ///- Released keys
 ///- Held keys
+///
+/// Each can be asked about by key, or by action through the `Bindings`
+/// (& `gamepad::BUTTON_ACTIONS`). The left stick is kept as a throttle.
 pub struct Input {
 	pressed_keys:   HashMap<u32, bool>,
 	released_keys:  HashMap<u32, bool>,
 	held_keys:      HashMap<u32, bool>,
 
+	pressed_buttons:  HashMap<u32, bool>,
+	released_buttons: HashMap<u32, bool>,
+	held_buttons:     HashMap<u32, bool>,
+
+	stick:          (f64, f64),
+	stick_released: (bool, bool),
+
+	bindings:       Bindings,
 }
 
 impl Input {
-	pub fn new() -> Input {
+	pub fn new(bindings: Bindings) -> Input {
 		Input{
 			pressed_keys:   HashMap::<u32, bool>::new(),
 			released_keys:  HashMap::<u32, bool>::new(),
 			held_keys:      HashMap::<u32, bool>::new(),
+
+			pressed_buttons:  HashMap::<u32, bool>::new(),
+			released_buttons: HashMap::<u32, bool>::new(),
+			held_buttons:     HashMap::<u32, bool>::new(),
+
+			stick:          (0.0, 0.0),
+			stick_released: (false, false),
+
+			bindings:       bindings,
 		}
 	}
 
+	/// Hands a key, button or axis event to the matching handler.
+	pub fn apply(&mut self, signal: Signal) {
+		match signal {
+			Signal::Key(KeyAction::Down, key) => { self.key_down_event(key); },
+			Signal::Key(KeyAction::Up, key) => { self.key_up_event(key); },
+			Signal::Button(KeyAction::Down, button) => { self.button_down_event(button); },
+			Signal::Button(KeyAction::Up, button) => { self.button_up_event(button); },
+			Signal::Axis(axis, value) => { self.axis_event(axis, value); }
+		}
+	}
+
+	pub fn bindings(&self) -> &Bindings {
+		&self.bindings
+	}
+
+	/// Swaps in new bindings. Keys being held stay held.
+	pub fn set_bindings(&mut self, bindings: Bindings) {
+		self.bindings = bindings;
+	}
+
 	/// Resets the toggle states of pressed & released keys.
 	pub fn begin_new_frame(&mut self) {
 		self.pressed_keys.clear();
 		self.released_keys.clear();
+		self.pressed_buttons.clear();
+		self.released_buttons.clear();
+		self.stick_released = (false, false);
 	}
 
 	/// Handles a key down event
@@ -41,6 +118,48 @@ impl Input {
 		self.held_keys.insert(key as u32, false);
 	}
 
+	/// Handles a controller button down event
+	pub fn button_down_event(&mut self, button: Button) {
+		self.pressed_buttons.insert(button as u32, true);
+		self.held_buttons.insert(button as u32, true);
+	}
+
+	/// Handles a controller button up event
+	pub fn button_up_event(&mut self, button: Button) {
+		self.released_buttons.insert(button as u32, true);
+		self.held_buttons.insert(button as u32, false);
+	}
+
+	/// Handles a controller axis event. Only the left stick steers; a stick
+	/// let back into its deadzone counts as released.
+	pub fn axis_event(&mut self, axis: Axis, value: i16) {
+		let (x, y) = self.stick;
+		let throttle = gamepad::axis_throttle(value);
+		match axis {
+			Axis::LeftX => {
+				self.stick = (throttle, y);
+				if throttle == 0.0 && x != 0.0 { self.stick_released.0 = true; }
+			},
+			Axis::LeftY => {
+				self.stick = (x, throttle);
+				if throttle == 0.0 && y != 0.0 { self.stick_released.1 = true; }
+			},
+			_ => {}
+		}
+	}
+
+	/// How far the left stick is pushed along `(x, y)`, each from -1.0
+	/// (left, up) to 1.0 (right, down), w/ the deadzone taken out.
+	pub fn stick(&self) -> (f64, f64) {
+		self.stick
+	}
+
+	/// Responds true for each axis of the left stick which was let back
+	/// into its deadzone since last call to `beginNewFrame()`.
+	pub fn was_stick_released(&self) -> (bool, bool) {
+		self.stick_released
+	}
+
 	/// Responds true if key was pressed since last call to `beginNewFrame()`.
 	/// Responds false otherwise.
 	pub fn was_key_pressed(&self, key: Keycode) -> bool {
@@ -72,4 +191,89 @@ impl Input {
 			None             => false,
 		}
 	}
+
+	/// Responds true if any key or button bound to `action` was pressed
+	/// since last call to `beginNewFrame()`.
+	pub fn was_pressed(&self, action: Action) -> bool {
+		self.bindings.keys(action).iter().any(|key| self.was_key_pressed(*key)) ||
+		gamepad::buttons_for(action).iter().any(|button| is_set(&self.pressed_buttons, *button as u32))
+	}
+
+	/// Responds true if any key or button bound to `action` was released
+	/// since last call to `beginNewFrame()`.
+	pub fn was_released(&self, action: Action) -> bool {
+		self.bindings.keys(action).iter().any(|key| self.was_key_released(*key)) ||
+		gamepad::buttons_for(action).iter().any(|button| is_set(&self.released_buttons, *button as u32))
+	}
+
+	/// Responds true if any key or button bound to `action` is being held down.
+	pub fn is_held(&self, action: Action) -> bool {
+		self.bindings.keys(action).iter().any(|key| self.is_key_held(*key)) ||
+		gamepad::buttons_for(action).iter().any(|button| is_set(&self.held_buttons, *button as u32))
+	}
+}
+
+fn is_set(flags: &HashMap<u32, bool>, code: u32) -> bool {
+	match flags.get(&code) {
+		Some(is_set) => { *is_set },
+		None         => false,
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use sdl2::controller::{Axis, Button};
+	use sdl2::event::Event;
+
+	use game::controls::{Action, Bindings};
+	use super::{signal_for, Input};
+
+	fn send(input: &mut Input, event: Event) {
+		input.apply(signal_for(event).unwrap());
+	}
+
+	#[test]
+	fn buttons_press_hold_and_release_actions() {
+		let mut input = Input::new(Bindings::new());
+		send(&mut input, Event::ControllerButtonDown { timestamp: 0, which: 0, button: Button::A });
+		assert!(input.was_pressed(Action::UseItem));
+		assert!(input.is_held(Action::UseItem));
+		assert!(!input.was_pressed(Action::Pause));
+
+		input.begin_new_frame();
+		send(&mut input, Event::ControllerButtonUp { timestamp: 0, which: 0, button: Button::A });
+		assert!(!input.was_pressed(Action::UseItem));
+		assert!(input.was_released(Action::UseItem));
+		assert!(!input.is_held(Action::UseItem));
+	}
+
+	#[test]
+	fn dpad_moves() {
+		let mut input = Input::new(Bindings::new());
+		send(&mut input, Event::ControllerButtonDown { timestamp: 0, which: 0, button: Button::DPadUp });
+		send(&mut input, Event::ControllerButtonDown { timestamp: 0, which: 0, button: Button::DPadLeft });
+		assert!(input.is_held(Action::MoveUp));
+		assert!(input.is_held(Action::MoveLeft));
+		assert!(!input.is_held(Action::MoveDown));
+		assert!(!input.is_held(Action::MoveRight));
+	}
+
+	#[test]
+	fn stick_steers_until_let_go() {
+		let mut input = Input::new(Bindings::new());
+		send(&mut input, Event::ControllerAxisMotion { timestamp: 0, which: 0, axis: Axis::LeftX, value: 32767 });
+		send(&mut input, Event::ControllerAxisMotion { timestamp: 0, which: 0, axis: Axis::RightY, value: 32767 });
+		assert_eq!(input.stick(), (1.0, 0.0));
+
+		input.begin_new_frame();
+		send(&mut input, Event::ControllerAxisMotion { timestamp: 0, which: 0, axis: Axis::LeftX, value: 100 });
+		assert_eq!(input.stick(), (0.0, 0.0));
+		assert_eq!(input.was_stick_released(), (true, false));
+	}
+
+	#[test]
+	fn other_events_are_no_signal() {
+		assert!(signal_for(Event::Quit { timestamp: 0 }).is_none());
+		assert!(signal_for(Event::ControllerDeviceAdded { timestamp: 0, which: 0 }).is_none());
+	}
 }
@@ -0,0 +1,267 @@
+use std::fs;
+use std::fs::File;
+use std::io::{BufRead, BufReader, Read};
+use std::path::{Path, PathBuf};
+use std::string::String;
+use std::vec::Vec;
+
+use time;
+
+use game::storage;
+
+static LEADERBOARD_HEADER: &'static str = "just-run leaderboard 1";
+static LEADERBOARD_FILE:   &'static str = "leaderboard.txt";
+
+// where the single best score used to be kept, in the working directory
+static LEGACY_HIGHSCORE:   &'static str = "highscore.txt";
+
+/// How many runs the leaderboard remembers.
+pub static MAX_ENTRIES: usize = 10;
+
+/// The longest name a run can be entered under.
+pub static MAX_NAME_LENGTH: usize = 12;
+
+/// What a run is entered under when the player skips typing a name, & what
+/// scores from before there were names are entered under.
+pub static DEFAULT_NAME: &'static str = "RUNNER";
+
+/// One run on the leaderboard.
+#[derive(PartialEq,Clone)]
+pub struct Entry {
+	pub name:  String,
+	pub score: i32,
+	pub level: i32,
+	pub seed:  u32,
+	/// When the run was entered, as `YYYY-MM-DD`.
+	pub date:  String
+}
+
+impl Entry {
+	/// An entry for a run which ended today.
+	pub fn new(name: &str, score: i32, level: i32, seed: u32) -> Entry {
+		let date = match time::strftime("%Y-%m-%d", &time::now()) {
+			Ok(date) => { date },
+			Err(_) => { "????-??-??".to_string() }
+		};
+		Entry { name: name.to_string(), score: score, level: level, seed: seed, date: date }
+	}
+}
+
+/// The best `MAX_ENTRIES` runs, highest score first, kept in the data
+/// directory (see `storage::data_dir()`).
+pub struct Leaderboard {
+	path:    PathBuf,
+	entries: Vec<Entry>
+}
+
+impl Leaderboard {
+	/// Reads the leaderboard from the data directory. A missing file is
+	/// an empty leaderboard (seeded w/ the old `highscore.txt`, if any).
+	pub fn load() -> Leaderboard {
+		let path = storage::data_dir().join(LEADERBOARD_FILE);
+		if fs::metadata(&path).is_err() {
+			let mut leaderboard = Leaderboard { path: path, entries: Vec::new() };
+			leaderboard.import_legacy_highscore();
+			return leaderboard;
+		}
+
+		Leaderboard::from_file(&path)
+	}
+
+	/// Reads a leaderboard file:
+	///
+	///- `just-run leaderboard 1`
+	///- one `<score> <level> <seed> <date> <name>` line per run
+	///
+	/// A file which is corrupt can't take the game down: lines which
+	/// can't be read are reported & dropped, and a file which isn't a
+	/// leaderboard at all is moved aside to `<file>.corrupt`.
+	pub fn from_file(file_path: &Path) -> Leaderboard {
+		let mut leaderboard = Leaderboard { path: file_path.to_path_buf(), entries: Vec::new() };
+		let file = match File::open(file_path) {
+			Ok(file) => { file },
+			Err(msg) => {
+				println!("could not open leaderboard {}: {}", file_path.display(), msg);
+				return leaderboard;
+			}
+		};
+
+		for (index, line) in BufReader::new(file).lines().enumerate() {
+			let line_num = index + 1;
+			let line = match line {
+				Ok(line) => { line },
+				Err(msg) => { println!("{}:{}: {}", file_path.display(), line_num, msg); break; }
+			};
+
+			if line_num == 1 {
+				if line.trim() != LEADERBOARD_HEADER {
+					println!("{}:{}: not a leaderboard file, starting a new one", file_path.display(), line_num);
+					leaderboard.set_aside();
+					return leaderboard;
+				}
+				continue;
+			}
+			if line.trim().len() == 0 {
+				continue;
+			}
+
+			match parse_entry(&line) {
+				Some(entry) => { leaderboard.entries.push(entry); },
+				None => { println!("{}:{}: skipping unreadable entry `{}`", file_path.display(), line_num, line); }
+			}
+		}
+		leaderboard.sort();
+		leaderboard
+	}
+
+	pub fn entries(&self) -> &[Entry] {
+		&self.entries
+	}
+
+	/// The best score on the leaderboard, or 0 while it is empty.
+	pub fn best(&self) -> i32 {
+		self.entries.first().map_or(0, |entry| entry.score)
+	}
+
+	/// Whether a run scoring `score` would make it onto the leaderboard.
+	pub fn qualifies(&self, score: i32) -> bool {
+		score > 0 && (self.entries.len() < MAX_ENTRIES || self.entries.iter().any(|entry| entry.score < score))
+	}
+
+	/// Adds `entry` (in place of `replacing`, an earlier entry for the same
+	/// run) & drops whatever falls off the bottom. Responds w/ the rank
+	/// the entry landed at, counting from 0, if it made it.
+	pub fn submit(&mut self, entry: Entry, replacing: Option<&Entry>) -> Option<usize> {
+		match replacing {
+			Some(old) => {
+				let position = self.entries.iter().position(|other| other == old);
+				match position {
+					Some(position) => { self.entries.remove(position); },
+					None => {}
+				}
+			},
+			None => {}
+		}
+
+		self.entries.push(entry.clone());
+		self.sort();
+		self.entries.iter().position(|other| *other == entry)
+	}
+
+	/// Writes the leaderboard back to its file, atomically.
+	pub fn save(&self) -> Result<(), String> {
+		let mut contents = String::new();
+		contents.push_str(LEADERBOARD_HEADER);
+		contents.push_str("\n");
+		for entry in self.entries.iter() {
+			contents.push_str(&format!("{} {} {} {} {}\n", entry.score, entry.level, entry.seed, entry.date, entry.name));
+		}
+
+		storage::write_atomically(&self.path, &contents)
+	}
+
+	/// Best first; ties go to the run entered first.
+	fn sort(&mut self) {
+		let mut ranked: Vec<Entry> = Vec::new();
+		for entry in self.entries.drain(..) {
+			let position = ranked.iter().position(|other| other.score < entry.score).unwrap_or(ranked.len());
+			ranked.insert(position, entry);
+		}
+		ranked.truncate(MAX_ENTRIES);
+		self.entries = ranked;
+	}
+
+	/// Moves an unreadable leaderboard out of the way so it isn't overwritten.
+	fn set_aside(&self) {
+		let corrupt_path = self.path.with_extension("corrupt");
+		match fs::rename(&self.path, &corrupt_path) {
+			Ok(_) => { println!("moved the old leaderboard to {}", corrupt_path.display()); },
+			Err(msg) => { println!("could not move {} aside: {}", self.path.display(), msg); }
+		}
+	}
+
+	/// Brings the single score `highscore.txt` used to keep over as the
+	/// first entry.
+	fn import_legacy_highscore(&mut self) {
+		let mut contents = String::new();
+		match File::open(&Path::new(LEGACY_HIGHSCORE)).and_then(|mut file| file.read_to_string(&mut contents)) {
+			Ok(_) => {},
+			Err(_) => { return; }
+		}
+		match contents.trim().parse::<i32>() {
+			Ok(score) if score > 0 => {
+				self.entries.push(Entry::new(DEFAULT_NAME, score, 0, 0));
+				match self.save() {
+					Ok(_) => { println!("moved the highscore in {} to {}", LEGACY_HIGHSCORE, self.path.display()); },
+					Err(msg) => { println!("{}", msg); }
+				}
+			},
+			_ => { println!("ignoring unreadable {}", LEGACY_HIGHSCORE); }
+		}
+	}
+}
+
+/// Reads `<score> <level> <seed> <date> <name>`; the name may have spaces.
+fn parse_entry(line: &str) -> Option<Entry> {
+	let fields: Vec<&str> = line.split_whitespace().collect();
+	if fields.len() < 5 {
+		return None;
+	}
+
+	match (fields[0].parse::<i32>(), fields[1].parse::<i32>(), fields[2].parse::<u32>()) {
+		(Ok(score), Ok(level), Ok(seed)) => {
+			Some(Entry {
+				name:  fields[4..].join(" "),
+				score: score,
+				level: level,
+				seed:  seed,
+				date:  fields[3].to_string()
+			})
+		},
+		_ => { None }
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use std::env;
+	use std::fs;
+	use std::fs::File;
+	use std::io::Write;
+
+	use super::{Entry, Leaderboard, MAX_ENTRIES};
+
+	#[test]
+	fn keeps_the_best_ten_best_first() {
+		let path = env::temp_dir().join("just-run-ranked.txt");
+		let _ = fs::remove_file(&path);
+		let mut leaderboard = Leaderboard::from_file(&path);
+		for score in 1.. 13 {
+			leaderboard.submit(Entry::new("RUNNER", score * 100, 1, 0), None);
+		}
+
+		let scores: Vec<i32> = leaderboard.entries().iter().map(|entry| entry.score).collect();
+		assert_eq!(scores.len(), MAX_ENTRIES);
+		assert_eq!(scores, vec![1200, 1100, 1000, 900, 800, 700, 600, 500, 400, 300]);
+		assert!(!leaderboard.qualifies(300));
+		assert!(leaderboard.qualifies(301));
+		assert_eq!(leaderboard.submit(Entry::new("LATE", 100, 1, 0), None), None);
+
+		// ties go to whoever got there first
+		assert_eq!(leaderboard.submit(Entry::new("SECOND", 1100, 1, 0), None), Some(2));
+	}
+
+	#[test]
+	fn corrupt_file_is_set_aside() {
+		let path = env::temp_dir().join("just-run-corrupt.txt");
+		let corrupt_path = path.with_extension("corrupt");
+		let _ = fs::remove_file(&corrupt_path);
+		File::create(&path).unwrap().write_all(b"1200 3 42 2016-01-01 RUNNER\n").unwrap();
+
+		let leaderboard = Leaderboard::from_file(&path);
+		assert!(leaderboard.entries().is_empty());
+		assert!(fs::metadata(&path).is_err());
+		assert!(fs::metadata(&corrupt_path).is_ok());
+		fs::remove_file(&corrupt_path).unwrap();
+	}
+}
@@ -1,17 +1,47 @@
+use std::cmp;
+use std::cmp::Ordering;
+use std::collections::BinaryHeap;
+use std::fs;
+use std::fs::File;
+use std::io::{BufRead, BufReader};
 use std::vec::Vec;
 use std::rc::Rc;
-use rand;
 use rand::Rng;
 
 use game;
 use game::backdrop;
+use game::camera;
 use game::graphics;
+use game::packs;
+use game::random;
 use game::sprite;
+use game::tiled;
+use game::traps;
 use game::units;
 
 use game::collisions::Rectangle;
 use game::units::{AsGame,AsTile};
 
+static MAP_HEADER:    &'static str = "just-run map 1";
+static MAP_DIRECTORY: &'static str = "assets/maps";
+
+static MAP_DIRECTIVES: [&'static str; 11] = [
+	"tileset", "backdrop", "size", "tile", "player", "vehicle", "part", "zombie", "powerup", "trap", "tiles"
+];
+
+// level files are tried in this order
+static MAP_EXTENSIONS: [&'static str; 3] = ["map", "tmx", "tmj"];
+
+// a wall all around & room to stand in
+pub static MIN_LEVEL_SIZE: u32 = 3;
+
+static DEFAULT_TILESET:  &'static str = "assets/base/Stage/PrtCave.bmp";
+static DEFAULT_BACKDROP: &'static str = "assets/base/bkBlue.bmp";
+
+// cost of a step when pathfinding, diagonals are ~sqrt(2) times longer
+static STRAIGHT_STEP:  u32 = 10;
+static DIAGONAL_STEP:  u32 = 14;
+
 #[derive(PartialEq,Eq,Clone)]
 pub enum TileType {
 	Air,
@@ -35,125 +65,450 @@ impl CollisionTile {
 #[derive(Clone)]
 pub struct Tile {
 	tile_type:  TileType,
-	sprite:     Option<Rc<Box<sprite::Updatable<units::Game>>>>
+	sprite:     Option<Rc<Box<sprite::Updatable<units::Game>>>>,
+	// index of the `TileDef` the tile was built from
+	kind:       usize
 }
 
-impl Tile {
-	/// Creates n air tile w/ no sprite.
-	fn new() -> Tile {
-		Tile { tile_type: TileType::Air, sprite: None }
+/// Where a map wants its actors placed, as `(col, row)` tile coordinates.
+///
+/// Anything a map leaves empty is placed at random by the world.
+/// Zombies & powerups are listed by the name of their kind in
+/// `world::ZOMBIE_CONFIG` & `world::POWERUP_CONFIG`. Traps are listed w/
+/// their kind (see `get_type()`), which maps name w/ `trap_kind`.
+#[derive(Clone)]
+pub struct SpawnPoints {
+	pub player:   Option<(units::Tile, units::Tile)>,
+	pub vehicle:  Option<(units::Tile, units::Tile)>,
+	pub parts:    Vec<(units::Tile, units::Tile)>,
+	pub zombies:  Vec<(String, (units::Tile, units::Tile))>,
+	pub powerups: Vec<(String, (units::Tile, units::Tile))>,
+	pub traps:    Vec<(u32, (units::Tile, units::Tile))>
+}
+
+impl SpawnPoints {
+	pub fn new() -> SpawnPoints {
+		SpawnPoints {
+			player:   None,
+			vehicle:  None,
+			parts:    Vec::new(),
+			zombies:  Vec::new(),
+			powerups: Vec::new(),
+			traps:    Vec::new()
+		}
+	}
+}
+
+/// The kind of trap a map calls `name`, e.g. `bear` or `tripwire`
+/// (see `traps::KINDS`).
+pub fn trap_kind(name: &str) -> Option<u32> {
+	traps::find(name)
+}
+
+/// One kind of tile a map is built from: its collision type & where
+/// (if anywhere) its sprite lives on a tile sheet.
+#[derive(Clone)]
+pub struct TileDef {
+	pub tile_type: TileType,
+	pub sprite:    Option<(String, (units::Tile, units::Tile))>
+}
+
+/// A tile on the open list of `Map::find_path`, ordered so the
+/// `BinaryHeap` pops the cheapest estimate first.
+#[derive(PartialEq,Eq)]
+struct PathNode {
+	estimate: u32,
+	index:    usize
+}
+
+impl Ord for PathNode {
+	fn cmp(&self, other: &PathNode) -> Ordering {
+		match other.estimate.cmp(&self.estimate) {
+			Ordering::Equal => { other.index.cmp(&self.index) },
+			ordering => { ordering }
+		}
 	}
+}
 
-	/// Creates a tile of `tile_type` initialized w/ its optional sprite.
-	fn from_sprite(sprite: Rc<Box<sprite::Updatable<units::Game>>>,
-	               tile_type: TileType) -> Tile {
-		Tile { tile_type: tile_type, sprite: Some(sprite) }
+impl PartialOrd for PathNode {
+	fn partial_cmp(&self, other: &PathNode) -> Option<Ordering> {
+		Some(self.cmp(other))
 	}
 }
 
+/// A character of a map file's tile grid & what it stands for.
+struct TileLegend {
+	symbol:    char,
+	tile_type: TileType,
+	offset:    Option<(units::Tile, units::Tile)>
+}
+
 #[derive(Clone)]
 pub struct Map {
 	background:    backdrop::FixedBackdrop,
+	kinds:         Vec<TileDef>,
 	tiles:         Vec<Box<Vec<Box<Tile>>>>, 
-	page_x:        u32,
-	page_y:        u32
+	camera:        camera::Camera,
+	pub spawns:    SpawnPoints
 }
 
 impl Map {
-	/// Will initialize a map (60 * 60) tiles:
-	pub fn load_map(graphics: &mut graphics::Graphics, level: i32) -> Map {
-		static rows: u32 = 60;
-		static cols: u32 = 60; 
-
-		let map_path =  "assets/base/Stage/PrtCave.bmp".to_string();
-		let sprite   =  Rc::new(
-			Box::new( sprite::Sprite::new(
-				graphics,
-				(units::Tile(1) , units::Tile(0)),
-				(units::Tile(1), units::Tile(1)),
-				map_path.clone()
-			) ) as Box<sprite::Updatable<_>>
-		);
-
-		let blank_tile = Tile::new();
-		let wall_tile = Tile::from_sprite(sprite, TileType::Wall);
-		let mut tile_vec: Vec<Box<Vec<Box<Tile>>>> = Vec::new();
+	/// Will initialize a map of `LEVEL_WIDTH` * `LEVEL_HEIGHT` tiles:
+	/// Random wall placement is drawn from `rng`.
+	pub fn load_map(level: i32, rng: &mut random::GameRng) -> Map {
+		let (units::Tile(cols), units::Tile(rows)) = (game::LEVEL_WIDTH, game::LEVEL_HEIGHT);
+
+		let kinds = vec![
+			TileDef { tile_type: TileType::Air,  sprite: None },
+			TileDef { tile_type: TileType::Wall, sprite: Some((DEFAULT_TILESET.to_string(), (units::Tile(1), units::Tile(0)))) }
+		];
+		let (blank_tile, wall_tile) = (0, 1);
+		let mut grid: Vec<Vec<usize>> = Vec::new();
 		match level { 
 			1 => { 
 				for i in 0.. rows {
-					let mut vec = Box::new( Vec::new() );
+					let mut vec = Vec::new();
 					for j in 0.. cols {
 						// make the border
 						if i == rows - 1 || i == 0 || j == 0 || j == cols - 1 {
-							vec.push( Box::new(wall_tile.clone()) );
+							vec.push(wall_tile);
 						}
 						else {
-							vec.push( Box::new(blank_tile.clone()) );
+							vec.push(blank_tile);
 						}
 					}
-					tile_vec.push(vec);
+					grid.push(vec);
 				}
 			},
 			_ => {
-				let mut rng = rand::thread_rng();
 				let rand_num_cols = rng.gen_range(1, 30);
 				let rand_num_rows = rng.gen_range(1, 30);
 				let mut rand_cols: Vec<i32> = Vec::new();
 				let mut rand_rows: Vec<i32> = Vec::new();
 				for _ in 0.. rand_num_cols {
-					let mut rng = rand::thread_rng();
-					rand_cols.push( rng.gen_range(1, 60) );
+					rand_cols.push( rng.gen_range(1, cols as i32) );
 				}
 				for _ in 0.. rand_num_rows {
-					let mut rng = rand::thread_rng();
-					rand_rows.push( rng.gen_range(1, 60) );
+					rand_rows.push( rng.gen_range(1, rows as i32) );
 				}
 				for i in 0.. rows {
-					let mut vec = Box::new( Vec::new() );
+					let mut vec = Vec::new();
 					for j in 0.. cols {
 						// make the border
 						if i == rows - 1 || i == 0 || j == 0 || j == cols - 1 || ( rand_rows.contains(&(i as i32)) && rand_cols.contains(&(j as i32)) ) {
 							if (i > 0 && i < 9) && (j > 0 && j < 9) {
-								vec.push( Box::new(blank_tile.clone()) );
+								vec.push(blank_tile);
 							} else {
-								vec.push( Box::new(wall_tile.clone()) );
+								vec.push(wall_tile);
 							}
 						}
 						else {
-							vec.push( Box::new(blank_tile.clone()) );
+							vec.push(blank_tile);
 						}
 					}
-					tile_vec.push(vec);
+					grid.push(vec);
 				}
 			}
 		}
-		let background: backdrop::FixedBackdrop = match level {
-			1 => { backdrop::FixedBackdrop::new("assets/base/bkBlue.bmp".to_string(), graphics) },
-			_ => { backdrop::FixedBackdrop::new("assets/base/bkRed.bmp".to_string(), graphics) },
+		let background = match level {
+			1 => { "assets/base/bkBlue.bmp".to_string() },
+			_ => { "assets/base/bkRed.bmp".to_string() },
+		};
+
+		match Map::from_grid(background, &kinds, &grid, SpawnPoints::new()) {
+			Ok(map) => { map },
+			Err(err) => { panic!("generated level {} is unplayable: {}", level, err.message()); }
+		}
+	}
+
+	/// Loads `assets/maps/level<n>.map` (or a Tiled `.tmx`/`.tmj` of the
+	/// same name) when the level has a map file, otherwise the level is
+	/// generated by `load_map`.
+	///
+	/// A map file which fails to load is reported & replaced by a
+	/// generated level so a typo can't take the game down.
+	pub fn load_level(level: i32, rng: &mut random::GameRng) -> Map {
+		for extension in MAP_EXTENSIONS.iter() {
+			let file_path = format!("{}/level{}.{}", MAP_DIRECTORY, level, extension);
+			if fs::metadata(packs::locate(&file_path)).is_err() {
+				continue;
+			}
+
+			let map = match *extension {
+				"map" => Map::from_file(&file_path),
+				_     => tiled::load(&file_path)
+			};
+			return match map {
+				Ok(map) => { map },
+				Err(msg) => {
+					println!("{}", msg);
+					Map::load_map(level, rng)
+				}
+			};
+		}
+
+		Map::load_map(level, rng)
+	}
+
+	/// Builds a map from a grid of indices into `kinds`, one `Vec` per row.
+	/// Every row must be the same length.
+	///
+	/// Every use of a kind shares one tile (& sprite). Fails if the edge of
+	/// the grid isn't all wall or anything in `spawns` is placed on a wall.
+	pub fn from_grid(backdrop: String, kinds: &[TileDef], grid: &[Vec<usize>],
+	                 spawns: SpawnPoints) -> Result<Map, GridError> {
+		try!(validate(kinds, grid, &spawns));
+
+		let tiles: Vec<Tile> = kinds.iter().enumerate().map(|(index, kind)| {
+			match kind.sprite {
+				Some((ref sheet, offset)) => {
+					let sprite = Rc::new(
+						Box::new( sprite::Sprite::new(
+							offset,
+							(units::Tile(1), units::Tile(1)),
+							sheet.clone()
+						) ) as Box<sprite::Updatable<_>>
+					);
+					Tile { tile_type: kind.tile_type.clone(), sprite: Some(sprite), kind: index }
+				},
+				None => { Tile { tile_type: kind.tile_type.clone(), sprite: None, kind: index } }
+			}
+		}).collect();
+
+		let mut tile_vec: Vec<Box<Vec<Box<Tile>>>> = Vec::new();
+		for row in grid.iter() {
+			let mut vec = Box::new( Vec::new() );
+			for kind in row.iter() {
+				vec.push( Box::new(tiles[*kind].clone()) );
+			}
+			tile_vec.push(vec);
+		}
+
+		let cols = grid.get(0).map_or(0, |row| row.len());
+		Ok(Map {
+			background: backdrop::FixedBackdrop::new(backdrop),
+			kinds: kinds.to_vec(),
+			tiles: tile_vec,
+			camera: camera::Camera::new(camera::CameraMode::Follow, units::Tile(cols as u32), units::Tile(grid.len() as u32)),
+			spawns: spawns
+		})
+	}
+
+	/// Reads a map file.
+	///
+	/// Map files are plain text. After the `just-run map 1` header each
+	/// line is one directive (`#` starts a comment):
+	///
+	///- `tileset <image>` & `backdrop <image>`
+	///- `size <cols> <rows>`
+	///- `tile <symbol> <air|wall> [<sheet col> <sheet row>]` adds to the
+	///  default legend of `.` (air) and `#` (wall)
+	///- `player <col> <row>` & `vehicle <col> <row>`
+	///- `part <col> <row>`, `zombie <kind> <col> <row>`,
+	///  `powerup <kind> <col> <row>` and `trap <kind> <col> <row>`, once per spawn
+	///- `tiles`, followed by one line of symbols per row of the map
+	///
+	/// The edge of the map must be all wall & nothing may spawn on a wall.
+	/// Returns an error naming the offending line if the file is malformed.
+	pub fn from_file(file_path: &str) -> Result<Map, String> {
+		let file = match File::open(packs::locate(file_path)) {
+			Ok(file) => { file },
+			Err(msg) => { return Err(format!("could not open map {}: {}", file_path, msg)); }
 		};
-		let map = Map {
-			background: background,
-			tiles: tile_vec, 
-			page_x: 0,
-			page_y: 0
+
+		let mut tileset  = DEFAULT_TILESET.to_string();
+		let mut backdrop = DEFAULT_BACKDROP.to_string();
+		let mut size: Option<(u32, u32)> = None;
+		let mut spawns = SpawnPoints::new();
+		let mut legend: Vec<TileLegend> = vec![
+			TileLegend { symbol: '.', tile_type: TileType::Air,  offset: None },
+			TileLegend { symbol: '#', tile_type: TileType::Wall, offset: Some((units::Tile(1), units::Tile(0))) },
+		];
+		let mut rows: Vec<Vec<usize>> = Vec::new();
+		let mut reading_tiles = false;
+		let mut line_num = 0;
+		// where the grid starts & each spawn was given, to report problems at
+		let mut tiles_line = 0;
+		let mut spawn_lines: Vec<((units::Tile, units::Tile), usize)> = Vec::new();
+
+		for (index, line) in BufReader::new(file).lines().enumerate() {
+			line_num = index + 1;
+			let line = match line {
+				Ok(line) => { line },
+				Err(msg) => { return Err(format!("{}:{}: {}", file_path, line_num, msg)); }
+			};
+
+			if line_num == 1 {
+				if line.trim() != MAP_HEADER {
+					return Err(format!("{}:{}: not a map file", file_path, line_num));
+				}
+				continue;
+			}
+
+			// every line after `tiles` is a row of the grid
+			if reading_tiles {
+				let (cols, expected_rows) = size.unwrap();
+				let symbols: Vec<char> = line.trim_right().chars().collect();
+				if rows.len() == expected_rows as usize {
+					if symbols.len() == 0 {
+						continue;
+					}
+					return Err(format!("{}:{}: more rows than the {} given by `size`", file_path, line_num, expected_rows));
+				}
+				if symbols.len() != cols as usize {
+					return Err(format!("{}:{}: expected {} tiles, found {}", file_path, line_num, cols, symbols.len()));
+				}
+				let mut row: Vec<usize> = Vec::new();
+				for symbol in symbols.iter() {
+					match legend.iter().position(|entry| entry.symbol == *symbol) {
+						Some(entry) => { row.push(entry); },
+						None => { return Err(format!("{}:{}: unknown tile `{}`", file_path, line_num, symbol)); }
+					}
+				}
+				rows.push(row);
+				continue;
+			}
+
+			let directive = match line.find('#') {
+				Some(comment) => { &line[..comment] },
+				None          => { &line[..] }
+			};
+			let fields: Vec<&str> = directive.split_whitespace().collect();
+			if fields.len() == 0 {
+				continue;
+			}
+
+			match (fields[0], fields.len()) {
+				("tileset", 2) => {
+					try!(check_asset(file_path, line_num, fields[1]));
+					tileset = fields[1].to_string();
+				},
+				("backdrop", 2) => {
+					try!(check_asset(file_path, line_num, fields[1]));
+					backdrop = fields[1].to_string();
+				},
+				("size", 3) => {
+					let cols = try!(parse_number(file_path, line_num, fields[1]));
+					let rows = try!(parse_number(file_path, line_num, fields[2]));
+					if cols < MIN_LEVEL_SIZE || rows < MIN_LEVEL_SIZE {
+						return Err(format!("{}:{}: maps must be at least {}x{} tiles, found {}x{}",
+						                   file_path, line_num, MIN_LEVEL_SIZE, MIN_LEVEL_SIZE, cols, rows));
+					}
+					size = Some((cols, rows));
+				},
+				("tile", 3) | ("tile", 5) => {
+					let symbols: Vec<char> = fields[1].chars().collect();
+					if symbols.len() != 1 {
+						return Err(format!("{}:{}: tile symbols must be one character, found `{}`", file_path, line_num, fields[1]));
+					}
+					let tile_type = match fields[2] {
+						"air"  => TileType::Air,
+						"wall" => TileType::Wall,
+						_      => { return Err(format!("{}:{}: unknown tile type `{}`", file_path, line_num, fields[2])); }
+					};
+					let offset = if fields.len() == 5 {
+						let col = try!(parse_number(file_path, line_num, fields[3]));
+						let row = try!(parse_number(file_path, line_num, fields[4]));
+						Some((units::Tile(col), units::Tile(row)))
+					} else { None };
+					legend.retain(|entry| entry.symbol != symbols[0]);
+					legend.push(TileLegend { symbol: symbols[0], tile_type: tile_type, offset: offset });
+				},
+				("player", 3) => {
+					let at = try!(parse_position(file_path, line_num, size, &fields[1..]));
+					spawn_lines.push((at, line_num));
+					spawns.player = Some(at);
+				},
+				("vehicle", 3) => {
+					let at = try!(parse_position(file_path, line_num, size, &fields[1..]));
+					spawn_lines.push((at, line_num));
+					spawns.vehicle = Some(at);
+				},
+				("part", 3) => {
+					let at = try!(parse_position(file_path, line_num, size, &fields[1..]));
+					spawn_lines.push((at, line_num));
+					spawns.parts.push(at);
+				},
+				("zombie", 4) => {
+					let at = try!(parse_position(file_path, line_num, size, &fields[2..]));
+					spawn_lines.push((at, line_num));
+					spawns.zombies.push((fields[1].to_string(), at));
+				},
+				("powerup", 4) => {
+					let at = try!(parse_position(file_path, line_num, size, &fields[2..]));
+					spawn_lines.push((at, line_num));
+					spawns.powerups.push((fields[1].to_string(), at));
+				},
+				("trap", 4) => {
+					let kind = match trap_kind(fields[1]) {
+						Some(kind) => { kind },
+						None => { return Err(format!("{}:{}: unknown trap `{}`", file_path, line_num, fields[1])); }
+					};
+					let at = try!(parse_position(file_path, line_num, size, &fields[2..]));
+					spawn_lines.push((at, line_num));
+					spawns.traps.push((kind, at));
+				},
+				("tiles", 1) => {
+					if size.is_none() {
+						return Err(format!("{}:{}: `size` must come before `tiles`", file_path, line_num));
+					}
+					reading_tiles = true;
+					tiles_line = line_num;
+				},
+				(other, _) if MAP_DIRECTIVES.iter().any(|name| *name == other) => {
+					return Err(format!("{}:{}: wrong number of fields for `{}`", file_path, line_num, other));
+				},
+				(other, _) => {
+					return Err(format!("{}:{}: unexpected `{}`", file_path, line_num, other));
+				}
+			}
+		}
+
+		let (_, expected_rows) = match size {
+			Some(size) => { size },
+			None => { return Err(format!("{}:{}: map has no `size`", file_path, line_num)); }
 		};
-	
-		map
+		if rows.len() != expected_rows as usize {
+			return Err(format!("{}:{}: expected {} rows of tiles, found {}", file_path, line_num, expected_rows, rows.len()));
+		}
+
+		let kinds: Vec<TileDef> = legend.iter().map(|entry| {
+			TileDef {
+				tile_type: entry.tile_type.clone(),
+				sprite:    entry.offset.map(|offset| (tileset.clone(), offset))
+			}
+		}).collect();
+
+		match Map::from_grid(backdrop, &kinds, &rows, spawns) {
+			Ok(map) => { Ok(map) },
+			Err(err) => {
+				let at_line = match err {
+					GridError::OpenEdge(_, row) => { tiles_line + 1 + row as usize },
+					GridError::SpawnInWall(_, col, row) => {
+						let at = (units::Tile(col), units::Tile(row));
+						spawn_lines.iter().find(|&&(spawn, _)| spawn == at).map_or(line_num, |&(_, line)| line)
+					}
+				};
+				Err(format!("{}:{}: {}", file_path, at_line, err.message()))
+			}
+		}
 	}
 
 	pub fn draw_background(&self, graphics: &mut graphics::Graphics) {
 		self.background.draw(graphics);
 	}
 
-	/// Draws current state to `display`
-	pub fn draw(&mut self, graphics: &mut graphics::Graphics) {
-		for a in self.page_y * 20.. (self.page_y * 20) + 20 {
-			for b in self.page_x * 20.. (self.page_x * 20) + 20 {
-				match self.tiles.get(a as usize).unwrap().get(b as usize).unwrap().sprite {
+	/// Draws the tiles in view of the camera to `display`
+	pub fn draw(&self, graphics: &mut graphics::Graphics) {
+		let ((first_col, last_col), (first_row, last_row)) = self.camera.visible_tiles();
+		let last_row = cmp::min(last_row, self.tiles.len() as u32);
+		for a in first_row.. last_row {
+			let row = self.tiles.get(a as usize).unwrap();
+			let last_col = cmp::min(last_col, row.len() as u32);
+			for b in first_col.. last_col {
+				match row.get(b as usize).unwrap().sprite {
 					Some(ref sprite) => {
-						sprite.draw(graphics,
-						            (units::Tile(b as u32).to_game() % game::game::SCREEN_WIDTH.to_game(),
-						             units::Tile(a as u32).to_game() % game::game::SCREEN_HEIGHT.to_game()));
+						sprite.draw(graphics, self.camera.to_screen(units::Tile(b).to_game(), units::Tile(a).to_game()));
 					}
 					_ => {}
 				};
@@ -161,29 +516,127 @@ impl Map {
 		}
 	}
 
-	pub fn set_page(&mut self, player_x: units::Game, player_y: units::Game) {
-		if player_x < units::Tile(20).to_game() {
-			self.page_x = 0;
-		} else if player_x > units::Tile(20).to_game() && player_x < units::Tile(40).to_game() {
-			self.page_x = 1;
-		} else {
-			self.page_x = 2;
-		}	
-		if player_y < units::Tile(20).to_game() {
-			self.page_y = 0;
-		} else if player_y > units::Tile(20).to_game() && player_y < units::Tile(40).to_game() {
-			self.page_y = 1;
-		} else {
-			self.page_y = 2;
-		}	
+	/// Moves the camera along w/ the player's center (`player_x`, `player_y`).
+	pub fn follow(&mut self, player_x: units::Game, player_y: units::Game, elapsed_time: units::Millis) {
+		self.camera.follow(player_x, player_y, elapsed_time);
+	}
+
+	/// Points the camera straight at (`player_x`, `player_y`), w/o easing.
+	pub fn snap_camera(&mut self, player_x: units::Game, player_y: units::Game) {
+		self.camera.snap_to(player_x, player_y);
+	}
+
+	pub fn set_camera_mode(&mut self, mode: camera::CameraMode) {
+		self.camera.set_mode(mode);
+	}
+
+	pub fn get_camera(&self) -> &camera::Camera {
+		&self.camera
 	}
 
-	pub fn get_page_x(&self) -> u32 {
-		self.page_x
+	/// How many columns of tiles the map has.
+	pub fn width(&self) -> units::Tile {
+		units::Tile(self.tiles.get(0).map_or(0, |row| row.len()) as u32)
 	}
 
-	pub fn get_page_y(&self) -> u32 {
-		self.page_y
+	/// How many rows of tiles the map has.
+	pub fn height(&self) -> units::Tile {
+		units::Tile(self.tiles.len() as u32)
+	}
+
+	/// Responds true if (`col`, `row`) is a wall or lies outside the map.
+	pub fn is_wall(&self, col: units::Tile, row: units::Tile) -> bool {
+		let (units::Tile(col), units::Tile(row)) = (col, row);
+		match self.tiles.get(row as usize).and_then(|tiles| tiles.get(col as usize)) {
+			Some(tile) => { tile.tile_type == TileType::Wall },
+			None => { true }
+		}
+	}
+
+	/// Finds the shortest walk from tile `from` to tile `to`, both given
+	/// as `(col, row)`, using A*.
+	///
+	/// Steps may be diagonal, but never squeeze between the corners of two
+	/// walls: both tiles beside a diagonal step must be open. The path
+	/// excludes `from` & ends w/ `to`; `None` means `to` can't be reached.
+	pub fn find_path(&self, from: (units::Tile, units::Tile),
+	                 to: (units::Tile, units::Tile)) -> Option<Vec<(units::Tile, units::Tile)>> {
+		let (units::Tile(cols), units::Tile(rows)) = (self.width(), self.height());
+		let ((units::Tile(from_col), units::Tile(from_row)), (units::Tile(to_col), units::Tile(to_row))) = (from, to);
+		if from_col >= cols || from_row >= rows || self.is_wall(units::Tile(to_col), units::Tile(to_row)) {
+			return None;
+		}
+
+		let index_of = |col: u32, row: u32| (row * cols + col) as usize;
+		let estimate = |col: u32, row: u32| {
+			let (dx, dy) = (diff(col, to_col), diff(row, to_row));
+			DIAGONAL_STEP * cmp::min(dx, dy) + STRAIGHT_STEP * (cmp::max(dx, dy) - cmp::min(dx, dy))
+		};
+
+		let start = index_of(from_col, from_row);
+		let goal  = index_of(to_col, to_row);
+		let mut cost: Vec<u32> = vec![u32::max_value(); (cols * rows) as usize];
+		let mut came_from: Vec<Option<usize>> = vec![None; (cols * rows) as usize];
+		let mut closed: Vec<bool> = vec![false; (cols * rows) as usize];
+		let mut open = BinaryHeap::new();
+		cost[start] = 0;
+		open.push(PathNode { estimate: estimate(from_col, from_row), index: start });
+
+		while let Some(PathNode { estimate: _, index }) = open.pop() {
+			if closed[index] {
+				continue;
+			}
+			closed[index] = true;
+
+			if index == goal {
+				let mut path = Vec::new();
+				let mut current = goal;
+				while current != start {
+					path.push((units::Tile(current as u32 % cols), units::Tile(current as u32 / cols)));
+					current = came_from[current].unwrap();
+				}
+				path.reverse();
+				return Some(path);
+			}
+
+			let (col, row) = (index as u32 % cols, index as u32 / cols);
+			for dy in -1i32.. 2 {
+				for dx in -1i32.. 2 {
+					let (next_col, next_row) = (col as i32 + dx, row as i32 + dy);
+					if (dx == 0 && dy == 0) || next_col < 0 || next_row < 0 {
+						continue;
+					}
+					let (next_col, next_row) = (next_col as u32, next_row as u32);
+					if self.is_wall(units::Tile(next_col), units::Tile(next_row)) {
+						continue;
+					}
+
+					// don't cut corners: a diagonal needs both sides open
+					let diagonal = dx != 0 && dy != 0;
+					if diagonal && (self.is_wall(units::Tile(next_col), units::Tile(row))
+					             || self.is_wall(units::Tile(col), units::Tile(next_row))) {
+						continue;
+					}
+
+					let next = index_of(next_col, next_row);
+					let next_cost = cost[index] + if diagonal { DIAGONAL_STEP } else { STRAIGHT_STEP };
+					if next_cost < cost[next] {
+						cost[next] = next_cost;
+						came_from[next] = Some(index);
+						open.push(PathNode { estimate: next_cost + estimate(next_col, next_row), index: next });
+					}
+				}
+			}
+		}
+
+		None
+	}
+
+	/// The backdrop, kinds of tiles & grid the map could be rebuilt from
+	/// by `from_grid`, e.g. when a game is saved.
+	pub fn to_grid(&self) -> (String, Vec<TileDef>, Vec<Vec<usize>>) {
+		let grid = self.tiles.iter().map(|row| row.iter().map(|tile| tile.kind).collect()).collect();
+		(self.background.path().to_string(), self.kinds.clone(), grid)
 	}
 
 	pub fn get_tiles(self) -> Vec<Box<Vec<Box<Tile>>>> {
@@ -191,15 +644,12 @@ impl Map {
 	}
 
 	pub fn on_screen(&self, map_x: units::Game, map_y: units::Game) -> bool {
-		let lower_limit_x = units::Tile(self.page_x * 20).to_game();
-		let upper_limit_x = lower_limit_x + units::Tile(20).to_game();
-		let lower_limit_y = units::Tile(self.page_y * 20).to_game();
-		let upper_limit_y = lower_limit_y + units::Tile(20).to_game();
-		if map_x <= upper_limit_x && map_x >= lower_limit_x && map_y <= upper_limit_y && map_y >= lower_limit_y {
-			true
-		} else {
-			false
-		}
+		self.camera.on_screen(map_x, map_y)
+	}
+
+	/// The area to query a `spatial::SpatialHash` w/ for what may be on screen.
+	pub fn view_rectangle(&self) -> Rectangle {
+		self.camera.view_rectangle()
 	}
 
 	/// no-op for demo map
@@ -216,9 +666,7 @@ impl Map {
 	}
 
 	/// Checks if `Rectangle` is colliding with any tiles in the foreground.
-	/// 
-	/// NOTE: Checking a Rectangle which would be placed outside the tile-map
-	/// results in a runtime failure!
+	/// Tiles outside the tile-map are walls, as w/ `is_wall`.
 	/// 
 	/// NOTE: This is a simple check of the _outside bounds_ of the
 	/// rectangle & tile. -- This method may claim that the player is 
@@ -234,7 +682,10 @@ impl Map {
 
 		for row in first_row.. last_row + 1 {
 			for col in first_col.. last_col + 1 {
-				let tile_type = self.tiles.get(row as usize).unwrap().get(col as usize).unwrap().tile_type.clone();
+				let tile_type = match self.tiles.get(row as usize).and_then(|tiles| tiles.get(col as usize)) {
+					Some(tile) => { tile.tile_type.clone() },
+					None => { TileType::Wall }
+				};
 				collision_tiles.push( 
 					CollisionTile::new(units::Tile(row), units::Tile(col), tile_type)
 				);
@@ -244,3 +695,184 @@ impl Map {
 		collision_tiles
 	}
 }
+
+/// Why a grid of tiles can't be played on, w/ the tile at fault as
+/// `col, row`.
+pub enum GridError {
+	/// A tile on the edge of the map which isn't a wall, which would let
+	/// actors walk off it.
+	OpenEdge(u32, u32),
+	/// A spawn, named as in a map file (e.g. `player` or `zombie`), placed
+	/// on a wall.
+	SpawnInWall(String, u32, u32)
+}
+
+impl GridError {
+	/// What's wrong, for an error message about the map it was found on.
+	pub fn message(&self) -> String {
+		match *self {
+			GridError::OpenEdge(col, row) => {
+				format!("the map's edge must be wall, ({}, {}) isn't", col, row)
+			},
+			GridError::SpawnInWall(ref name, col, row) => {
+				format!("`{}` spawns inside a wall at ({}, {})", name, col, row)
+			}
+		}
+	}
+}
+
+/// Checks the edge of `grid` is all wall & nothing in `spawns` is placed
+/// on a wall, for `Map::from_grid`.
+fn validate(kinds: &[TileDef], grid: &[Vec<usize>], spawns: &SpawnPoints) -> Result<(), GridError> {
+	let rows = grid.len();
+	for (row, tiles) in grid.iter().enumerate() {
+		let cols = tiles.len();
+		for (col, kind) in tiles.iter().enumerate() {
+			let edge = row == 0 || row == rows - 1 || col == 0 || col == cols - 1;
+			if edge && kinds[*kind].tile_type != TileType::Wall {
+				return Err(GridError::OpenEdge(col as u32, row as u32));
+			}
+		}
+	}
+
+	let is_wall = |&(units::Tile(col), units::Tile(row)): &(units::Tile, units::Tile)| {
+		match grid.get(row as usize).and_then(|tiles| tiles.get(col as usize)) {
+			Some(kind) => { kinds[*kind].tile_type == TileType::Wall },
+			None => { true }
+		}
+	};
+
+	let mut placed: Vec<(&str, (units::Tile, units::Tile))> = Vec::new();
+	for at in spawns.player.iter()  { placed.push(("player", *at)); }
+	for at in spawns.vehicle.iter() { placed.push(("vehicle", *at)); }
+	for at in spawns.parts.iter()   { placed.push(("part", *at)); }
+	for &(_, at) in spawns.zombies.iter()  { placed.push(("zombie", at)); }
+	for &(_, at) in spawns.powerups.iter() { placed.push(("powerup", at)); }
+	for &(_, at) in spawns.traps.iter()    { placed.push(("trap", at)); }
+
+	for &(name, at) in placed.iter() {
+		if is_wall(&at) {
+			let (units::Tile(col), units::Tile(row)) = at;
+			return Err(GridError::SpawnInWall(name.to_string(), col, row));
+		}
+	}
+
+	Ok(())
+}
+
+/// Fails w/ the line of the map file if `asset_path` does not exist.
+fn check_asset(file_path: &str, line_num: usize, asset_path: &str) -> Result<(), String> {
+	match fs::metadata(packs::locate(asset_path)) {
+		Ok(_) => { Ok(()) },
+		Err(_) => { Err(format!("{}:{}: missing asset `{}`", file_path, line_num, asset_path)) }
+	}
+}
+
+fn diff(a: u32, b: u32) -> u32 {
+	if a > b { a - b } else { b - a }
+}
+
+fn parse_number(file_path: &str, line_num: usize, field: &str) -> Result<u32, String> {
+	match field.parse::<u32>() {
+		Ok(value) => { Ok(value) },
+		Err(_) => { Err(format!("{}:{}: expected a number, found `{}`", file_path, line_num, field)) }
+	}
+}
+
+/// Parses a `<col> <row>` pair which must lie inside a map of `size`.
+fn parse_position(file_path: &str, line_num: usize, size: Option<(u32, u32)>,
+                  fields: &[&str]) -> Result<(units::Tile, units::Tile), String> {
+	let col = try!(parse_number(file_path, line_num, fields[0]));
+	let row = try!(parse_number(file_path, line_num, fields[1]));
+	let (cols, rows) = match size {
+		Some(size) => { size },
+		None => { return Err(format!("{}:{}: `size` must come before spawn points", file_path, line_num)); }
+	};
+	if col >= cols || row >= rows {
+		return Err(format!("{}:{}: ({}, {}) is outside the {}x{} map", file_path, line_num, col, row, cols, rows));
+	}
+
+	Ok((units::Tile(col), units::Tile(row)))
+}
+
+#[cfg(test)]
+mod tests {
+	use std::env;
+	use std::fs;
+	use std::fs::File;
+	use std::io::Write;
+
+	use super::{Map, SpawnPoints, TileDef, TileType};
+	use game::units;
+
+	/// Writes `contents` to a map file called `name` & loads it.
+	fn load(name: &str, contents: &str) -> Result<Map, String> {
+		let path = env::temp_dir().join(format!("just-run-{}.map", name));
+		File::create(&path).unwrap().write_all(contents.as_bytes()).unwrap();
+		let file_path = path.to_str().unwrap().to_string();
+		let map = Map::from_file(&file_path);
+		fs::remove_file(&path).unwrap();
+		map
+	}
+
+	/// A map of `rows`, where `#` is wall & anything else is air.
+	fn grid_map(rows: &[&str]) -> Map {
+		let kinds = vec![
+			TileDef { tile_type: TileType::Air,  sprite: None },
+			TileDef { tile_type: TileType::Wall, sprite: None }
+		];
+		let grid: Vec<Vec<usize>> = rows.iter().map(|row| {
+			row.chars().map(|symbol| if symbol == '#' { 1 } else { 0 }).collect()
+		}).collect();
+		Map::from_grid(String::new(), &kinds, &grid, SpawnPoints::new()).ok().unwrap()
+	}
+
+	fn tiles(path: &[(u32, u32)]) -> Vec<(units::Tile, units::Tile)> {
+		path.iter().map(|&(col, row)| (units::Tile(col), units::Tile(row))).collect()
+	}
+
+	#[test]
+	fn open_edge_names_its_row() {
+		let err = load("open-edge", "just-run map 1\nsize 4 4\nplayer 1 1\ntiles\n####\n...#\n#..#\n####\n").err().unwrap();
+		assert!(err.ends_with(":6: the map's edge must be wall, (0, 1) isn't"), "{}", err);
+	}
+
+	#[test]
+	fn spawn_in_wall_names_its_spawn() {
+		let err = load("spawn-in-wall", "just-run map 1\nsize 4 4\nvehicle 2 1\nplayer 1 2\ntiles\n####\n#..#\n##.#\n####\n").err().unwrap();
+		assert!(err.ends_with(":4: `player` spawns inside a wall at (1, 2)"), "{}", err);
+	}
+
+	#[test]
+	fn valid_map_loads() {
+		let map = load("valid", "just-run map 1\nsize 4 3\nplayer 1 1\ntiles\n####\n#..#\n####\n").ok().unwrap();
+		assert!(map.is_wall(units::Tile(0), units::Tile(1)));
+		assert!(!map.is_wall(units::Tile(2), units::Tile(1)));
+	}
+
+	#[test]
+	fn path_goes_round_walls() {
+		let map = grid_map(&[
+			"#####",
+			"#.#.#",
+			"#.#.#",
+			"#...#",
+			"#####"
+		]);
+		// no cutting the corners of the wall in the middle
+		let path = map.find_path((units::Tile(1), units::Tile(1)), (units::Tile(3), units::Tile(1)));
+		assert!(path == Some(tiles(&[(1, 2), (1, 3), (2, 3), (3, 3), (3, 2), (3, 1)])));
+	}
+
+	#[test]
+	fn walled_off_goal_has_no_path() {
+		let map = grid_map(&[
+			"#####",
+			"#.#.#",
+			"#.#.#",
+			"#.#.#",
+			"#####"
+		]);
+		assert!(map.find_path((units::Tile(1), units::Tile(1)), (units::Tile(3), units::Tile(1))).is_none());
+	}
+}
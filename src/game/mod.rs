@@ -1,9 +1,12 @@
-pub use game::game::Game;
+pub use game::game::{Game, Options};
+pub use game::world::World;
 pub use game::game::{SCREEN_WIDTH, LEVEL_WIDTH, SCREEN_HEIGHT, LEVEL_HEIGHT};
 
 pub mod game;
+pub mod world;
 pub mod units;
 pub mod backdrop;
+pub mod camera;
 pub mod collisions;
 pub mod common;
 pub mod enemies;
@@ -16,4 +19,19 @@ pub mod heli;
 pub mod car;
 pub mod vehicle;
 pub mod powerups;
-pub mod traps;
\ No newline at end of file
+pub mod random;
+pub mod tiled;
+pub mod replay;
+pub mod spatial;
+pub mod traps;
+pub mod save;
+pub mod storage;
+pub mod leaderboard;
+pub mod settings;
+pub mod controls;
+pub mod gamepad;
+pub mod sounds;
+pub mod text;
+pub mod animation;
+pub mod assets;
+pub mod packs;
\ No newline at end of file
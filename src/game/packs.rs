@@ -0,0 +1,189 @@
+use std::cell::RefCell;
+use std::fs;
+use std::fs::File;
+use std::io::{BufRead, BufReader};
+use std::path::{Path, PathBuf};
+use std::string::String;
+use std::vec::Vec;
+
+use game::units;
+
+static PACK_HEADER: &'static str = "just-run pack 1";
+static PACK_FILE:   &'static str = "pack.cfg";
+
+/// Where packs given by name, rather than by path, are looked for.
+pub static PACK_DIRECTORY: &'static str = "packs";
+
+/// What every asset's path starts w/; the rest of it is looked up in
+/// each pack, e.g. `assets/base/heart.bmp` as `<pack>/base/heart.bmp`.
+pub static ASSET_ROOT: &'static str = "assets/";
+
+/// A directory of assets laid over the base `assets/`: anything in it
+/// replaces the file at the same path there.
+pub struct Pack {
+	pub name:      String,
+	/// What the pack was asked for by: a name or a directory.
+	pub source:    String,
+	pub root:      PathBuf,
+	/// How many pixels wide the pack's tiles are drawn at.
+	pub tile_size: u32
+}
+
+impl Pack {
+	/// Reads the pack at `name`: a directory, or the name of one in
+	/// `PACK_DIRECTORY`. Its `pack.cfg` is a plain text file:
+	///
+	///- `just-run pack 1`
+	///- `name <name>` what it's called when reported, if not its directory's name
+	///- `tile_size <pixels>` the size its art is drawn at, e.g. `16` for the
+	///  freeware release or `32` (the default) for CaveStory+
+	///
+	/// Lines starting w/ `#` are comments. Returns an error naming the
+	/// offending line if the file is malformed or missing.
+	pub fn load(name: &str) -> Result<Pack, String> {
+		let root = if name.contains('/') || name.contains('\\') {
+			PathBuf::from(name)
+		} else {
+			Path::new(PACK_DIRECTORY).join(name)
+		};
+		let file_path = root.join(PACK_FILE);
+		let file = match File::open(&file_path) {
+			Ok(file) => { file },
+			Err(msg) => { return Err(format!("could not open pack {}: {}", file_path.display(), msg)); }
+		};
+
+		let mut pack = Pack { name: name.to_string(), source: name.to_string(), root: root.clone(), tile_size: units::TILE_SIZE as u32 };
+		for (index, line) in BufReader::new(file).lines().enumerate() {
+			let line_num = index + 1;
+			let line = match line {
+				Ok(line) => { line },
+				Err(msg) => { return Err(format!("{}:{}: {}", file_path.display(), line_num, msg)); }
+			};
+
+			if line_num == 1 {
+				if line.trim() != PACK_HEADER {
+					return Err(format!("{}:{}: not a pack file", file_path.display(), line_num));
+				}
+				continue;
+			}
+
+			let fields: Vec<&str> = line.split_whitespace().collect();
+			if fields.len() == 0 || fields[0].starts_with("#") {
+				continue;
+			}
+
+			match (fields[0], fields.len()) {
+				("name", _) if fields.len() > 1 => { pack.name = fields[1..].join(" "); },
+				("tile_size", 2) => {
+					pack.tile_size = match fields[1].parse::<u32>() {
+						Ok(size) if size > 0 => { size },
+						_ => { return Err(format!("{}:{}: expected a tile size in pixels, found `{}`", file_path.display(), line_num, fields[1])); }
+					};
+				},
+				_ => { return Err(format!("{}:{}: unknown line `{}`", file_path.display(), line_num, line.trim())); }
+			}
+		}
+
+		Ok(pack)
+	}
+}
+
+// the packs in use, the last laid over the rest
+thread_local!(static ACTIVE: RefCell<Vec<Pack>> = RefCell::new(Vec::new()));
+
+/// Lays the packs in `names` over the base assets, each over the ones
+/// before it. A pack which can't be read is reported & left out.
+pub fn activate(names: &[String]) {
+	let mut packs = Vec::new();
+	for name in names.iter() {
+		match Pack::load(name) {
+			Ok(pack) => {
+				println!("using asset pack {} ({}px tiles)", pack.name, pack.tile_size);
+				packs.push(pack);
+			},
+			Err(msg) => { println!("{}, leaving it out", msg); }
+		}
+	}
+
+	ACTIVE.with(|active| { *active.borrow_mut() = packs; });
+}
+
+/// The packs in use, bottom first, as `activate` would take them.
+pub fn active() -> Vec<String> {
+	ACTIVE.with(|active| active.borrow().iter().map(|pack| pack.source.clone()).collect())
+}
+
+/// Where the file at `path` really is: in the topmost pack which has it,
+/// if it's an asset, or else at `path` itself.
+pub fn locate(path: &str) -> PathBuf {
+	match find(path) {
+		Some((found, _)) => { found },
+		None => { PathBuf::from(path) }
+	}
+}
+
+/// How many pixels wide the tiles of the image at `path` are: its pack's
+/// tile size, or the base assets' if no pack replaces it.
+pub fn tile_size(path: &str) -> u32 {
+	match find(path) {
+		Some((_, tile_size)) => { tile_size },
+		None => { units::TILE_SIZE as u32 }
+	}
+}
+
+fn find(path: &str) -> Option<(PathBuf, u32)> {
+	if !path.starts_with(ASSET_ROOT) {
+		return None;
+	}
+
+	let relative = &path[ASSET_ROOT.len()..];
+	ACTIVE.with(|active| {
+		active.borrow().iter().rev()
+			.map(|pack| (pack.root.join(relative), pack.tile_size))
+			.find(|&(ref found, _)| fs::metadata(found).is_ok())
+	})
+}
+
+#[cfg(test)]
+mod tests {
+	use std::env;
+	use std::fs;
+	use std::fs::File;
+	use std::io::Write;
+	use std::path::{Path, PathBuf};
+
+	use super::{activate, active, locate, tile_size};
+	use game::units;
+
+	/// Makes a pack at `root` w/ `pack_cfg` & an empty file at each of `assets`.
+	fn make_pack(root: &Path, pack_cfg: &str, assets: &[&str]) {
+		let _ = fs::remove_dir_all(root);
+		fs::create_dir_all(root.join("base")).unwrap();
+		File::create(root.join("pack.cfg")).unwrap().write_all(pack_cfg.as_bytes()).unwrap();
+		for asset in assets.iter() {
+			File::create(root.join(asset)).unwrap();
+		}
+	}
+
+	#[test]
+	fn topmost_pack_wins() {
+		let (low, high) = (env::temp_dir().join("just-run-low"), env::temp_dir().join("just-run-high"));
+		make_pack(&low, "just-run pack 1\ntile_size 16\n", &["base/heart.bmp", "base/bkBlue.bmp"]);
+		make_pack(&high, "just-run pack 1\n", &["base/heart.bmp"]);
+		let names = vec![low.to_str().unwrap().to_string(), "no-such-pack".to_string(), high.to_str().unwrap().to_string()];
+		activate(&names);
+
+		// a pack which can't be read is left out
+		assert_eq!(active(), vec![names[0].clone(), names[2].clone()]);
+		assert_eq!(locate("assets/base/heart.bmp"), high.join("base/heart.bmp"));
+		assert_eq!(tile_size("assets/base/heart.bmp"), units::TILE_SIZE as u32);
+		assert_eq!(locate("assets/base/bkBlue.bmp"), low.join("base/bkBlue.bmp"));
+		assert_eq!(tile_size("assets/base/bkBlue.bmp"), 16);
+		// what no pack has comes from the base assets, & only assets are looked up
+		assert_eq!(locate("assets/base/MyChar.bmp"), PathBuf::from("assets/base/MyChar.bmp"));
+		assert_eq!(locate("base/heart.bmp"), PathBuf::from("base/heart.bmp"));
+
+		fs::remove_dir_all(&low).unwrap();
+		fs::remove_dir_all(&high).unwrap();
+	}
+}
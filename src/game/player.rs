@@ -1,5 +1,5 @@
-use std::collections::HashMap;
-
+use game::animation;
+use game::camera;
 use game::graphics;
 use game::sprite;
 
@@ -12,42 +12,60 @@ use game::common::Character;
 
 pub type MotionTup = (sprite::Motion, sprite::Facing);
 
-// player sprite animation
-const CHAR_OFFSET:          u32           = 12;
-const CRICKET_OFFSET:       u32           = 10;
-static SPRITE_NUM_FRAMES:   units::Frame  = 3;
-static SPRITE_FPS:          units::Fps    = 20;
-static STANDING_HIT_FRAMES: units::Frame  = 2;
-static WALKING_HIT_FRAMES:  units::Frame  = 4;
-static HIT_FPS:             units::Fps    = 10;
-
 static WALKING_ACCEL:  units::Acceleration  = units::Acceleration(0.00083007812);
 static MAX_VELOCITY: units::Velocity      = units::Velocity(0.17859375);
 
-// motion
-static STAND_FRAME: units::Tile   = units::Tile(0);
-static TELEPORT_FRAME: units::Tile = units::Tile(3);
-static TELEPORT_ANIMATION_FRAME: units::Tile = units::Tile(1);
-static HIT_FRAME: units::Tile = units::Tile(3);
+/// What the player looks like while moving.
+#[derive(Hash,Eq,PartialEq,Clone,Copy)]
+pub enum Look {
+	Plain,
+	Bat,
+	Teleport,
+	Hit
+}
+
+// each look & the prefix of its sprites' names
+static LOOKS: [(Look, &'static str); 4] = [
+	(Look::Plain, "player."), (Look::Bat, "player.bat."),
+	(Look::Teleport, "player.teleport."), (Look::Hit, "player.hit.")
+];
+
+/// The clips the player's animator picks from.
+#[derive(Hash,Eq,PartialEq,Clone,Copy)]
+pub enum PlayerClip {
+	Moving(Look, MotionTup),
+	/// Plays once as a teleport starts, then hands over to standing
+	/// teleported facing the same way.
+	TeleportFlash(sprite::Facing),
+	Dying
+}
 
-// horizontal facing (Facing)
-static FACING_WEST: units::Tile  = units::Tile(0 + CHAR_OFFSET);
-static FACING_EAST: units::Tile  = units::Tile(1 + CHAR_OFFSET);
-static CRICKET_FACING_WEST: units::Tile  = units::Tile(0 + CRICKET_OFFSET);
-static CRICKET_FACING_EAST: units::Tile  = units::Tile(1 + CRICKET_OFFSET);
+/// Everything about the player a saved game has to bring back.
+#[derive(Clone)]
+pub struct PlayerState {
+	pub character:      common::CharacterState,
+	pub health:         u32,
+	pub cricket_bat:    bool,
+	pub teleport_timer: i32,
+	pub immunity_timer: i32,
+	pub trap_kits:      Vec<u32>
+}
 
 pub struct Player {
 	pub character: common::Character,
-	cricket_sprites: HashMap<MotionTup, Box<sprite::Updatable<units::Game>>>,
-	teleport_sprites: HashMap<MotionTup, Box<sprite::Updatable<units::Game>>>,
-	hit_sprites: HashMap<MotionTup, Box<sprite::Updatable<units::Game>>>,
+	animator: animation::Animator<PlayerClip>,
 	cricket_bat: bool,
+	trap_kits: Vec<u32>,
 	teleport_timer: i32, 
 	immunity_timer: i32,
 	health: u32,
 	follow_x: units::Game,
 	follow_y: units::Game,
-	update_count: i32
+	update_count: i32,
+
+	// how hard the player is pushing each way, from 0.0 to 1.0
+	throttle_x: f64,
+	throttle_y: f64
 }
 
 impl Player {
@@ -57,32 +75,26 @@ impl Player {
 	/// The player will spawn at `x` and `y`, though it will immediately be subject to gravity.
 	/// The player is initailized `standing` facing `east`.
 	/// The player will continue to fall until some collision is detected.
-	pub fn new(graphics: &mut graphics::Graphics, x: units::Game, y: units::Game) -> Player {
-		let cricket = HashMap::<MotionTup, Box<sprite::Updatable<_>>>::new();
-		let teleport = HashMap::<MotionTup, Box<sprite::Updatable<_>>>::new();
-		let hit = HashMap::<MotionTup, Box<sprite::Updatable<_>>>::new();
+	pub fn new(x: units::Game, y: units::Game) -> Player {
 		// construct new player
+		let character = common::Character::new(x, y);
 		let mut new_player = Player{
-			character: common::Character::new(x, y),
-			cricket_sprites: cricket,
-			teleport_sprites: teleport,
-			hit_sprites: hit,
+			animator: animation::Animator::new(PlayerClip::Moving(Look::Plain, character.movement)),
+			character: character,
 			cricket_bat: false,
+			trap_kits: Vec::new(),
 			teleport_timer: 0, 
 			immunity_timer: 0,
 			health: 3, 
 			follow_x: units::Game(0 as f64),
 			follow_y: units::Game(0 as f64),
-			update_count: 0
-		};
+			update_count: 0,
 
-		// load sprites for every possible movement tuple.
-		for motion in sprite::MOTIONS.iter() {
-			for facing in sprite::FACINGS.iter() {
-				new_player.load_sprite(graphics, (*motion, *facing));
-			}
-		}
+			throttle_x: 1.0,
+			throttle_y: 1.0
+		};
 
+		new_player.load_clips();
 		new_player
 	}
 
@@ -112,196 +124,73 @@ impl Player {
 
 		// update sprite
 		self.character.current_motion(); // update motion once at beginning of frame for consistency
-		self.character.sprites.get_mut(&self.character.movement).unwrap().update(elapsed_time);
-		if self.has_bat() {
-			self.cricket_sprites.get_mut(&self.character.movement).unwrap().update(elapsed_time);
-		}
-		if self.teleport_timer > 0 {
-			self.teleport_sprites.get_mut(&self.character.movement).unwrap().update(elapsed_time);
-		}
-		if self.immunity_timer > 0 {
-			self.hit_sprites.get_mut(&self.character.movement).unwrap().update(elapsed_time);
-		}
-		if self.character.is_killed() {
-			self.character.killed_sprite.get_mut(0).unwrap().update(elapsed_time);
-		}
-
-		// run physics sim
-		self.character.update_x(map, WALKING_ACCEL, MAX_VELOCITY);
-		self.character.update_y(map, WALKING_ACCEL, MAX_VELOCITY);
+		let clip = self.pick_clip();
+		self.animator.request(clip);
+		self.animator.update(elapsed_time);
+
+		// run physics sim, a light push on the stick walks slower
+		let (units::Acceleration(accel), units::Velocity(max_velocity)) = (WALKING_ACCEL, MAX_VELOCITY);
+		self.character.update_x(map, units::Acceleration(accel * self.throttle_x), units::Velocity(max_velocity * self.throttle_x));
+		self.character.update_y(map, units::Acceleration(accel * self.throttle_y), units::Velocity(max_velocity * self.throttle_y));
+		self.character.clear_slow_down();
 	}
 
-	/// Loads a sprite for the selected `movement`, stores it in the player's sprite map.
-	/// This exhaustively matches all tuples of (Motion,Facing,Looking), though certain
-	/// sprites are considered invalid states [for e.g: walking + looking down]
-	fn load_sprite(
-		&mut self, 
-		graphics: &mut graphics::Graphics, 
-		movement: (sprite::Motion, sprite::Facing)
-	) {
-		self.character.load_killed_sprite(graphics);
-		self.cricket_sprites.insert(movement, {
-			let file_path = "assets/MyChar.bmp".to_string();
-			let (_, facing) = movement;
-			let motion_frame = STAND_FRAME;
-
-			let facing_frame = match facing {
-				sprite::Facing::West => CRICKET_FACING_WEST,
-				sprite::Facing::East => CRICKET_FACING_EAST
-			};
-
-			match movement {
-				// static: standing in place
-				(sprite::Motion::Standing, _) => {
-					Box::new( sprite::Sprite::new(
-						graphics, 
-						(motion_frame, facing_frame), 
-						(units::Tile(1), units::Tile(1)),	
-						file_path
-					) ) as Box<sprite::Updatable<_>> 
-				}
-
-				// dynamic: 
-				(sprite::Motion::Walking, _) => {
-					Box::new( sprite::AnimatedSprite::new(
-						graphics, file_path,
-						(motion_frame, facing_frame),
-						(units::Tile(1), units::Tile(1)),
-						SPRITE_NUM_FRAMES, SPRITE_FPS
-					).unwrap() ) as Box<sprite::Updatable<_>>
-				}
-			}
-		});
-		self.teleport_sprites.insert(movement, {
-			let file_path = "assets/MyChar.bmp".to_string();
-			let (_, facing) = movement;
-			let motion_frame = TELEPORT_FRAME;
-
-			let facing_frame = match facing {
-				sprite::Facing::West => CRICKET_FACING_WEST,
-				sprite::Facing::East => CRICKET_FACING_EAST
-			};
-
-			match movement {
-				// static: standing in place
-				(sprite::Motion::Standing, _) => {
-					Box::new( sprite::Sprite::new(
-						graphics, 
-						(motion_frame, facing_frame), 
-						(units::Tile(1), units::Tile(1)),	
-						file_path
-					) ) as Box<sprite::Updatable<_>> 
-				}
-
-				// dynamic: 
-				(sprite::Motion::Walking, _) => {
-					Box::new( sprite::AnimatedSprite::new(
-						graphics, file_path,
-						(motion_frame, facing_frame),
-						(units::Tile(1), units::Tile(1)),
-						SPRITE_NUM_FRAMES, SPRITE_FPS
-					).unwrap() ) as Box<sprite::Updatable<_>>
-				}
-			}
-		});
-		self.hit_sprites.insert(movement, {
-			let file_path = "assets/MyChar.bmp".to_string();
-			let (_, facing) = movement;
-			let motion_frame = HIT_FRAME;
-
-			let facing_frame = match facing {
-				sprite::Facing::West => FACING_WEST,
-				sprite::Facing::East => FACING_EAST
-			};
-
-			match movement {
-				// static: standing in place
-				(sprite::Motion::Standing, _) => {
-					Box::new( sprite::AnimatedSprite::new(
-						graphics, file_path,
-						(motion_frame, facing_frame),
-						(units::Tile(1), units::Tile(1)),
-						STANDING_HIT_FRAMES, HIT_FPS
-					).unwrap() ) as Box<sprite::Updatable<_>>
-				}
-
-				// dynamic: 
-				(sprite::Motion::Walking, _) => {
-					Box::new( sprite::AnimatedSprite::new(
-						graphics, file_path,
-						(motion_frame, facing_frame),
-						(units::Tile(1), units::Tile(1)),
-						WALKING_HIT_FRAMES, HIT_FPS
-					).unwrap() ) as Box<sprite::Updatable<_>>
+	/// Hands the animator a clip for every look & movement from the
+	/// sprite manifest, e.g. `player.walk.east` & the same w/ the bat
+	/// (`player.bat.walk.east`), teleporting (`player.teleport.walk.east`)
+	/// & hit (`player.hit.walk.east`), along w/ `player.teleport.flash` &
+	/// `player.dying`.
+	fn load_clips(&mut self) {
+		for motion in sprite::MOTIONS.iter() {
+			for facing in sprite::FACINGS.iter() {
+				let movement = (*motion, *facing);
+				let name = sprite::movement_name(movement);
+				for &(look, prefix) in LOOKS.iter() {
+					self.animator.add_clip(PlayerClip::Moving(look, movement), sprite::named(&format!("{}{}", prefix, name)));
 				}
 			}
-		});
-		self.character.sprites.insert(movement, {
-			let file_path = "assets/MyChar.bmp".to_string();
-			let (_, facing) = movement;
-			let motion_frame = STAND_FRAME;
-
-			let facing_frame = match facing {
-				sprite::Facing::West => FACING_WEST,
-				sprite::Facing::East => FACING_EAST
-			};
-
-			match movement {
-				// static: standing in place
-				(sprite::Motion::Standing, _) => {
-					Box::new( sprite::Sprite::new(
-						graphics, 
-						(motion_frame, facing_frame), 
-						(units::Tile(1), units::Tile(1)),	
-						file_path
-					) ) as Box<sprite::Updatable<_>> 
-				}
+		}
 
-				// dynamic: 
-				(sprite::Motion::Walking, _) => {
-					Box::new( sprite::AnimatedSprite::new(
-						graphics, file_path,
-						(motion_frame, facing_frame),
-						(units::Tile(1), units::Tile(1)),
-						SPRITE_NUM_FRAMES, SPRITE_FPS
-					).unwrap() ) as Box<sprite::Updatable<_>>
-				}
-			}
-		});
+		for facing in sprite::FACINGS.iter() {
+			let flash = PlayerClip::TeleportFlash(*facing);
+			self.animator.add_clip(flash, sprite::named("player.teleport.flash"));
+			self.animator.then(flash, PlayerClip::Moving(Look::Teleport, (sprite::Motion::Standing, *facing)));
+		}
+		self.animator.add_clip(PlayerClip::Dying, sprite::named("player.dying"));
 	}
 
-	//draw the player
-	pub fn draw(&self, display: &mut graphics::Graphics) {
-		if self.cricket_bat {
-			self.cricket_sprites.get(&self.character.movement).unwrap().draw(display, (self.character.x, self.character.y));
+	/// The clip the player's state calls for. Dying outranks everything,
+	/// then carrying the bat, then teleporting (w/ the flash for the first
+	/// few updates) & then being hit.
+	fn pick_clip(&self) -> PlayerClip {
+		let (_, facing) = self.character.movement;
+		if self.character.is_killed() {
+			PlayerClip::Dying
+		} else if self.cricket_bat {
+			PlayerClip::Moving(Look::Bat, self.character.movement)
+		} else if self.teleport_timer > 15 {
+			PlayerClip::TeleportFlash(facing)
 		} else if self.teleport_timer > 0 {
-			match self.teleport_timer {
-				x if x > 15 => {
-					let asset_path = "assets/base/teleport.bmp".to_string();
-					let motion_frame = TELEPORT_ANIMATION_FRAME;
-					let facing_frame = units::Tile(0);
-					let teleport_sprite = Box::new( sprite::Sprite::new(
-						display, 
-						(motion_frame, facing_frame),
-						(units::Tile(1), units::Tile(1)),
-						asset_path
-					) ) as Box<sprite::Updatable<_>>;
-					teleport_sprite.draw(display, (self.character.x, self.character.y));
-				},
-				_ => { self.teleport_sprites.get(&self.character.movement).unwrap().draw(display, (self.character.x, self.character.y)); }
-			}
+			PlayerClip::Moving(Look::Teleport, self.character.movement)
 		} else if self.immunity_timer > 0 {
-			self.hit_sprites.get(&self.character.movement).unwrap().draw(display, (self.character.x, self.character.y));
+			PlayerClip::Moving(Look::Hit, self.character.movement)
 		} else {
-			self.character.draw(display);
+			PlayerClip::Moving(Look::Plain, self.character.movement)
 		}
 	}
 
+	//draw the player
+	pub fn draw(&self, display: &mut graphics::Graphics, camera: &camera::Camera) {
+		let screen = camera.to_screen(self.character.map_x, self.character.map_y);
+		self.animator.draw(display, screen);
+	}
+
 	/// The player will immediately face `West`
 	/// They will then accelerate at a constant rate in that direction.
 	pub fn start_moving_left(&mut self) {
 		self.character.set_facing(sprite::Facing::West);
 		self.character.accel_x = -1;
+		self.throttle_x = 1.0;
 	}
 
 	/// The player will immediately face `East`
@@ -309,14 +198,39 @@ impl Player {
 	pub fn start_moving_right(&mut self) {
 		self.character.set_facing(sprite::Facing::East);
 		self.character.accel_x = 1;
+		self.throttle_x = 1.0;
 	}
 
 	pub fn start_moving_up(&mut self) {
 		self.character.accel_y = -1;
+		self.throttle_y = 1.0;
 	}
 
 	pub fn start_moving_down(&mut self) {
 		self.character.accel_y = 1;
+		self.throttle_y = 1.0;
+	}
+
+	/// Like `start_moving_left()`/`start_moving_right()`, but w/ `throttle`
+	/// (from -1.0 to 1.0) scaling how hard they accelerate & how fast they go.
+	pub fn steer_horizontally(&mut self, throttle: f64) {
+		if throttle < 0.0 {
+			self.start_moving_left();
+		} else {
+			self.start_moving_right();
+		}
+		self.throttle_x = throttle.abs().min(1.0);
+	}
+
+	/// Like `start_moving_up()`/`start_moving_down()`, but w/ `throttle`
+	/// (from -1.0 to 1.0) scaling how hard they accelerate & how fast they go.
+	pub fn steer_vertically(&mut self, throttle: f64) {
+		if throttle < 0.0 {
+			self.start_moving_up();
+		} else {
+			self.start_moving_down();
+		}
+		self.throttle_y = throttle.abs().min(1.0);
 	}
 
 	/// The player will immediately cease acceleration.
@@ -347,6 +261,20 @@ impl Player {
 		self.cricket_bat
 	}
 
+	/// Adds a kit for a trap of `kind` (see `traps::KINDS`) to the inventory.
+	pub fn give_trap_kit(&mut self, kind: u32) {
+		self.trap_kits.push(kind);
+	}
+
+	/// Takes out the kit picked up last, if any are left.
+	pub fn take_trap_kit(&mut self) -> Option<u32> {
+		self.trap_kits.pop()
+	}
+
+	pub fn trap_kits(&self) -> usize {
+		self.trap_kits.len()
+	}
+
 	pub fn start_teleport_timer(&mut self) {
 		self.teleport_timer = 20;
 	}
@@ -374,4 +302,28 @@ impl Player {
 	pub fn get_follow_coords(&self) -> (units::Game, units::Game) {
 		(self.follow_x, self.follow_y)
 	}
+
+	pub fn state(&self) -> PlayerState {
+		PlayerState {
+			character:      self.character.state(),
+			health:         self.health,
+			cricket_bat:    self.cricket_bat,
+			teleport_timer: self.teleport_timer,
+			immunity_timer: self.immunity_timer,
+			trap_kits:      self.trap_kits.clone()
+		}
+	}
+
+	/// Puts the player back as `state` left them. Chasing zombies head for
+	/// where the player is straight away.
+	pub fn restore_state(&mut self, state: &PlayerState) {
+		self.character.restore_state(&state.character);
+		self.health = state.health;
+		self.cricket_bat = state.cricket_bat;
+		self.teleport_timer = state.teleport_timer;
+		self.immunity_timer = state.immunity_timer;
+		self.trap_kits = state.trap_kits.clone();
+		self.follow_x = self.character.map_center_x();
+		self.follow_y = self.character.map_center_y();
+	}
 }
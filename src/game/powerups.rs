@@ -1,37 +1,28 @@
 use std::collections::HashMap;
+use std::fs;
+use std::fs::File;
+use std::io::{BufRead, BufReader};
+use std::rc::Rc;
+use std::vec::Vec;
+use rand::Rng;
 
 use game::collisions::Rectangle;
 use game::sprite;
+use game::camera;
 use game::graphics;
-use game::Game;
 use game::map;
+use game::packs;
+use game::random;
 
 use game::units;
 
 use game::common;
 use game::common::Character;
 
-type MotionTup = (sprite::Motion, sprite::Facing);
-
-static SPRITE_NUM_FRAMES:  units::Frame  = 3;
-static SPRITE_FPS:         units::Fps    = 20;
-
-// Sprite locations
-const KILLZOMBIE_FRAME: units::Tile = units::Tile(0);
-const WIPEOUT_FRAME: units::Tile = units::Tile(1);
-const NUKE_FRAME: units::Tile = units::Tile(2);
-const FREEZE_FRAME: units::Tile = units::Tile(3);
-const CRICKET_FRAME: units::Tile = units::Tile(4);
-const BAD_FRAME: units::Tile = units::Tile(5);
-const TELEPORT_FRAME: units::Tile = units::Tile(6);
-const TELEPORT_ANIMATION_FRAME: units::Tile = units::Tile(0);
-
-// Animation frames 
-const WIPEOUT_ANIMATION_FRAME: units::Tile = units::Tile(1);
-const NUKE_ANIMATION_FRAME: units::Tile = units::Tile(0);
+static POWERUPS_HEADER: &'static str = "just-run powerups 1";
 
 pub trait Powerup {
-	fn draw(&self, display: &mut graphics::Graphics);
+	fn draw(&self, display: &mut graphics::Graphics, camera: &camera::Camera);
 	fn update(&mut self, elapsed_time: units::Millis, map: &map::Map);
 	fn damage_rectangle(&self) -> Rectangle;
 	fn get_type(&self) -> i32;
@@ -43,289 +34,340 @@ pub trait Powerup {
 	fn get_map_y(&self) -> units::Game;
 }
 
-pub struct CricketBat {
-	character: Character, 
-	is_debuff: bool
+/// The named parameters of an effect, e.g. `radius 200`.
+#[derive(Clone)]
+pub struct Params {
+	values: HashMap<String, String>
 }
 
-pub struct KillZombie {
-	character: Character, 
-	animation_timer: i32,
-	is_debuff: bool
-}
+impl Params {
+	pub fn new() -> Params {
+		Params { values: HashMap::new() }
+	}
 
-pub struct WipeOut {
-	character: Character, 
-	animation_sprite: Vec<Box<sprite::Updatable<units::Game>>>, 
-	animation_timer: i32,
-	is_debuff: bool
-}
+	/// The parameter `name` as a number, or `default` if it isn't set.
+	pub fn number(&self, name: &str, default: f64) -> f64 {
+		self.values.get(name).and_then(|value| value.parse::<f64>().ok()).unwrap_or(default)
+	}
 
-pub struct Freeze {
-	character: Character, 
-	is_debuff: bool
+	/// The parameter `name` as text, or `default` if it isn't set.
+	pub fn text<'a>(&'a self, name: &str, default: &'a str) -> &'a str {
+		self.values.get(name).map(|value| &value[..]).unwrap_or(default)
+	}
 }
 
-pub struct Teleport {
-	character: Character, 
-	animation_sprite: Vec<Box<sprite::Updatable<units::Game>>>, 
-	animation_timer: i32,
-	is_debuff: bool
+/// What picking up a powerup does: the effect registered under `effect`
+/// is run w/ `params`, after printing `message` & playing `sound`.
+pub struct Variant {
+	pub frame:   (units::Tile, units::Tile),
+	pub sound:   String,
+	pub message: String,
+	pub effect:  String,
+	pub params:  Params
 }
 
-pub struct Nuke {
-	character: Character, 
-	alternate_sprites: HashMap<MotionTup, Box<sprite::Updatable<units::Game>>>,
-	animation_sprite: Vec<Box<sprite::Updatable<units::Game>>>, 
-	animation_timer: i32,
-	is_debuff: bool
+/// The animation played where a powerup was used, `offset` up & left of it.
+pub struct Animation {
+	pub sheet:  String,
+	pub frame:  (units::Tile, units::Tile),
+	pub size:   (units::Tile, units::Tile),
+	pub frames: units::Frame,
+	pub fps:    units::Fps,
+	pub offset: units::Game
 }
 
-impl CricketBat {
-	pub fn new(graphics: &mut graphics::Graphics,
-	           x: units::Game, y: units::Game) -> CricketBat {
-
-		let mut new_powerup = CricketBat { 
-			character: common::Character::new(x, y), 
-			is_debuff: false
-		};
-
-		for motion in sprite::MOTIONS.iter() {
-			for facing in sprite::FACINGS.iter() {
-				new_powerup.load_sprite(graphics, (*motion, *facing));
-			}
-		}
-
-		new_powerup
-	}
-
-	pub fn load_sprite(&mut self, 
-	               display: &mut graphics::Graphics,
-	               movement: (sprite::Motion, sprite::Facing)) {
-
-		let asset_path = "assets/base/powerups.bmp".to_string();
-		let motion_frame = CRICKET_FRAME;
-
-		let facing_frame = units::Tile(0);
-
-		let loaded_sprite = Box::new( sprite::Sprite::new(
-			display,
-			(motion_frame, facing_frame),
-			(units::Tile(1), units::Tile(1)),
-			asset_path
-		) ) as Box<sprite::Updatable<_>>;
-
-		self.character.sprites.insert(movement, loaded_sprite);
-	}
+/// Everything which sets one kind of powerup apart from another.
+///
+/// Powerups w/ a `debuff` variant flip between it & their usual one
+/// while lying on the level; what they do depends on which is showing.
+pub struct PowerupDef {
+	pub name:      String,
+	pub sheet:     String,
+	pub buff:      Variant,
+	pub debuff:    Option<Variant>,
+	pub animation: Option<Animation>,
+	pub linger:    i32,
+	pub tracer:    bool,
+	pub weight:    u32
 }
 
-impl Powerup for CricketBat {
-	fn draw(&self, display: &mut graphics::Graphics) {
-		let correction_x = self.character.get_map_x() % common::SCREEN_CORRECTION;
-		let correction_y = self.character.get_map_y() % common::SCREEN_CORRECTION;
-		self.character.sprites.get(&self.character.movement).unwrap().draw(display, (correction_x, correction_y));
-	}
-
-	#[allow(unused_variable)]
-	fn update(&mut self, elapsed_time: units::Millis, map: &map::Map) {
-		;
-	}
-
-	fn damage_rectangle(&self) -> Rectangle {
-		self.character.damage_rectangle()
-	}
-
-	fn toggle_debuff(&mut self) {
-		self.is_debuff = !self.is_debuff;
-	}
-
-	fn is_debuff(&self) -> bool {
-		self.is_debuff
-	}
-
-	fn get_type(&self) -> i32 {
-		1
-	}
-
-	fn is_finished(&mut self) -> bool {
-		true
-	}
-
-	fn set_timer(&mut self) {
-		;
-	}
-
-	fn get_map_x(&self) -> units::Game {
-		self.character.map_center_x()
-	}
-
-	fn get_map_y(&self) -> units::Game {
-		self.character.map_center_y()
-	}
+/// Every kind of powerup the game knows, numbered in the order they were
+/// listed. A powerup's kind (see `get_type()`) is its index in here.
+pub struct PowerupDefs {
+	kinds: Vec<Rc<PowerupDef>>
 }
 
-impl KillZombie {
-	pub fn new(graphics: &mut graphics::Graphics,
-	           x: units::Game, y: units::Game) -> KillZombie {
-
-		let mut new_powerup = KillZombie { 
-			character: common::Character::new(x, y), 
-			animation_timer: 0,
-			is_debuff: false
+impl PowerupDefs {
+	/// Reads the kinds of powerups from `file_path`, a plain text file:
+	///
+	///- `just-run powerups 1`
+	///- `powerup <name>` starts a new kind, which the lines after it describe
+	///- `sheet <image>` & `frame <sheet col> <sheet row>`
+	///- `effect <name>`, `param <name> <value>`, `sound <name>` & `message <text>`
+	///  say what picking it up does
+	///- `debuff_frame`, `debuff_effect`, `debuff_param`, `debuff_sound` &
+	///  `debuff_message` describe a debuff variant in the same way
+	///- `animation <image> <col> <row> <width> <height> <frames> <fps> <offset>`
+	///  is played where the powerup was used, for `linger <updates>`
+	///- `tracer` draws a shot from the player to the zombie it killed
+	///- `weight <n>` how often the kind is picked at random, relative to the
+	///  others (defaults to 1, 0 for kinds which only spawn from maps)
+	///
+	/// Lines starting w/ `#` are comments. Returns an error naming the
+	/// offending line if the file is malformed.
+	pub fn load(file_path: &str) -> Result<PowerupDefs, String> {
+		let file = match File::open(packs::locate(file_path)) {
+			Ok(file) => { file },
+			Err(msg) => { return Err(format!("could not open powerups {}: {}", file_path, msg)); }
 		};
 
-		for motion in sprite::MOTIONS.iter() {
-			for facing in sprite::FACINGS.iter() {
-				new_powerup.load_sprite(graphics, (*motion, *facing));
+		let mut drafts: Vec<Draft> = Vec::new();
+		for (index, line) in BufReader::new(file).lines().enumerate() {
+			let line_num = index + 1;
+			let line = match line {
+				Ok(line) => { line },
+				Err(msg) => { return Err(format!("{}:{}: {}", file_path, line_num, msg)); }
+			};
+
+			if line_num == 1 {
+				if line.trim() != POWERUPS_HEADER {
+					return Err(format!("{}:{}: not a powerups file", file_path, line_num));
+				}
+				continue;
 			}
-		}
 
-		new_powerup
-	}
-
-	pub fn load_sprite(&mut self, 
-	               display: &mut graphics::Graphics,
-	               movement: (sprite::Motion, sprite::Facing)) {
-
-		let asset_path = "assets/base/powerups.bmp".to_string();
-		let motion_frame = KILLZOMBIE_FRAME;
-
-		let facing_frame = units::Tile(0);
-
-		let loaded_sprite = Box::new( sprite::Sprite::new(
-			display,
-			(motion_frame, facing_frame),
-			(units::Tile(1), units::Tile(1)),
-			asset_path
-		) ) as Box<sprite::Updatable<_>>;
+			let fields: Vec<&str> = line.split_whitespace().collect();
+			if fields.len() == 0 || fields[0].starts_with("#") {
+				continue;
+			}
 
-		self.character.sprites.insert(movement, loaded_sprite);
-	}
-}
+			if fields[0] == "powerup" {
+				if fields.len() != 2 {
+					return Err(format!("{}:{}: expected `powerup <name>`", file_path, line_num));
+				}
+				if drafts.iter().any(|draft| draft.name == fields[1]) {
+					return Err(format!("{}:{}: powerup `{}` is listed twice", file_path, line_num, fields[1]));
+				}
+				drafts.push(Draft::new(fields[1], line_num));
+				continue;
+			}
 
-impl Powerup for KillZombie {
-	fn draw(&self, display: &mut graphics::Graphics) {
-		let correction_x = self.character.get_map_x() % common::SCREEN_CORRECTION;
-		let correction_y = self.character.get_map_y() % common::SCREEN_CORRECTION;
-		self.character.sprites.get(&self.character.movement).unwrap().draw(display, (correction_x, correction_y));
-	}
+			let draft = match drafts.last_mut() {
+				Some(draft) => { draft },
+				None => { return Err(format!("{}:{}: `{}` must follow a `powerup`", file_path, line_num, fields[0])); }
+			};
+			match (fields[0], fields.len()) {
+				("sheet", 2) => {
+					try!(check_asset(file_path, line_num, fields[1]));
+					draft.sheet = Some(fields[1].to_string());
+				},
+				("frame", 3) => {
+					draft.buff.frame = Some(try!(parse_frame(file_path, line_num, &fields[1..])));
+				},
+				("debuff_frame", 3) => {
+					draft.debuff.frame = Some(try!(parse_frame(file_path, line_num, &fields[1..])));
+				},
+				("sound", 2) => {
+					draft.buff.sound = Some(fields[1].to_string());
+				},
+				("debuff_sound", 2) => {
+					draft.debuff.sound = Some(fields[1].to_string());
+				},
+				("message", _) => {
+					draft.buff.message = Some(fields[1..].join(" "));
+				},
+				("debuff_message", _) => {
+					draft.debuff.message = Some(fields[1..].join(" "));
+				},
+				("effect", 2) => {
+					draft.buff.effect = Some(fields[1].to_string());
+				},
+				("debuff_effect", 2) => {
+					draft.debuff.effect = Some(fields[1].to_string());
+				},
+				("param", 3) => {
+					draft.buff.params.values.insert(fields[1].to_string(), fields[2].to_string());
+				},
+				("debuff_param", 3) => {
+					draft.debuff.params.values.insert(fields[1].to_string(), fields[2].to_string());
+				},
+				("animation", 9) => {
+					try!(check_asset(file_path, line_num, fields[1]));
+					draft.animation = Some(Animation {
+						sheet:  fields[1].to_string(),
+						frame:  try!(parse_frame(file_path, line_num, &fields[2..4])),
+						size:   try!(parse_frame(file_path, line_num, &fields[4..6])),
+						frames: try!(parse_number(file_path, line_num, fields[6])),
+						fps:    try!(parse_number(file_path, line_num, fields[7])),
+						offset: units::Game(try!(parse_number(file_path, line_num, fields[8])) as f64)
+					});
+				},
+				("linger", 2) => {
+					draft.linger = try!(parse_number(file_path, line_num, fields[1])) as i32;
+				},
+				("tracer", 1) => {
+					draft.tracer = true;
+				},
+				("weight", 2) => {
+					draft.weight = try!(parse_number(file_path, line_num, fields[1]));
+				},
+				(other, _) => {
+					return Err(format!("{}:{}: unexpected `{}`", file_path, line_num, other));
+				}
+			}
+		}
 
-	#[allow(unused_variable)]
-	fn update(&mut self, elapsed_time: units::Millis, map: &map::Map) {
-		;
-	}
+		let mut kinds: Vec<Rc<PowerupDef>> = Vec::new();
+		for draft in drafts.into_iter() {
+			kinds.push(Rc::new(try!(draft.finish(file_path))));
+		}
+		if !kinds.iter().any(|kind| kind.weight > 0) {
+			return Err(format!("{}: no powerup has a weight above 0", file_path));
+		}
 
-	fn damage_rectangle(&self) -> Rectangle {
-		self.character.damage_rectangle()
+		Ok(PowerupDefs { kinds: kinds })
 	}
 
-	fn toggle_debuff(&mut self) {
-		self.is_debuff = !self.is_debuff;
+	/// The kind of powerup called `name`.
+	pub fn find(&self, name: &str) -> Option<u32> {
+		self.kinds.iter().position(|kind| kind.name == name).map(|index| index as u32)
 	}
 
-	fn is_debuff(&self) -> bool {
-		self.is_debuff
+	pub fn get(&self, kind: u32) -> Option<&Rc<PowerupDef>> {
+		self.kinds.get(kind as usize)
 	}
 
-	fn get_type(&self) -> i32 {
-		2
+	pub fn iter(&self) -> ::std::slice::Iter<Rc<PowerupDef>> {
+		self.kinds.iter()
 	}
 
-	fn is_finished(&mut self) -> bool {
-		match self.animation_timer {
-			0 => { true },
-			_ => { self.animation_timer = self.animation_timer - 1; false }
+	/// Picks a kind of powerup at random, in proportion to their weights.
+	pub fn pick(&self, rng: &mut random::GameRng) -> u32 {
+		let total = self.kinds.iter().fold(0, |total, kind| total + kind.weight);
+		let mut roll = rng.gen_range(0u32, total);
+		for (index, kind) in self.kinds.iter().enumerate() {
+			if roll < kind.weight {
+				return index as u32;
+			}
+			roll = roll - kind.weight;
 		}
-	}
 
-	fn set_timer(&mut self) {
-		self.animation_timer = 1;
-	}
-
-	fn get_map_x(&self) -> units::Game {
-		self.character.map_center_x()
-	}
-
-	fn get_map_y(&self) -> units::Game {
-		self.character.map_center_y()
+		unreachable!()
 	}
 }
 
-impl WipeOut {
-	pub fn new(graphics: &mut graphics::Graphics,
-	           x: units::Game, y: units::Game) -> WipeOut {
-
-		let animation: Vec<Box<sprite::Updatable<units::Game>>> = Vec::new();
+/// A powerup whose looks & effects come from its `PowerupDef`.
+pub struct DefinedPowerup {
+	character:        Character,
+	def:              Rc<PowerupDef>,
+	kind:             u32,
+	debuff_sprite:    Option<Box<sprite::Updatable<units::Game>>>,
+	animation_sprite: Option<Box<sprite::Updatable<units::Game>>>,
+	animation_timer:  i32,
+	is_debuff:        bool
+}
 
-		let mut new_powerup = WipeOut { 
-			character: common::Character::new(x, y), 
-			animation_sprite: animation,
-			animation_timer: 0,
-			is_debuff: false
+impl DefinedPowerup {
+	pub fn new(kind: u32, def: Rc<PowerupDef>, x: units::Game, y: units::Game) -> DefinedPowerup {
+
+		let mut new_powerup = DefinedPowerup {
+			character:        common::Character::new(x, y),
+			def:              def,
+			kind:             kind,
+			debuff_sprite:    None,
+			animation_sprite: None,
+			animation_timer:  0,
+			is_debuff:        false
 		};
 
 		for motion in sprite::MOTIONS.iter() {
 			for facing in sprite::FACINGS.iter() {
-				new_powerup.load_sprite(graphics, (*motion, *facing));
+				new_powerup.load_sprite((*motion, *facing));
 			}
 		}
+		new_powerup.load_effect_sprites();
 
 		new_powerup
 	}
 
-	pub fn load_sprite(&mut self, 
-	               display: &mut graphics::Graphics,
-	               movement: (sprite::Motion, sprite::Facing)) {
+	pub fn load_sprite(&mut self, movement: (sprite::Motion, sprite::Facing)) {
 
-		let asset_path = "assets/base/powerups.bmp".to_string();
-		let motion_frame = WIPEOUT_FRAME;
-
-		let facing_frame = units::Tile(0);
+		let asset_path = self.def.sheet.clone();
+		let (motion_frame, facing_frame) = self.def.buff.frame;
 
 		let loaded_sprite = Box::new( sprite::Sprite::new(
-			display,
 			(motion_frame, facing_frame),
 			(units::Tile(1), units::Tile(1)),
 			asset_path
 		) ) as Box<sprite::Updatable<_>>;
 
 		self.character.sprites.insert(movement, loaded_sprite);
+	}
 
-		let asset_path = "assets/base/explosion.bmp".to_string();
-		let motion_frame = WIPEOUT_ANIMATION_FRAME;
-		let facing_frame = units::Tile(0);
-		let animation_sprite = Box::new( sprite::AnimatedSprite::new(
-			display, asset_path,
-			(motion_frame, facing_frame),
-			(units::Tile(4), units::Tile(5)),
-			SPRITE_NUM_FRAMES, SPRITE_FPS
-		).unwrap() ) as Box<sprite::Updatable<_>>;
-		self.animation_sprite.push(animation_sprite);
+	fn load_effect_sprites(&mut self) {
+		let def = self.def.clone();
+
+		self.debuff_sprite = def.debuff.as_ref().map(|debuff| {
+			Box::new( sprite::Sprite::new(
+				debuff.frame,
+				(units::Tile(1), units::Tile(1)),
+				def.sheet.clone()
+			) ) as Box<sprite::Updatable<_>>
+		});
+
+		self.animation_sprite = def.animation.as_ref().map(|animation| {
+			if animation.frames > 1 {
+				Box::new( sprite::AnimatedSprite::new(
+					animation.sheet.clone(),
+					animation.frame,
+					animation.size,
+					animation.frames, animation.fps
+				).unwrap() ) as Box<sprite::Updatable<_>>
+			} else {
+				Box::new( sprite::Sprite::new(
+					animation.frame,
+					animation.size,
+					animation.sheet.clone()
+				) ) as Box<sprite::Updatable<_>>
+			}
+		});
 	}
 }
 
-impl Powerup for WipeOut {
-	fn draw(&self, display: &mut graphics::Graphics) {
-		let correction_x = self.character.get_map_x() % common::SCREEN_CORRECTION;
-		let correction_y = self.character.get_map_y() % common::SCREEN_CORRECTION;
-		if self.animation_timer > 0 {
-			self.animation_sprite.get(0).unwrap().draw(display, (correction_x - units::Game(60.0), correction_y - units::Game(60.0)));
-		} else {
-			self.character.sprites.get(&self.character.movement).unwrap().draw(display, (correction_x, correction_y));
+impl Powerup for DefinedPowerup {
+	fn draw(&self, display: &mut graphics::Graphics, camera: &camera::Camera) {
+		let (correction_x, correction_y) = camera.to_screen(self.character.get_map_x(), self.character.get_map_y());
+		match (self.animation_timer > 0, &self.animation_sprite, &self.debuff_sprite) {
+			(true, &Some(ref animation), _) => {
+				let offset = self.def.animation.as_ref().map_or(units::Game(0.0), |animation| animation.offset);
+				animation.draw(display, (correction_x - offset, correction_y - offset));
+			},
+			(false, _, &Some(ref debuff)) if self.is_debuff => {
+				debuff.draw(display, (correction_x, correction_y));
+			},
+			_ => {
+				self.character.sprites.get(&self.character.movement).unwrap().draw(display, (correction_x, correction_y));
+			}
 		}
 	}
 
 	#[allow(unused_variable)]
 	fn update(&mut self, elapsed_time: units::Millis, map: &map::Map) {
-		self.animation_sprite.get_mut(0).unwrap().update(elapsed_time);
+		match self.animation_sprite {
+			Some(ref mut animation) => { animation.update(elapsed_time); },
+			None => {}
+		}
 	}
 
 	fn damage_rectangle(&self) -> Rectangle {
 		self.character.damage_rectangle()
 	}
 
+	/// Only powerups w/ a debuff variant ever turn into one.
 	fn toggle_debuff(&mut self) {
-		self.is_debuff = !self.is_debuff;
+		if self.def.debuff.is_some() {
+			self.is_debuff = !self.is_debuff;
+		}
 	}
 
 	fn is_debuff(&self) -> bool {
@@ -333,7 +375,7 @@ impl Powerup for WipeOut {
 	}
 
 	fn get_type(&self) -> i32 {
-		3
+		self.kind as i32
 	}
 
 	fn is_finished(&mut self) -> bool {
@@ -344,7 +386,7 @@ impl Powerup for WipeOut {
 	}
 
 	fn set_timer(&mut self) {
-		self.animation_timer = 5;
+		self.animation_timer = self.def.linger;
 	}
 
 	fn get_map_x(&self) -> units::Game {
@@ -356,317 +398,166 @@ impl Powerup for WipeOut {
 	}
 }
 
-impl Freeze {
-	pub fn new(graphics: &mut graphics::Graphics,
-	           x: units::Game, y: units::Game) -> Freeze {
-
-		let mut new_powerup = Freeze { 
-			character: common::Character::new(x, y), 
-			is_debuff: false
-		};
-
-		for motion in sprite::MOTIONS.iter() {
-			for facing in sprite::FACINGS.iter() {
-				new_powerup.load_sprite(graphics, (*motion, *facing));
-			}
-		}
-
-		new_powerup
-	}
-
-	pub fn load_sprite(&mut self, 
-	               display: &mut graphics::Graphics,
-	               movement: (sprite::Motion, sprite::Facing)) {
-
-		let asset_path = "assets/base/powerups.bmp".to_string();
-		let motion_frame = FREEZE_FRAME;
-
-		let facing_frame = units::Tile(0);
-
-		let loaded_sprite = Box::new( sprite::Sprite::new(
-			display,
-			(motion_frame, facing_frame),
-			(units::Tile(1), units::Tile(1)),
-			asset_path
-		) ) as Box<sprite::Updatable<_>>;
-
-		self.character.sprites.insert(movement, loaded_sprite);
-	}
+/// One variant of a kind of powerup which is still being read.
+struct VariantDraft {
+	frame:   Option<(units::Tile, units::Tile)>,
+	sound:   Option<String>,
+	message: Option<String>,
+	effect:  Option<String>,
+	params:  Params
 }
 
-impl Powerup for Freeze {
-	fn draw(&self, display: &mut graphics::Graphics) {
-		let correction_x = self.character.get_map_x() % common::SCREEN_CORRECTION;
-		let correction_y = self.character.get_map_y() % common::SCREEN_CORRECTION;
-		self.character.sprites.get(&self.character.movement).unwrap().draw(display, (correction_x, correction_y));
-	}
-
-	#[allow(unused_variable)]
-	fn update(&mut self, elapsed_time: units::Millis, map: &map::Map) {
-		;
-	}
-
-	fn damage_rectangle(&self) -> Rectangle {
-		self.character.damage_rectangle()
-	}
-
-	fn toggle_debuff(&mut self) {
-		self.is_debuff = !self.is_debuff;
-	}
-
-	fn is_debuff(&self) -> bool {
-		self.is_debuff
+impl VariantDraft {
+	fn new() -> VariantDraft {
+		VariantDraft { frame: None, sound: None, message: None, effect: None, params: Params::new() }
 	}
 
-	fn get_type(&self) -> i32 {
-		4
+	fn is_empty(&self) -> bool {
+		self.frame.is_none() && self.sound.is_none() && self.message.is_none()
+		&& self.effect.is_none() && self.params.values.len() == 0
 	}
 
-	fn is_finished(&mut self) -> bool {
-		true
-	}
+	/// Fails naming the first missing field, prefixed w/ `prefix`.
+	fn finish(self, prefix: &str) -> Result<Variant, String> {
+		let frame = match self.frame { Some(frame) => frame, None => { return Err(format!("{}frame", prefix)); } };
+		let sound = match self.sound { Some(sound) => sound, None => { return Err(format!("{}sound", prefix)); } };
+		let effect = match self.effect { Some(effect) => effect, None => { return Err(format!("{}effect", prefix)); } };
 
-	fn set_timer(&mut self) {
-		;
-	}
-
-	fn get_map_x(&self) -> units::Game {
-		self.character.map_center_x()
-	}
-
-	fn get_map_y(&self) -> units::Game {
-		self.character.map_center_y()
+		Ok(Variant {
+			frame:   frame,
+			sound:   sound,
+			message: self.message.unwrap_or(effect.to_uppercase()),
+			effect:  effect,
+			params:  self.params
+		})
 	}
 }
 
-impl Teleport {
-	pub fn new(graphics: &mut graphics::Graphics,
-	           x: units::Game, y: units::Game) -> Teleport {
-
-		let animation: Vec<Box<sprite::Updatable<units::Game>>> = Vec::new();
-
-		let mut new_powerup = Teleport { 
-			character: common::Character::new(x, y), 
-			animation_sprite: animation,
-			animation_timer: 0,
-			is_debuff: false
-		};
-
-		for motion in sprite::MOTIONS.iter() {
-			for facing in sprite::FACINGS.iter() {
-				new_powerup.load_sprite(graphics, (*motion, *facing));
-			}
-		}
-
-		new_powerup
-	}
-
-	pub fn load_sprite(&mut self, 
-	               display: &mut graphics::Graphics,
-	               movement: (sprite::Motion, sprite::Facing)) {
-
-		let asset_path = "assets/base/powerups.bmp".to_string();
-		let motion_frame = TELEPORT_FRAME;
-
-		let facing_frame = units::Tile(0);
-
-		let loaded_sprite = Box::new( sprite::Sprite::new(
-			display,
-			(motion_frame, facing_frame),
-			(units::Tile(1), units::Tile(1)),
-			asset_path
-		) ) as Box<sprite::Updatable<_>>;
-
-		self.character.sprites.insert(movement, loaded_sprite);
-
-		let asset_path = "assets/base/teleport.bmp".to_string();
-		let motion_frame = TELEPORT_ANIMATION_FRAME;
-		let facing_frame = units::Tile(0);
-		let animation_sprite = Box::new( sprite::Sprite::new(
-			display, 
-			(motion_frame, facing_frame),
-			(units::Tile(1), units::Tile(1)),
-			asset_path
-		) ) as Box<sprite::Updatable<_>>;
-		self.animation_sprite.push(animation_sprite);
-	}
+/// A kind of powerup which is still being read.
+struct Draft {
+	name:      String,
+	line_num:  usize,
+	sheet:     Option<String>,
+	buff:      VariantDraft,
+	debuff:    VariantDraft,
+	animation: Option<Animation>,
+	linger:    i32,
+	tracer:    bool,
+	weight:    u32
 }
 
-impl Powerup for Teleport {
-	fn draw(&self, display: &mut graphics::Graphics) {
-		let correction_x = self.character.get_map_x() % common::SCREEN_CORRECTION;
-		let correction_y = self.character.get_map_y() % common::SCREEN_CORRECTION;
-		if self.animation_timer > 0 {
-			self.animation_sprite.get(0).unwrap().draw(display, (correction_x - units::Game(60.0), correction_y - units::Game(60.0)));
-		} else {
-			self.character.sprites.get(&self.character.movement).unwrap().draw(display, (correction_x, correction_y));
-		}
-	}
-
-	#[allow(unused_variable)]
-	fn update(&mut self, elapsed_time: units::Millis, map: &map::Map) {
-		self.animation_sprite.get_mut(0).unwrap().update(elapsed_time);
-	}
-
-	fn damage_rectangle(&self) -> Rectangle {
-		self.character.damage_rectangle()
-	}
-
-	fn toggle_debuff(&mut self) {
-		self.is_debuff = !self.is_debuff;
-	}
-
-	fn is_debuff(&self) -> bool {
-		self.is_debuff
-	}
-
-	fn get_type(&self) -> i32 {
-		5
-	}
-
-	fn is_finished(&mut self) -> bool {
-		match self.animation_timer {
-			0 => { true },
-			_ => { self.animation_timer = self.animation_timer - 1; false }
+impl Draft {
+	fn new(name: &str, line_num: usize) -> Draft {
+		Draft {
+			name:      name.to_string(),
+			line_num:  line_num,
+			sheet:     None,
+			buff:      VariantDraft::new(),
+			debuff:    VariantDraft::new(),
+			animation: None,
+			linger:    0,
+			tracer:    false,
+			weight:    1
 		}
 	}
 
-	fn set_timer(&mut self) {
-		self.animation_timer = 4;
-	}
-
-	fn get_map_x(&self) -> units::Game {
-		self.character.map_center_x()
-	}
-
-	fn get_map_y(&self) -> units::Game {
-		self.character.map_center_y()
-	}
-}
-
-impl Nuke {
-	pub fn new(graphics: &mut graphics::Graphics,
-	           x: units::Game, y: units::Game) -> Nuke {
-
-		let animation: Vec<Box<sprite::Updatable<units::Game>>> = Vec::new();
-
-		let mut new_powerup = Nuke { 
-			character: common::Character::new(x, y), 
-			alternate_sprites: HashMap::<MotionTup, Box<sprite::Updatable<_>>>::new(),
-			animation_sprite: animation,
-			animation_timer: 0,
-			is_debuff: false
+	/// Fails w/ the line the powerup started on if a required field is missing.
+	fn finish(self, file_path: &str) -> Result<PowerupDef, String> {
+		let (name, line_num) = (self.name, self.line_num);
+		let missing = |field: String| format!("{}:{}: powerup `{}` has no `{}`", file_path, line_num, name, field);
+		let sheet = match self.sheet { Some(sheet) => sheet, None => { return Err(missing("sheet".to_string())); } };
+		let buff = match self.buff.finish("") { Ok(buff) => buff, Err(field) => { return Err(missing(field)); } };
+		let debuff = if self.debuff.is_empty() {
+			None
+		} else {
+			match self.debuff.finish("debuff_") { Ok(debuff) => Some(debuff), Err(field) => { return Err(missing(field)); } }
 		};
 
-		for motion in sprite::MOTIONS.iter() {
-			for facing in sprite::FACINGS.iter() {
-				new_powerup.load_sprite(graphics, (*motion, *facing));
-			}
-		}
-
-		new_powerup
-	}
-
-	pub fn load_sprite(&mut self, 
-	               display: &mut graphics::Graphics,
-	               movement: (sprite::Motion, sprite::Facing)) {
-
-		let asset_path = "assets/base/powerups.bmp".to_string();
-		let motion_frame = NUKE_FRAME;
-
-		let facing_frame = units::Tile(0);
-
-		let loaded_sprite = Box::new( sprite::Sprite::new(
-			display,
-			(motion_frame, facing_frame),
-			(units::Tile(1), units::Tile(1)),
-			asset_path
-		) ) as Box<sprite::Updatable<_>>;
-
-		self.character.sprites.insert(movement, loaded_sprite);
-
-
-        let asset_path = "assets/base/powerups.bmp".to_string();
-       	let motion_frame = BAD_FRAME;
-
-        let facing_frame = units::Tile(0);
-
-        let alternate_sprite = Box::new( sprite::Sprite::new(
-               display,
-               (motion_frame, facing_frame),
-               (units::Tile(1), units::Tile(1)),
-               asset_path
-        ) ) as Box<sprite::Updatable<_>>;
-
-        self.alternate_sprites.insert(movement, alternate_sprite);
-
-
-		let asset_path = "assets/base/nuke.bmp".to_string();
-		let motion_frame = NUKE_ANIMATION_FRAME;
-
-		let facing_frame = units::Tile(0);
-
-		let animation_sprite = Box::new( sprite::AnimatedSprite::new(
-			display, asset_path,
-			(motion_frame, facing_frame),
-			(units::Tile(20), units::Tile(20)),
-			SPRITE_NUM_FRAMES, 60
-		).unwrap() ) as Box<sprite::Updatable<_>>;
-
-		self.animation_sprite.push(animation_sprite);
+		Ok(PowerupDef {
+			name:      name.clone(),
+			sheet:     sheet,
+			buff:      buff,
+			debuff:    debuff,
+			animation: self.animation,
+			linger:    self.linger,
+			tracer:    self.tracer,
+			weight:    self.weight
+		})
 	}
 }
 
-impl Powerup for Nuke {
-	fn draw(&self, display: &mut graphics::Graphics) {
-		let correction_x = self.character.get_map_x() % common::SCREEN_CORRECTION;
-		let correction_y = self.character.get_map_y() % common::SCREEN_CORRECTION;
-		if self.animation_timer > 0 {
-			self.animation_sprite.get(0).unwrap().draw(display, (correction_x - units::Game(250.0), correction_y - units::Game(250.0)));
-		} else if self.is_debuff {
-			self.alternate_sprites.get(&self.character.movement).unwrap().draw(display, (correction_x, correction_y));
-		} else {
-			self.character.sprites.get(&self.character.movement).unwrap().draw(display, (correction_x, correction_y));
-		}
-	}
-
-	#[allow(unused_variable)]
-	fn update(&mut self, elapsed_time: units::Millis, map: &map::Map) {
-		self.animation_sprite.get_mut(0).unwrap().update(elapsed_time);
-	}
-
-	fn damage_rectangle(&self) -> Rectangle {
-		self.character.damage_rectangle()
-	}
-
-	fn toggle_debuff(&mut self) {
-		self.is_debuff = !self.is_debuff;
+/// Fails w/ the line of the powerups file if `asset_path` does not exist.
+fn check_asset(file_path: &str, line_num: usize, asset_path: &str) -> Result<(), String> {
+	match fs::metadata(packs::locate(asset_path)) {
+		Ok(_) => { Ok(()) },
+		Err(_) => { Err(format!("{}:{}: missing asset `{}`", file_path, line_num, asset_path)) }
 	}
+}
 
-	fn is_debuff(&self) -> bool {
-		self.is_debuff
+fn parse_number(file_path: &str, line_num: usize, field: &str) -> Result<u32, String> {
+	match field.parse::<u32>() {
+		Ok(value) => { Ok(value) },
+		Err(_) => { Err(format!("{}:{}: expected a number, found `{}`", file_path, line_num, field)) }
 	}
+}
 
-	fn get_type(&self) -> i32 {
-		6
-	}
+/// Parses a `<col> <row>` pair of tiles.
+fn parse_frame(file_path: &str, line_num: usize, fields: &[&str]) -> Result<(units::Tile, units::Tile), String> {
+	Ok((units::Tile(try!(parse_number(file_path, line_num, fields[0]))),
+	    units::Tile(try!(parse_number(file_path, line_num, fields[1])))))
+}
 
-	fn is_finished(&mut self) -> bool {
-		match self.animation_timer {
-			0 => { true },
-			_ => { self.animation_timer = self.animation_timer - 1; false }
+#[cfg(test)]
+mod tests {
+	use std::env;
+	use std::fs;
+	use std::fs::File;
+	use std::io::Write;
+
+	use super::PowerupDefs;
+	use game::random;
+
+	static WEIGHTED: &'static str = "just-run powerups 1
+powerup common
+sheet   assets/base/powerups.bmp
+frame   0 0
+sound   powerup
+message COMMON
+effect  bat
+weight  3
+
+powerup rare
+sheet   assets/base/powerups.bmp
+frame   1 0
+sound   powerup
+message RARE
+effect  bat
+
+powerup placed
+sheet   assets/base/powerups.bmp
+frame   2 0
+sound   powerup
+message PLACED
+effect  bat
+weight  0
+";
+
+	#[test]
+	fn picks_in_proportion_to_weight() {
+		let path = env::temp_dir().join("just-run-powerups.cfg");
+		File::create(&path).unwrap().write_all(WEIGHTED.as_bytes()).unwrap();
+		let defs = PowerupDefs::load(path.to_str().unwrap());
+		fs::remove_file(&path).unwrap();
+		let defs = defs.ok().unwrap();
+		assert_eq!(defs.find("rare"), Some(1));
+
+		let mut rng = random::new_rng(7);
+		let mut picked = [0u32; 3];
+		for _ in 0.. 4000 {
+			picked[defs.pick(&mut rng) as usize] += 1;
 		}
-	}
-
-	fn set_timer(&mut self) {
-		self.animation_timer = 5;
-	}
-
-	fn get_map_x(&self) -> units::Game {
-		self.character.map_center_x()
-	}
-
-	fn get_map_y(&self) -> units::Game {
-		self.character.map_center_y()
+		assert!(picked[0] > 2800 && picked[0] < 3200, "{:?}", picked);
+		assert!(picked[1] > 800 && picked[1] < 1200, "{:?}", picked);
+		assert_eq!(picked[2], 0);
 	}
 }
@@ -0,0 +1,22 @@
+use rand;
+use rand::{Rng, SeedableRng, XorShiftRng};
+
+/// The one source of randomness for a game.
+///
+/// Every roll the simulation makes (map layout, spawns, zombie wandering,
+/// powerup outcomes) is drawn from this generator, so two worlds built
+/// from the same seed play out identically.
+pub type GameRng = XorShiftRng;
+
+/// Builds the game's generator from a single `seed`.
+///
+/// (The xorshift state may not be all zeroes, so the seed is mixed
+/// w/ a few fixed words before use.)
+pub fn new_rng(seed: u32) -> GameRng {
+	XorShiftRng::from_seed([seed, seed ^ 0x9E3779B9, 0x243F6A88, 0xB7E15162])
+}
+
+/// Picks a fresh seed for when the player did not ask for one.
+pub fn random_seed() -> u32 {
+	rand::thread_rng().gen::<u32>()
+}
@@ -0,0 +1,360 @@
+use std::fs::File;
+use std::io::{BufRead, BufReader, Write};
+use std::path::Path;
+use std::string::String;
+use std::vec::Vec;
+
+use sdl2::keyboard::Keycode;
+
+use game::camera::CameraMode;
+use game::controls::{self, Action, Bindings};
+use game::gamepad;
+use game::settings::Difficulty;
+
+pub use game::input::{KeyAction, Signal};
+
+static REPLAY_HEADER: &'static str = "just-run replay 1";
+
+/// A single keyboard or controller event & the frame of the event loop it arrived on.
+#[derive(Clone,Copy)]
+pub struct InputEvent {
+	pub frame:  u32,
+	pub signal: Signal
+}
+
+/// Collects every key & controller event handed to `input::Input` during a run.
+///
+/// Replay files are plain text:
+///
+///- `just-run replay 1`
+///- `seed <seed>`
+///- `packs <name>,<name>` (or `packs none`) & `camera <follow|page>`, which
+///  are put back in place for the replay since they change the levels &
+///  what's in view
+///- `difficulty <easy|normal|hard>` & `alt_control <on|off>`
+///- one `bind <action> <key> [<key>...]` line per action, as in the
+///  controls file
+///- one line per event: `<frame> <down|up> <keycode>` for keys,
+///  `<frame> button <down|up> <button>` for controller buttons & `<frame>
+///  axis <axis> <value>` for sticks, w/ buttons & axes named as by `gamepad`
+///
+/// Written by hand, a replay can also stand in for a controller that
+/// isn't plugged in.
+pub struct Recorder {
+	seed:        u32,
+	packs:       Vec<String>,
+	camera:      CameraMode,
+	difficulty:  Difficulty,
+	alt_control: bool,
+	bindings:    Bindings,
+	events:      Vec<InputEvent>
+}
+
+impl Recorder {
+	/// Starts a recording of runs played w/ `packs` & the `camera` on
+	/// `difficulty` w/ `bindings`, starting off w/ the alternate control
+	/// method if `alt_control` is set.
+	pub fn new(seed: u32, packs: &[String], camera: CameraMode, difficulty: Difficulty,
+	           alt_control: bool, bindings: &Bindings) -> Recorder {
+		Recorder {
+			seed:        seed,
+			packs:       packs.to_vec(),
+			camera:      camera,
+			difficulty:  difficulty,
+			alt_control: alt_control,
+			bindings:    bindings.clone(),
+			events:      Vec::new()
+		}
+	}
+
+	pub fn record(&mut self, frame: u32, signal: Signal) {
+		self.events.push(InputEvent { frame: frame, signal: signal });
+	}
+
+	/// Writes the recording to `file_path`, replacing any file already there.
+	pub fn save(&self, file_path: &str) -> Result<(), String> {
+		let mut file = match File::create(&Path::new(file_path)) {
+			Ok(file) => { file },
+			Err(msg) => { return Err(format!("could not create replay {}: {}", file_path, msg)); }
+		};
+
+		let mut contents = String::new();
+		contents.push_str(REPLAY_HEADER);
+		contents.push_str("\n");
+		contents.push_str(&format!("seed {}\n", self.seed));
+		contents.push_str(&format!("packs {}\n", if self.packs.len() == 0 { "none".to_string() } else { self.packs.join(",") }));
+		contents.push_str(&format!("camera {}\n", self.camera.name()));
+		contents.push_str(&format!("difficulty {}\n", self.difficulty.name()));
+		contents.push_str(&format!("alt_control {}\n", if self.alt_control { "on" } else { "off" }));
+		for action in controls::ACTIONS.iter() {
+			let names: Vec<String> = self.bindings.keys(*action).iter().map(|key| controls::key_name(*key)).collect();
+			contents.push_str(&format!("bind {} {}\n", action.name(), names.join(" ")));
+		}
+		for event in self.events.iter() {
+			let line = match event.signal {
+				Signal::Key(action, key) => format!("{} {} {}\n", event.frame, action_name(action), key as i32),
+				Signal::Button(action, button) => format!("{} button {} {}\n", event.frame, action_name(action), gamepad::button_name(button)),
+				Signal::Axis(axis, value) => format!("{} axis {} {}\n", event.frame, gamepad::axis_name(axis), value)
+			};
+			contents.push_str(&line);
+		}
+
+		match file.write_all(contents.as_bytes()) {
+			Ok(_) => { Ok(()) },
+			Err(msg) => { Err(format!("could not write replay {}: {}", file_path, msg)) }
+		}
+	}
+}
+
+/// A recording being fed back into the event loop in place of the keyboard
+/// & controllers.
+pub struct Replay {
+	pub seed:        u32,
+	pub packs:       Vec<String>,
+	pub camera:      CameraMode,
+	pub difficulty:  Difficulty,
+	pub alt_control: bool,
+	pub bindings:    Bindings,
+	events:          Vec<InputEvent>,
+	cursor:          usize
+}
+
+impl Replay {
+	/// Reads a replay written by `Recorder::save`.
+	///
+	/// Returns an error naming the offending line if the file is malformed.
+	pub fn load(file_path: &str) -> Result<Replay, String> {
+		let file = match File::open(&Path::new(file_path)) {
+			Ok(file) => { file },
+			Err(msg) => { return Err(format!("could not open replay {}: {}", file_path, msg)); }
+		};
+
+		let mut seed: Option<u32> = None;
+		let mut packs: Option<Vec<String>> = None;
+		let mut camera: Option<CameraMode> = None;
+		let mut difficulty: Option<Difficulty> = None;
+		let mut alt_control: Option<bool> = None;
+		let mut bindings = Bindings::new();
+		let mut bound: Vec<Action> = Vec::new();
+		let mut events: Vec<InputEvent> = Vec::new();
+		for (index, line) in BufReader::new(file).lines().enumerate() {
+			let line_num = index + 1;
+			let line = match line {
+				Ok(line) => { line },
+				Err(msg) => { return Err(format!("{}:{}: {}", file_path, line_num, msg)); }
+			};
+			let fields: Vec<&str> = line.split_whitespace().collect();
+
+			if line_num == 1 {
+				if line.trim() != REPLAY_HEADER {
+					return Err(format!("{}:{}: not a replay file", file_path, line_num));
+				}
+				continue;
+			}
+
+			match fields.len() {
+				0 => {},
+				2 if fields[0] == "seed" => {
+					seed = match fields[1].parse::<u32>() {
+						Ok(value) => { Some(value) },
+						Err(_) => { return Err(format!("{}:{}: invalid seed `{}`", file_path, line_num, fields[1])); }
+					};
+				},
+				2 if fields[0] == "packs" => {
+					packs = Some(match fields[1] {
+						"none" => { Vec::new() },
+						names  => { names.split(',').filter(|name| name.len() > 0).map(|name| name.to_string()).collect() }
+					});
+				},
+				2 if fields[0] == "camera" => {
+					camera = match CameraMode::from_name(fields[1]) {
+						Some(value) => { Some(value) },
+						None => { return Err(format!("{}:{}: invalid camera `{}`", file_path, line_num, fields[1])); }
+					};
+				},
+				2 if fields[0] == "difficulty" => {
+					difficulty = match Difficulty::from_name(fields[1]) {
+						Some(value) => { Some(value) },
+						None => { return Err(format!("{}:{}: invalid difficulty `{}`", file_path, line_num, fields[1])); }
+					};
+				},
+				2 if fields[0] == "alt_control" => {
+					alt_control = match fields[1] {
+						"on"  => Some(true),
+						"off" => Some(false),
+						_     => { return Err(format!("{}:{}: invalid alt_control `{}`", file_path, line_num, fields[1])); }
+					};
+				},
+				n if n >= 3 && fields[0] == "bind" => {
+					let action = match Action::from_name(fields[1]) {
+						Some(action) => { action },
+						None => { return Err(format!("{}:{}: unknown action `{}`", file_path, line_num, fields[1])); }
+					};
+					let mut keys: Vec<Keycode> = Vec::new();
+					for name in fields[2..].iter() {
+						match controls::key_from_name(name) {
+							Some(key) => { keys.push(key); },
+							None => { return Err(format!("{}:{}: unknown key `{}`", file_path, line_num, name)); }
+						}
+					}
+					bindings.bind(action, keys);
+					bound.push(action);
+				},
+				3 => {
+					let frame = try!(parse_frame(file_path, line_num, fields[0]));
+					let action = try!(parse_action(file_path, line_num, fields[1]));
+					let key = match fields[2].parse::<i32>().ok().and_then(|code| Keycode::from_i32(code)) {
+						Some(key) => { key },
+						None => { return Err(format!("{}:{}: invalid keycode `{}`", file_path, line_num, fields[2])); }
+					};
+					events.push(InputEvent { frame: frame, signal: Signal::Key(action, key) });
+				},
+				4 if fields[1] == "button" => {
+					let frame = try!(parse_frame(file_path, line_num, fields[0]));
+					let action = try!(parse_action(file_path, line_num, fields[2]));
+					let button = match gamepad::button_from_name(fields[3]) {
+						Some(button) => { button },
+						None => { return Err(format!("{}:{}: invalid button `{}`", file_path, line_num, fields[3])); }
+					};
+					events.push(InputEvent { frame: frame, signal: Signal::Button(action, button) });
+				},
+				4 if fields[1] == "axis" => {
+					let frame = try!(parse_frame(file_path, line_num, fields[0]));
+					let axis = match gamepad::axis_from_name(fields[2]) {
+						Some(axis) => { axis },
+						None => { return Err(format!("{}:{}: invalid axis `{}`", file_path, line_num, fields[2])); }
+					};
+					let value = match fields[3].parse::<i16>() {
+						Ok(value) => { value },
+						Err(_) => { return Err(format!("{}:{}: invalid axis value `{}`", file_path, line_num, fields[3])); }
+					};
+					events.push(InputEvent { frame: frame, signal: Signal::Axis(axis, value) });
+				},
+				_ => { return Err(format!("{}:{}: expected `<frame> <down|up> <keycode>`, `<frame> button <down|up> <button>` or `<frame> axis <axis> <value>`", file_path, line_num)); }
+			}
+		}
+
+		let seed = match seed {
+			Some(seed) => { seed },
+			None => { return Err(format!("{}: replay has no seed", file_path)); }
+		};
+		let packs = match packs {
+			Some(packs) => { packs },
+			None => { return Err(format!("{}: replay has no `packs`", file_path)); }
+		};
+		let camera = match camera {
+			Some(camera) => { camera },
+			None => { return Err(format!("{}: replay has no `camera`", file_path)); }
+		};
+		match controls::ACTIONS.iter().find(|&&action| !bound.contains(&action)) {
+			Some(action) => { return Err(format!("{}: replay has no `bind` for `{}`", file_path, action.name())); },
+			None => {}
+		}
+		let difficulty = match difficulty {
+			Some(difficulty) => { difficulty },
+			None => { return Err(format!("{}: replay has no `difficulty`", file_path)); }
+		};
+		let alt_control = match alt_control {
+			Some(alt_control) => { alt_control },
+			None => { return Err(format!("{}: replay has no `alt_control`", file_path)); }
+		};
+
+		Ok(Replay {
+			seed:        seed,
+			packs:       packs,
+			camera:      camera,
+			difficulty:  difficulty,
+			alt_control: alt_control,
+			bindings:    bindings,
+			events:      events,
+			cursor:      0
+		})
+	}
+
+	/// Returns the events which were recorded on `frame`.
+	/// Frames must be requested in increasing order.
+	pub fn events_for_frame(&mut self, frame: u32) -> Vec<InputEvent> {
+		let mut frame_events: Vec<InputEvent> = Vec::new();
+		while self.cursor < self.events.len() && self.events[self.cursor].frame <= frame {
+			frame_events.push(self.events[self.cursor]);
+			self.cursor = self.cursor + 1;
+		}
+
+		frame_events
+	}
+
+	/// Responds true once every recorded event has been played back.
+	pub fn is_finished(&self) -> bool {
+		self.cursor >= self.events.len()
+	}
+}
+
+fn action_name(action: KeyAction) -> &'static str {
+	match action {
+		KeyAction::Down => "down",
+		KeyAction::Up   => "up"
+	}
+}
+
+fn parse_frame(file_path: &str, line_num: usize, field: &str) -> Result<u32, String> {
+	match field.parse::<u32>() {
+		Ok(frame) => { Ok(frame) },
+		Err(_) => { Err(format!("{}:{}: invalid frame `{}`", file_path, line_num, field)) }
+	}
+}
+
+fn parse_action(file_path: &str, line_num: usize, field: &str) -> Result<KeyAction, String> {
+	match field {
+		"down" => { Ok(KeyAction::Down) },
+		"up"   => { Ok(KeyAction::Up) },
+		_      => { Err(format!("{}:{}: invalid key action `{}`", file_path, line_num, field)) }
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use std::env;
+	use std::fs;
+
+	use sdl2::controller::{Axis, Button};
+	use sdl2::keyboard::Keycode;
+
+	use super::{KeyAction, Recorder, Replay, Signal};
+	use game::camera::CameraMode;
+	use game::controls::{Action, Bindings};
+	use game::settings::Difficulty;
+
+	#[test]
+	fn header_reads_back_as_recorded() {
+		let mut bindings = Bindings::new();
+		bindings.bind(Action::MoveLeft, vec![Keycode::A, Keycode::Left]);
+		let packs = vec!["dark".to_string(), "mods/big".to_string()];
+		let mut recorder = Recorder::new(77, &packs, CameraMode::Page, Difficulty::Hard, true, &bindings);
+		recorder.record(3, Signal::Key(KeyAction::Down, Keycode::A));
+		recorder.record(3, Signal::Button(KeyAction::Up, Button::A));
+		recorder.record(9, Signal::Axis(Axis::LeftX, -12000));
+
+		let path = env::temp_dir().join("just-run-header.replay");
+		let file_path = path.to_str().unwrap().to_string();
+		recorder.save(&file_path).unwrap();
+		let replay = Replay::load(&file_path);
+		fs::remove_file(&path).unwrap();
+		let mut replay = replay.ok().unwrap();
+
+		assert_eq!(replay.seed, 77);
+		assert_eq!(replay.packs, packs);
+		assert!(replay.camera == CameraMode::Page);
+		assert!(replay.difficulty == Difficulty::Hard);
+		assert!(replay.alt_control);
+		assert!(replay.bindings.keys(Action::MoveLeft) == &[Keycode::A, Keycode::Left][..]);
+		assert!(replay.bindings.keys(Action::MoveRight) == bindings.keys(Action::MoveRight));
+
+		let events = replay.events_for_frame(3);
+		assert_eq!(events.len(), 2);
+		assert!(match events[0].signal { Signal::Key(KeyAction::Down, Keycode::A) => true, _ => false });
+		assert!(match events[1].signal { Signal::Button(KeyAction::Up, Button::A) => true, _ => false });
+		let events = replay.events_for_frame(9);
+		assert!(match events[0].signal { Signal::Axis(Axis::LeftX, -12000) => true, _ => false });
+		assert!(replay.is_finished());
+	}
+}
@@ -0,0 +1,480 @@
+use std::fs::File;
+use std::io::{BufRead, BufReader};
+use std::path::Path;
+use std::string::String;
+use std::vec::Vec;
+
+use game::common::CharacterState;
+use game::map::{TileDef, TileType};
+use game::player::PlayerState;
+use game::settings::Difficulty;
+use game::storage;
+use game::traps;
+use game::units;
+
+static SAVE_HEADER: &'static str = "just-run save 1";
+
+/// A zombie as saved, w/ its kind named as in `world::ZOMBIE_CONFIG`.
+pub struct SavedZombie {
+	pub kind:    String,
+	pub state:   CharacterState,
+	pub chasing: bool
+}
+
+/// A powerup as saved, w/ its kind named as in `world::POWERUP_CONFIG`.
+pub struct SavedPowerup {
+	pub kind:   String,
+	pub x:      units::Game,
+	pub y:      units::Game,
+	pub debuff: bool
+}
+
+/// A trap as saved, w/ its kind named as in `traps::KINDS`.
+pub struct SavedTrap {
+	pub kind:   String,
+	pub x:      units::Game,
+	pub y:      units::Game,
+	pub timers: (i32, i32)
+}
+
+/// A vehicle part still lying on the level (see `Part::part_type()`).
+pub struct SavedPart {
+	pub part_type: u32,
+	pub x:         units::Game,
+	pub y:         units::Game
+}
+
+/// A game in progress, as written to & read from a save file.
+///
+/// Every position is the top-left corner of whatever is placed there, as
+/// its constructor takes it.
+pub struct SaveGame {
+	pub seed:           u32,
+	pub difficulty:     Difficulty,
+	pub level:          i32,
+	pub score:          i32,
+	pub timer:          i32,
+	pub updates:        i32,
+	pub freeze_counter: i32,
+
+	pub player:         PlayerState,
+	pub vehicle_type:   i32,
+	pub vehicle_x:      units::Game,
+	pub vehicle_y:      units::Game,
+	pub vehicle_parts:  Vec<u32>,
+	pub collected:      Vec<u32>,
+	pub parts:          Vec<SavedPart>,
+	pub zombies:        Vec<SavedZombie>,
+	pub powerups:       Vec<SavedPowerup>,
+	pub traps:          Vec<SavedTrap>,
+
+	// the map, as taken by `Map::from_grid`
+	pub backdrop:       String,
+	pub kinds:          Vec<TileDef>,
+	pub grid:           Vec<Vec<usize>>
+}
+
+impl SaveGame {
+	/// Writes the game to `file_path`, atomically replacing any file already there.
+	///
+	/// Save files are plain text:
+	///
+	///- `just-run save 1`
+	///- `seed`, `level`, `score`, `timer`, `updates` & `freeze`, each w/ its number
+	///- `difficulty <easy|normal|hard>`
+	///- `player <x> <y> <velocity x> <velocity y> <health> <bat> <teleport timer> <immunity timer>`
+	///  & one `kit <trap>` per trap kit the player carries
+	///- `vehicle <type> <x> <y> [<part>...]`, one `collected <part>` per part
+	///  being carried & one `part <part> <x> <y>` per part left on the level
+	///- `zombie <kind> <x> <y> <velocity x> <velocity y> <target x> <target y> <chasing>`,
+	///  `powerup <kind> <x> <y> <debuff>` & `trap <kind> <x> <y> <re-arm timer> <arming timer>`
+	///- `backdrop <image>`, one `tile <air|wall> [<sheet> <col> <row>]` per kind
+	///  of tile, numbered from 0, & `tiles <cols> <rows>` followed by one line
+	///  of tile numbers per row of the map
+	///
+	/// Flags (`bat`, `chasing`, `debuff`) are written as `0` or `1`.
+	pub fn save(&self, file_path: &Path) -> Result<(), String> {
+		let mut contents = String::new();
+		contents.push_str(SAVE_HEADER);
+		contents.push_str("\n");
+		contents.push_str(&format!("seed {}\nlevel {}\nscore {}\ntimer {}\nupdates {}\nfreeze {}\n",
+		                           self.seed, self.level, self.score, self.timer, self.updates, self.freeze_counter));
+		contents.push_str(&format!("difficulty {}\n", self.difficulty.name()));
+
+		let player = &self.player;
+		contents.push_str(&format!("player {} {} {} {} {} {} {} {}\n",
+		                           game(player.character.map_x), game(player.character.map_y),
+		                           velocity(player.character.velocity_x), velocity(player.character.velocity_y),
+		                           player.health, flag(player.cricket_bat), player.teleport_timer, player.immunity_timer));
+		for kit in player.trap_kits.iter() {
+			match traps::rules(*kit) {
+				Some(rules) => { contents.push_str(&format!("kit {}\n", rules.name)); },
+				None        => {}
+			}
+		}
+
+		contents.push_str(&format!("vehicle {} {} {}", self.vehicle_type, game(self.vehicle_x), game(self.vehicle_y)));
+		for part in self.vehicle_parts.iter() {
+			contents.push_str(&format!(" {}", part));
+		}
+		contents.push_str("\n");
+		for part in self.collected.iter() {
+			contents.push_str(&format!("collected {}\n", part));
+		}
+		for part in self.parts.iter() {
+			contents.push_str(&format!("part {} {} {}\n", part.part_type, game(part.x), game(part.y)));
+		}
+
+		for zombie in self.zombies.iter() {
+			let state = &zombie.state;
+			contents.push_str(&format!("zombie {} {} {} {} {} {} {} {}\n",
+			                           zombie.kind, game(state.map_x), game(state.map_y),
+			                           velocity(state.velocity_x), velocity(state.velocity_y),
+			                           game(state.target_x), game(state.target_y), flag(zombie.chasing)));
+		}
+		for powerup in self.powerups.iter() {
+			contents.push_str(&format!("powerup {} {} {} {}\n", powerup.kind, game(powerup.x), game(powerup.y), flag(powerup.debuff)));
+		}
+		for trap in self.traps.iter() {
+			let (rearm_timer, arming_timer) = trap.timers;
+			contents.push_str(&format!("trap {} {} {} {} {}\n", trap.kind, game(trap.x), game(trap.y), rearm_timer, arming_timer));
+		}
+
+		contents.push_str(&format!("backdrop {}\n", self.backdrop));
+		for kind in self.kinds.iter() {
+			let tile_type = match kind.tile_type {
+				TileType::Air  => "air",
+				TileType::Wall => "wall"
+			};
+			match kind.sprite {
+				Some((ref sheet, (units::Tile(col), units::Tile(row)))) => {
+					contents.push_str(&format!("tile {} {} {} {}\n", tile_type, sheet, col, row));
+				},
+				None => { contents.push_str(&format!("tile {}\n", tile_type)); }
+			}
+		}
+		let cols = self.grid.get(0).map_or(0, |row| row.len());
+		contents.push_str(&format!("tiles {} {}\n", cols, self.grid.len()));
+		for row in self.grid.iter() {
+			let tiles: Vec<String> = row.iter().map(|tile| tile.to_string()).collect();
+			contents.push_str(&tiles.join(" "));
+			contents.push_str("\n");
+		}
+
+		storage::write_atomically(file_path, &contents)
+	}
+
+	/// Reads a save written by `SaveGame::save`.
+	///
+	/// Returns an error naming the offending line if the file is malformed.
+	/// Kinds of zombies, powerups & traps are only checked once the game
+	/// is resumed.
+	pub fn load(path: &Path) -> Result<SaveGame, String> {
+		let shown = path.display().to_string();
+		let file_path = &shown[..];
+		let file = match File::open(path) {
+			Ok(file) => { file },
+			Err(msg) => { return Err(format!("could not open save {}: {}", file_path, msg)); }
+		};
+
+		let mut seed: Option<u32> = None;
+		let mut difficulty: Option<Difficulty> = None;
+		let mut level: Option<i32> = None;
+		let (mut score, mut timer, mut updates, mut freeze_counter) = (0, 0, 0, 0);
+		let mut player: Option<PlayerState> = None;
+		let mut trap_kits: Vec<u32> = Vec::new();
+		let mut vehicle: Option<(i32, units::Game, units::Game, Vec<u32>)> = None;
+		let mut collected: Vec<u32> = Vec::new();
+		let mut parts: Vec<SavedPart> = Vec::new();
+		let mut zombies: Vec<SavedZombie> = Vec::new();
+		let mut powerups: Vec<SavedPowerup> = Vec::new();
+		let mut saved_traps: Vec<SavedTrap> = Vec::new();
+		let mut backdrop: Option<String> = None;
+		let mut kinds: Vec<TileDef> = Vec::new();
+		let mut size: Option<(usize, usize)> = None;
+		let mut grid: Vec<Vec<usize>> = Vec::new();
+		let mut line_num = 0;
+
+		for (index, line) in BufReader::new(file).lines().enumerate() {
+			line_num = index + 1;
+			let line = match line {
+				Ok(line) => { line },
+				Err(msg) => { return Err(format!("{}:{}: {}", file_path, line_num, msg)); }
+			};
+			let fields: Vec<&str> = line.split_whitespace().collect();
+
+			if line_num == 1 {
+				if line.trim() != SAVE_HEADER {
+					return Err(format!("{}:{}: not a save file", file_path, line_num));
+				}
+				continue;
+			}
+
+			// every line after `tiles` is a row of the grid
+			match size {
+				Some((cols, rows)) if grid.len() < rows => {
+					if fields.len() != cols {
+						return Err(format!("{}:{}: expected {} tiles, found {}", file_path, line_num, cols, fields.len()));
+					}
+					let mut row: Vec<usize> = Vec::new();
+					for field in fields.iter() {
+						let tile = try!(parse_number(file_path, line_num, field)) as usize;
+						if tile >= kinds.len() {
+							return Err(format!("{}:{}: there is no tile {}", file_path, line_num, tile));
+						}
+						row.push(tile);
+					}
+					grid.push(row);
+					continue;
+				},
+				_ => {}
+			}
+
+			if fields.len() == 0 {
+				continue;
+			}
+			match (fields[0], fields.len()) {
+				("seed", 2)    => { seed = Some(try!(parse_number(file_path, line_num, fields[1]))); },
+				("difficulty", 2) => {
+					difficulty = match Difficulty::from_name(fields[1]) {
+						Some(difficulty) => { Some(difficulty) },
+						None => { return Err(format!("{}:{}: unknown difficulty `{}`", file_path, line_num, fields[1])); }
+					};
+				},
+				("level", 2)   => { level = Some(try!(parse_int(file_path, line_num, fields[1]))); },
+				("score", 2)   => { score = try!(parse_int(file_path, line_num, fields[1])); },
+				("timer", 2)   => { timer = try!(parse_int(file_path, line_num, fields[1])); },
+				("updates", 2) => { updates = try!(parse_int(file_path, line_num, fields[1])); },
+				("freeze", 2)  => { freeze_counter = try!(parse_int(file_path, line_num, fields[1])); },
+				("player", 9) => {
+					let (x, y) = try!(parse_position(file_path, line_num, &fields[1..3]));
+					let (velocity_x, velocity_y) = try!(parse_velocity(file_path, line_num, &fields[3..5]));
+					player = Some(PlayerState {
+						character:      CharacterState {
+							map_x: x, map_y: y,
+							velocity_x: velocity_x, velocity_y: velocity_y,
+							target_x: x, target_y: y
+						},
+						health:         try!(parse_number(file_path, line_num, fields[5])),
+						cricket_bat:    try!(parse_flag(file_path, line_num, fields[6])),
+						teleport_timer: try!(parse_int(file_path, line_num, fields[7])),
+						immunity_timer: try!(parse_int(file_path, line_num, fields[8])),
+						trap_kits:      Vec::new()
+					});
+				},
+				("kit", 2) => {
+					match traps::find(fields[1]) {
+						Some(kind) => { trap_kits.push(kind); },
+						None => { return Err(format!("{}:{}: unknown trap `{}`", file_path, line_num, fields[1])); }
+					}
+				},
+				("vehicle", count) if count >= 4 => {
+					let vehicle_type = try!(parse_int(file_path, line_num, fields[1]));
+					let (x, y) = try!(parse_position(file_path, line_num, &fields[2..4]));
+					let mut added: Vec<u32> = Vec::new();
+					for field in fields[4..].iter() {
+						added.push(try!(parse_number(file_path, line_num, field)));
+					}
+					vehicle = Some((vehicle_type, x, y, added));
+				},
+				("collected", 2) => { collected.push(try!(parse_number(file_path, line_num, fields[1]))); },
+				("part", 4) => {
+					let (x, y) = try!(parse_position(file_path, line_num, &fields[2..4]));
+					parts.push(SavedPart { part_type: try!(parse_number(file_path, line_num, fields[1])), x: x, y: y });
+				},
+				("zombie", 9) => {
+					let (x, y) = try!(parse_position(file_path, line_num, &fields[2..4]));
+					let (velocity_x, velocity_y) = try!(parse_velocity(file_path, line_num, &fields[4..6]));
+					let (target_x, target_y) = try!(parse_position(file_path, line_num, &fields[6..8]));
+					zombies.push(SavedZombie {
+						kind:    fields[1].to_string(),
+						state:   CharacterState {
+							map_x: x, map_y: y,
+							velocity_x: velocity_x, velocity_y: velocity_y,
+							target_x: target_x, target_y: target_y
+						},
+						chasing: try!(parse_flag(file_path, line_num, fields[8]))
+					});
+				},
+				("powerup", 5) => {
+					let (x, y) = try!(parse_position(file_path, line_num, &fields[2..4]));
+					powerups.push(SavedPowerup {
+						kind:   fields[1].to_string(),
+						x: x, y: y,
+						debuff: try!(parse_flag(file_path, line_num, fields[4]))
+					});
+				},
+				("trap", 6) => {
+					let (x, y) = try!(parse_position(file_path, line_num, &fields[2..4]));
+					saved_traps.push(SavedTrap {
+						kind:   fields[1].to_string(),
+						x: x, y: y,
+						timers: (try!(parse_int(file_path, line_num, fields[4])), try!(parse_int(file_path, line_num, fields[5])))
+					});
+				},
+				("backdrop", 2) => { backdrop = Some(fields[1].to_string()); },
+				("tile", 2) | ("tile", 5) => {
+					let tile_type = match fields[1] {
+						"air"  => TileType::Air,
+						"wall" => TileType::Wall,
+						_      => { return Err(format!("{}:{}: unknown tile type `{}`", file_path, line_num, fields[1])); }
+					};
+					let sprite = if fields.len() == 5 {
+						let col = try!(parse_number(file_path, line_num, fields[3]));
+						let row = try!(parse_number(file_path, line_num, fields[4]));
+						Some((fields[2].to_string(), (units::Tile(col), units::Tile(row))))
+					} else { None };
+					kinds.push(TileDef { tile_type: tile_type, sprite: sprite });
+				},
+				("tiles", 3) => {
+					let cols = try!(parse_number(file_path, line_num, fields[1])) as usize;
+					let rows = try!(parse_number(file_path, line_num, fields[2])) as usize;
+					size = Some((cols, rows));
+				},
+				(other, _) => {
+					return Err(format!("{}:{}: unexpected `{}`", file_path, line_num, other));
+				}
+			}
+		}
+
+		let missing = |field: &str| format!("{}:{}: save has no `{}`", file_path, line_num, field);
+		let seed = match seed { Some(seed) => seed, None => { return Err(missing("seed")); } };
+		let difficulty = match difficulty { Some(difficulty) => difficulty, None => { return Err(missing("difficulty")); } };
+		let level = match level { Some(level) => level, None => { return Err(missing("level")); } };
+		let mut player = match player { Some(player) => player, None => { return Err(missing("player")); } };
+		player.trap_kits = trap_kits;
+		let (vehicle_type, vehicle_x, vehicle_y, vehicle_parts) = match vehicle { Some(vehicle) => vehicle, None => { return Err(missing("vehicle")); } };
+		let backdrop = match backdrop { Some(backdrop) => backdrop, None => { return Err(missing("backdrop")); } };
+		match size {
+			Some((_, rows)) if grid.len() == rows => {},
+			Some((_, rows)) => { return Err(format!("{}:{}: expected {} rows of tiles, found {}", file_path, line_num, rows, grid.len())); },
+			None => { return Err(missing("tiles")); }
+		}
+
+		Ok(SaveGame {
+			seed:           seed,
+			difficulty:     difficulty,
+			level:          level,
+			score:          score,
+			timer:          timer,
+			updates:        updates,
+			freeze_counter: freeze_counter,
+
+			player:         player,
+			vehicle_type:   vehicle_type,
+			vehicle_x:      vehicle_x,
+			vehicle_y:      vehicle_y,
+			vehicle_parts:  vehicle_parts,
+			collected:      collected,
+			parts:          parts,
+			zombies:        zombies,
+			powerups:       powerups,
+			traps:          saved_traps,
+
+			backdrop:       backdrop,
+			kinds:          kinds,
+			grid:           grid
+		})
+	}
+}
+
+fn game(position: units::Game) -> f64 {
+	let units::Game(position) = position;
+	position
+}
+
+fn velocity(velocity: units::Velocity) -> f64 {
+	let units::Velocity(velocity) = velocity;
+	velocity
+}
+
+fn flag(value: bool) -> u32 {
+	if value { 1 } else { 0 }
+}
+
+fn parse_number(file_path: &str, line_num: usize, field: &str) -> Result<u32, String> {
+	match field.parse::<u32>() {
+		Ok(value) => { Ok(value) },
+		Err(_) => { Err(format!("{}:{}: expected a number, found `{}`", file_path, line_num, field)) }
+	}
+}
+
+fn parse_int(file_path: &str, line_num: usize, field: &str) -> Result<i32, String> {
+	match field.parse::<i32>() {
+		Ok(value) => { Ok(value) },
+		Err(_) => { Err(format!("{}:{}: expected a number, found `{}`", file_path, line_num, field)) }
+	}
+}
+
+fn parse_float(file_path: &str, line_num: usize, field: &str) -> Result<f64, String> {
+	match field.parse::<f64>() {
+		Ok(value) => { Ok(value) },
+		Err(_) => { Err(format!("{}:{}: expected a number, found `{}`", file_path, line_num, field)) }
+	}
+}
+
+fn parse_flag(file_path: &str, line_num: usize, field: &str) -> Result<bool, String> {
+	match field {
+		"0" => { Ok(false) },
+		"1" => { Ok(true) },
+		_   => { Err(format!("{}:{}: expected `0` or `1`, found `{}`", file_path, line_num, field)) }
+	}
+}
+
+/// Reads `<x> <y>` from `fields`.
+fn parse_position(file_path: &str, line_num: usize, fields: &[&str]) -> Result<(units::Game, units::Game), String> {
+	let x = try!(parse_float(file_path, line_num, fields[0]));
+	let y = try!(parse_float(file_path, line_num, fields[1]));
+	Ok((units::Game(x), units::Game(y)))
+}
+
+/// Reads `<velocity x> <velocity y>` from `fields`.
+fn parse_velocity(file_path: &str, line_num: usize, fields: &[&str]) -> Result<(units::Velocity, units::Velocity), String> {
+	let x = try!(parse_float(file_path, line_num, fields[0]));
+	let y = try!(parse_float(file_path, line_num, fields[1]));
+	Ok((units::Velocity(x), units::Velocity(y)))
+}
+
+#[cfg(test)]
+mod tests {
+	use std::env;
+	use std::fs;
+	use std::fs::File;
+	use std::io::Read;
+	use std::path::Path;
+
+	use super::SaveGame;
+	use game::units;
+	use game::world::World;
+
+	fn contents(path: &Path) -> String {
+		let mut contents = String::new();
+		File::open(path).unwrap().read_to_string(&mut contents).unwrap();
+		contents
+	}
+
+	#[test]
+	fn save_reads_back_the_same() {
+		let mut world = World::new(5).unwrap();
+		for _ in 0.. 120 {
+			world.update(units::Millis(16));
+		}
+		let first = env::temp_dir().join("just-run-first.sav");
+		let second = env::temp_dir().join("just-run-second.sav");
+
+		world.snapshot().save(&first).unwrap();
+		let saved = SaveGame::load(&first).ok().unwrap();
+		saved.save(&second).unwrap();
+		let (first_contents, second_contents) = (contents(&first), contents(&second));
+		fs::remove_file(&first).unwrap();
+		fs::remove_file(&second).unwrap();
+		assert_eq!(first_contents, second_contents);
+
+		let resumed = World::resume(&saved).ok().unwrap();
+		assert_eq!(resumed.level, world.level);
+		assert_eq!(resumed.score, world.score);
+		assert_eq!(resumed.timer, world.timer);
+		assert_eq!(resumed.enemies.len(), world.enemies.len());
+		assert_eq!(resumed.powerups.len(), world.powerups.len());
+	}
+}
@@ -0,0 +1,310 @@
+use std::fs;
+use std::fs::File;
+use std::io::{BufRead, BufReader};
+use std::path::{Path, PathBuf};
+use std::string::String;
+
+use game::storage;
+use game::world;
+
+static SETTINGS_HEADER: &'static str = "just-run settings 1";
+static SETTINGS_FILE:   &'static str = "settings.txt";
+
+/// The loudest the mixer plays anything.
+pub static MAX_VOLUME: i32 = 128;
+
+/// How the levels are stocked: how many zombies & powerups turn up and
+/// how long the first level's timer runs.
+#[derive(PartialEq,Eq,Clone,Copy,Debug)]
+pub enum Difficulty {
+	Easy,
+	Normal,
+	Hard
+}
+
+impl Difficulty {
+	pub fn name(&self) -> &'static str {
+		match *self {
+			Difficulty::Easy   => "easy",
+			Difficulty::Normal => "normal",
+			Difficulty::Hard   => "hard"
+		}
+	}
+
+	pub fn from_name(name: &str) -> Option<Difficulty> {
+		match name {
+			"easy"   => Some(Difficulty::Easy),
+			"normal" => Some(Difficulty::Normal),
+			"hard"   => Some(Difficulty::Hard),
+			_        => None
+		}
+	}
+
+	/// The next difficulty up, wrapping around to the easiest.
+	pub fn next(&self) -> Difficulty {
+		match *self {
+			Difficulty::Easy   => Difficulty::Normal,
+			Difficulty::Normal => Difficulty::Hard,
+			Difficulty::Hard   => Difficulty::Easy
+		}
+	}
+
+	/// The next difficulty down, wrapping around to the hardest.
+	pub fn prev(&self) -> Difficulty {
+		match *self {
+			Difficulty::Easy   => Difficulty::Hard,
+			Difficulty::Normal => Difficulty::Easy,
+			Difficulty::Hard   => Difficulty::Normal
+		}
+	}
+
+	/// The fewest & (one past) the most zombies the first random level is
+	/// given; later & bigger levels get more (see `World::horde_range`).
+	pub fn zombie_range(&self) -> (u32, u32) {
+		match *self {
+			Difficulty::Easy   => (10, 20),
+			Difficulty::Normal => (20, world::MAX_ENEMIES),
+			Difficulty::Hard   => (30, 45)
+		}
+	}
+
+	/// One past the most powerups a random level is given.
+	pub fn max_powerups(&self) -> u32 {
+		match *self {
+			Difficulty::Easy   => 25,
+			Difficulty::Normal => world::MAX_POWERUPS,
+			Difficulty::Hard   => 12
+		}
+	}
+
+	/// What the timer starts at on the first level.
+	pub fn level_time(&self) -> i32 {
+		match *self {
+			Difficulty::Easy   => 6500,
+			Difficulty::Normal => world::LEVEL_1_TIME,
+			Difficulty::Hard   => 4000
+		}
+	}
+}
+
+/// Everything the player can tune, kept in the data directory (see
+/// `storage::data_dir()`) & edited from the options screen.
+///
+/// Settings files are plain text:
+///
+///- `just-run settings 1`
+///- one `<setting> <value>` line per setting, any left out keep their default
+#[derive(Clone)]
+pub struct Settings {
+	/// How many screen pixels each pixel of the game is drawn w/.
+	pub scale:        u32,
+	pub fullscreen:   bool,
+	/// From 0 to `MAX_VOLUME`.
+	pub music_volume: i32,
+	/// From 0 to `MAX_VOLUME`.
+	pub sound_volume: i32,
+	/// How many sound effects can play at once.
+	pub channels:     i32,
+	/// The most frames drawn each second.
+	pub framerate:    u32,
+	/// Whether runs start w/ the alternate control method.
+	pub alt_control:  bool,
+	pub difficulty:   Difficulty,
+	/// The asset packs laid over the base assets, bottom first (see
+	/// `packs::Pack::load`); saved as `packs <name>,<name>` or `packs none`.
+	pub packs:        Vec<String>,
+
+	path: PathBuf
+}
+
+impl Settings {
+	pub fn new() -> Settings {
+		Settings {
+			scale:        1,
+			fullscreen:   false,
+			music_volume: MAX_VOLUME,
+			sound_volume: MAX_VOLUME,
+			channels:     16,
+			framerate:    60,
+			alt_control:  false,
+			difficulty:   Difficulty::Normal,
+			packs:        Vec::new(),
+
+			path: storage::data_dir().join(SETTINGS_FILE)
+		}
+	}
+
+	/// Reads the settings from the data directory. A missing file leaves
+	/// every setting at its default.
+	pub fn load() -> Settings {
+		let settings = Settings::new();
+		if fs::metadata(&settings.path).is_err() {
+			return settings;
+		}
+
+		let path = settings.path.clone();
+		Settings::from_file(&path)
+	}
+
+	/// Reads a settings file. A bad line is reported & its setting keeps
+	/// its default, so a broken file never stops the game from starting.
+	pub fn from_file(file_path: &Path) -> Settings {
+		let mut settings = Settings::new();
+		settings.path = file_path.to_path_buf();
+		let file = match File::open(file_path) {
+			Ok(file) => { file },
+			Err(msg) => {
+				println!("could not open settings {}: {}", file_path.display(), msg);
+				return settings;
+			}
+		};
+
+		for (index, line) in BufReader::new(file).lines().enumerate() {
+			let line_num = index + 1;
+			let line = match line {
+				Ok(line) => { line },
+				Err(msg) => { println!("{}:{}: {}", file_path.display(), line_num, msg); break; }
+			};
+
+			if line_num == 1 {
+				if line.trim() != SETTINGS_HEADER {
+					println!("{}:{}: not a settings file, using the defaults", file_path.display(), line_num);
+					return settings;
+				}
+				continue;
+			}
+
+			let fields: Vec<&str> = line.split_whitespace().collect();
+			match fields.len() {
+				0 => {},
+				2 => {
+					match settings.set(fields[0], fields[1]) {
+						Ok(_) => {},
+						Err(msg) => { println!("{}:{}: {}", file_path.display(), line_num, msg); }
+					}
+				},
+				_ => { println!("{}:{}: expected `<setting> <value>`", file_path.display(), line_num); }
+			}
+		}
+
+		settings
+	}
+
+	/// Writes the settings back to their file, atomically.
+	pub fn save(&self) -> Result<(), String> {
+		let mut contents = String::new();
+		contents.push_str(SETTINGS_HEADER);
+		contents.push_str("\n");
+		contents.push_str(&format!("scale {}\n", self.scale));
+		contents.push_str(&format!("fullscreen {}\n", flag(self.fullscreen)));
+		contents.push_str(&format!("music_volume {}\n", self.music_volume));
+		contents.push_str(&format!("sound_volume {}\n", self.sound_volume));
+		contents.push_str(&format!("channels {}\n", self.channels));
+		contents.push_str(&format!("framerate {}\n", self.framerate));
+		contents.push_str(&format!("alt_control {}\n", flag(self.alt_control)));
+		contents.push_str(&format!("difficulty {}\n", self.difficulty.name()));
+		contents.push_str(&format!("packs {}\n", if self.packs.len() == 0 { "none".to_string() } else { self.packs.join(",") }));
+
+		storage::write_atomically(&self.path, &contents)
+	}
+
+	/// How long a frame should take at the frame rate cap.
+	pub fn frame_millis(&self) -> i64 {
+		1000 / self.framerate as i64
+	}
+
+	/// Changes the setting called `name` to `value`, which must be in range.
+	pub fn set(&mut self, name: &str, value: &str) -> Result<(), String> {
+		match name {
+			"scale"        => { self.scale = try!(parse_ranged(name, value, 1, 4)) as u32; },
+			"fullscreen"   => { self.fullscreen = try!(parse_flag(name, value)); },
+			"music_volume" => { self.music_volume = try!(parse_ranged(name, value, 0, MAX_VOLUME as i64)) as i32; },
+			"sound_volume" => { self.sound_volume = try!(parse_ranged(name, value, 0, MAX_VOLUME as i64)) as i32; },
+			"channels"     => { self.channels = try!(parse_ranged(name, value, 1, 32)) as i32; },
+			"framerate"    => { self.framerate = try!(parse_ranged(name, value, 15, 240)) as u32; },
+			"alt_control"  => { self.alt_control = try!(parse_flag(name, value)); },
+			"difficulty"   => {
+				self.difficulty = match Difficulty::from_name(value) {
+					Some(difficulty) => { difficulty },
+					None => { return Err(format!("unknown difficulty `{}`, expected easy, normal or hard", value)); }
+				};
+			},
+			"packs"        => {
+				self.packs = match value {
+					"none" => { Vec::new() },
+					_      => { value.split(',').filter(|name| name.len() > 0).map(|name| name.to_string()).collect() }
+				};
+			},
+			_ => { return Err(format!("unknown setting `{}`", name)); }
+		}
+
+		Ok(())
+	}
+}
+
+fn flag(value: bool) -> &'static str {
+	if value { "on" } else { "off" }
+}
+
+fn parse_flag(name: &str, value: &str) -> Result<bool, String> {
+	match value {
+		"on"  => { Ok(true) },
+		"off" => { Ok(false) },
+		_     => { Err(format!("{} should be `on` or `off`, found `{}`", name, value)) }
+	}
+}
+
+fn parse_ranged(name: &str, value: &str, min: i64, max: i64) -> Result<i64, String> {
+	match value.parse::<i64>() {
+		Ok(number) if number >= min && number <= max => { Ok(number) },
+		_ => { Err(format!("{} should be a number from {} to {}, found `{}`", name, min, max, value)) }
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use std::env;
+	use std::fs;
+	use std::fs::File;
+	use std::io::Write;
+
+	use super::{Difficulty, Settings, MAX_VOLUME};
+
+	#[test]
+	fn out_of_range_values_are_rejected() {
+		let mut settings = Settings::new();
+		assert!(settings.set("scale", "5").is_err());
+		assert!(settings.set("scale", "0").is_err());
+		assert!(settings.set("music_volume", &(MAX_VOLUME + 1).to_string()).is_err());
+		assert!(settings.set("framerate", "fast").is_err());
+		assert!(settings.set("fullscreen", "yes").is_err());
+		assert!(settings.set("difficulty", "nightmare").is_err());
+		assert_eq!(settings.scale, 1);
+		assert_eq!(settings.music_volume, MAX_VOLUME);
+		assert_eq!(settings.framerate, 60);
+		assert!(settings.difficulty == Difficulty::Normal);
+
+		assert!(settings.set("scale", "4").is_ok());
+		assert_eq!(settings.scale, 4);
+	}
+
+	#[test]
+	fn bad_lines_keep_their_default() {
+		let path = env::temp_dir().join("just-run-settings.txt");
+		File::create(&path).unwrap().write_all(b"just-run settings 1\nscale 9\nchannels 8\ndifficulty hard\n").unwrap();
+		let settings = Settings::from_file(&path);
+		fs::remove_file(&path).unwrap();
+
+		assert_eq!(settings.scale, 1);
+		assert_eq!(settings.channels, 8);
+		assert!(settings.difficulty == Difficulty::Hard);
+	}
+
+	#[test]
+	fn difficulty_steps_round_both_ways() {
+		for &difficulty in [Difficulty::Easy, Difficulty::Normal, Difficulty::Hard].iter() {
+			assert!(difficulty.next().prev() == difficulty);
+			assert!(Difficulty::from_name(difficulty.name()) == Some(difficulty));
+		}
+	}
+}
@@ -0,0 +1,284 @@
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::fs::File;
+use std::io::{BufRead, BufReader};
+use std::string::String;
+use std::vec::Vec;
+
+use sdl2_mixer;
+
+use game::packs;
+
+static SOUNDS_HEADER: &'static str = "just-run sounds 1";
+
+/// The loudest a sound can be played.
+pub static MAX_VOLUME: i32 = 128;
+
+// the sounds the game ships w/, for when the manifest can't be read
+static DEFAULT_SOUNDS: [&'static str; 8] = [
+	"bullet", "wipeout", "nuke", "powerup", "debuff", "trap", "hit", "goal"
+];
+
+/// A sound as listed in the manifest.
+#[derive(Clone)]
+pub struct SoundDef {
+	pub name:     String,
+	pub file:     String,
+	/// From 0 to `MAX_VOLUME`, before the player's sound volume.
+	pub volume:   i32,
+	/// When every channel is busy, a sound may cut off one of equal or
+	/// lower priority.
+	pub priority: i32
+}
+
+/// Reads the sounds listed in `file_path`, a plain text file:
+///
+///- `just-run sounds 1`
+///- `sound <name>` starts a new sound, which the lines after it describe
+///- `file <wav>` where it's loaded from
+///- `volume <0-128>` (defaults to 128) & `priority <n>` (defaults to 0)
+///
+/// Lines starting w/ `#` are comments. Returns an error naming the
+/// offending line if the file is malformed; missing sound files aren't
+/// an error here (see `SoundBank::load`).
+pub fn load_manifest(file_path: &str) -> Result<Vec<SoundDef>, String> {
+	let file = match File::open(packs::locate(file_path)) {
+		Ok(file) => { file },
+		Err(msg) => { return Err(format!("could not open sounds {}: {}", file_path, msg)); }
+	};
+
+	let mut defs: Vec<SoundDef> = Vec::new();
+	for (index, line) in BufReader::new(file).lines().enumerate() {
+		let line_num = index + 1;
+		let line = match line {
+			Ok(line) => { line },
+			Err(msg) => { return Err(format!("{}:{}: {}", file_path, line_num, msg)); }
+		};
+
+		if line_num == 1 {
+			if line.trim() != SOUNDS_HEADER {
+				return Err(format!("{}:{}: not a sounds file", file_path, line_num));
+			}
+			continue;
+		}
+
+		let fields: Vec<&str> = line.split_whitespace().collect();
+		if fields.len() == 0 || fields[0].starts_with("#") {
+			continue;
+		}
+
+		if fields[0] == "sound" {
+			if fields.len() != 2 {
+				return Err(format!("{}:{}: expected `sound <name>`", file_path, line_num));
+			}
+			if defs.iter().any(|def| def.name == fields[1]) {
+				return Err(format!("{}:{}: sound `{}` is listed twice", file_path, line_num, fields[1]));
+			}
+			defs.push(SoundDef { name: fields[1].to_string(), file: String::new(), volume: MAX_VOLUME, priority: 0 });
+			continue;
+		}
+
+		let def = match defs.last_mut() {
+			Some(def) => { def },
+			None => { return Err(format!("{}:{}: `{}` must follow a `sound`", file_path, line_num, fields[0])); }
+		};
+		match (fields[0], fields.len()) {
+			("file", 2) => { def.file = fields[1].to_string(); },
+			("volume", 2) => {
+				def.volume = match fields[1].parse::<i32>() {
+					Ok(volume) if volume >= 0 && volume <= MAX_VOLUME => { volume },
+					_ => { return Err(format!("{}:{}: volume should be from 0 to {}, found `{}`", file_path, line_num, MAX_VOLUME, fields[1])); }
+				};
+			},
+			("priority", 2) => {
+				def.priority = match fields[1].parse::<i32>() {
+					Ok(priority) => { priority },
+					Err(_) => { return Err(format!("{}:{}: expected a number, found `{}`", file_path, line_num, fields[1])); }
+				};
+			},
+			_ => { return Err(format!("{}:{}: unknown line `{}`", file_path, line_num, line.trim())); }
+		}
+	}
+
+	match defs.iter().find(|def| def.file.len() == 0) {
+		Some(def) => { Err(format!("{}: sound `{}` has no `file`", file_path, def.name)) },
+		None => { Ok(defs) }
+	}
+}
+
+/// The sounds the game shipped w/ before there was a manifest, each at
+/// `assets/<name>.wav`; used when the manifest can't be read.
+pub fn default_manifest() -> Vec<SoundDef> {
+	DEFAULT_SOUNDS.iter().map(|name| SoundDef {
+		name:     name.to_string(),
+		file:     format!("assets/{}.wav", name),
+		volume:   MAX_VOLUME,
+		priority: 0
+	}).collect()
+}
+
+// what is playing on a mixer channel: its priority & when it started
+#[derive(Clone,Copy)]
+struct Voice {
+	priority: i32,
+	started:  u64
+}
+
+/// The sound effects, played by name over a fixed number of mixer channels.
+pub struct SoundBank {
+	chunks:   HashMap<String, (sdl2_mixer::Chunk, i32)>,
+	voices:   Vec<Option<Voice>>,
+	plays:    u64,
+	// names already warned about, so a missing sound only warns once
+	warned:   HashSet<String>
+}
+
+impl SoundBank {
+	/// Loads every sound in `defs`. A sound whose file is missing or
+	/// unreadable is warned about & left silent, rather than stopping the game.
+	pub fn load(defs: &[SoundDef]) -> SoundBank {
+		let mut chunks = HashMap::new();
+		for def in defs.iter() {
+			let file = packs::locate(&def.file);
+			if fs::metadata(&file).is_err() {
+				println!("warning: sound `{}` is missing its file {}, it will be silent", def.name, def.file);
+				continue;
+			}
+			match sdl2_mixer::Chunk::from_file(&file) {
+				Ok(mut chunk) => {
+					chunk.set_volume(def.volume as isize);
+					chunks.insert(def.name.clone(), (chunk, def.priority));
+				},
+				Err(msg) => { println!("warning: could not load sound `{}` from {}: {}", def.name, def.file, msg); }
+			}
+		}
+
+		SoundBank { chunks: chunks, voices: Vec::new(), plays: 0, warned: HashSet::new() }
+	}
+
+	/// Lets the bank know how many mixer channels there are to play on.
+	pub fn set_channels(&mut self, channels: i32) {
+		self.voices.truncate(channels as usize);
+		while self.voices.len() < channels as usize {
+			self.voices.push(None);
+		}
+	}
+
+	/// Plays the sound called `name` in both ears at full volume.
+	pub fn play(&mut self, name: &str) -> Option<sdl2_mixer::Channel> {
+		self.play_at(name, 0.0, 0.0)
+	}
+
+	/// Plays the sound called `name` panned by `pan`, from -1.0 (all the
+	/// way left) to 1.0 (all the way right), & `distance` away, from 0.0
+	/// (right here) to 1.0 (as far as can be heard).
+	#[allow(unused_must_use)]
+	pub fn play_at(&mut self, name: &str, pan: f64, distance: f64) -> Option<sdl2_mixer::Channel> {
+		let channel = match self.start(name) {
+			Some(channel) => { channel },
+			None => { return None; }
+		};
+
+		// a channel keeps its panning & distance from one sound to the next,
+		// so they're always set; SDL_mixer drops them at 255/255 & 0
+		let pan = pan.max(-1.0).min(1.0);
+		let left = ((1.0 - pan).min(1.0) * 255.0) as u8;
+		let right = ((1.0 + pan).min(1.0) * 255.0) as u8;
+		channel.set_panning(left, right);
+		channel.set_distance((distance.max(0.0).min(1.0) * 255.0) as u8);
+		Some(channel)
+	}
+
+	/// Starts the sound called `name` on a free channel. When none is free
+	/// it cuts off the oldest of the lowest priority sounds playing, if
+	/// that's no higher than its own; otherwise it isn't played. Responds
+	/// w/ the channel it's playing on.
+	#[allow(unused_must_use)]
+	fn start(&mut self, name: &str) -> Option<sdl2_mixer::Channel> {
+		let priority = match self.chunks.get(name) {
+			Some(&(_, priority)) => { priority },
+			None => {
+				if self.warned.insert(name.to_string()) {
+					println!("warning: there is no sound `{}` to play", name);
+				}
+				return None;
+			}
+		};
+
+		// channels which finished are free again
+		for (index, voice) in self.voices.iter_mut().enumerate() {
+			if voice.is_some() && !sdl2_mixer::Channel(index as isize).is_playing() {
+				*voice = None;
+			}
+		}
+
+		let index = match choose_voice(&self.voices, priority) {
+			Some(index) => { index },
+			None => { return None; }
+		};
+		if self.voices[index].is_some() {
+			sdl2_mixer::Channel(index as isize).halt();
+		}
+
+		let channel = sdl2_mixer::Channel(index as isize);
+		let chunk = &self.chunks.get(name).unwrap().0;
+		match channel.play(chunk, 0) {
+			Ok(_) => {
+				self.plays = self.plays + 1;
+				self.voices[index] = Some(Voice { priority: priority, started: self.plays });
+				Some(channel)
+			},
+			Err(msg) => { println!("could not play sound `{}`: {}", name, msg); None }
+		}
+	}
+}
+
+/// The channel a sound of `priority` should play on: a free one, or else
+/// the oldest of the lowest priority voices, if that's no higher than its
+/// own. `None` when every voice outranks it.
+fn choose_voice(voices: &[Option<Voice>], priority: i32) -> Option<usize> {
+	match voices.iter().position(|voice| voice.is_none()) {
+		Some(index) => { return Some(index); },
+		None => {}
+	}
+
+	let mut victim: Option<(usize, Voice)> = None;
+	for (index, voice) in voices.iter().enumerate() {
+		let voice = match *voice { Some(voice) => voice, None => { continue; } };
+		let weaker = match victim {
+			Some((_, other)) => { (voice.priority, voice.started) < (other.priority, other.started) },
+			None => { true }
+		};
+		if voice.priority <= priority && weaker {
+			victim = Some((index, voice));
+		}
+	}
+
+	victim.map(|(index, _)| index)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::{choose_voice, Voice};
+
+	fn voice(priority: i32, started: u64) -> Option<Voice> {
+		Some(Voice { priority: priority, started: started })
+	}
+
+	#[test]
+	fn free_channel_comes_first() {
+		assert_eq!(choose_voice(&[voice(5, 1), None, voice(0, 2)], 0), Some(1));
+	}
+
+	#[test]
+	fn oldest_of_the_lowest_priority_is_cut_off() {
+		let voices = [voice(2, 1), voice(1, 4), voice(1, 3), voice(3, 2)];
+		assert_eq!(choose_voice(&voices, 1), Some(2));
+		assert_eq!(choose_voice(&voices, 5), Some(2));
+	}
+
+	#[test]
+	fn higher_priorities_are_left_playing() {
+		assert_eq!(choose_voice(&[voice(2, 1), voice(3, 2)], 1), None);
+	}
+}
@@ -0,0 +1,157 @@
+use std::collections::HashMap;
+use std::vec::Vec;
+
+use game::collisions::Rectangle;
+use game::units;
+use game::units::AsTile;
+
+/// A broadphase for collision & visibility checks: remembers which
+/// tiles each entity's rectangle overlaps so a query only has to look
+/// at the entities sharing tiles w/ it, rather than at all of them.
+///
+/// Entities are stored as their index in whichever `Vec` owns them, so
+/// the hash must be told when one is removed (see `remove`) & rebuilt
+/// once that `Vec` is reordered.
+pub struct SpatialHash {
+	cells: HashMap<(u32, u32), Vec<usize>>,
+	// the cells each entity is filed under, by index, so removing one
+	// only touches those
+	filed: Vec<Vec<(u32, u32)>>
+}
+
+impl SpatialHash {
+	pub fn new() -> SpatialHash {
+		SpatialHash { cells: HashMap::new(), filed: Vec::new() }
+	}
+
+	/// Forgets every entity, keeping the cells' storage for reuse.
+	pub fn clear(&mut self) {
+		for cell in self.cells.values_mut() {
+			cell.clear();
+		}
+		self.filed.clear();
+	}
+
+	/// Files the entity at `index` under every tile `rectangle` touches.
+	pub fn insert(&mut self, index: usize, rectangle: &Rectangle) {
+		while self.filed.len() <= index {
+			self.filed.push(Vec::new());
+		}
+
+		let ((first_col, last_col), (first_row, last_row)) = tile_span(rectangle);
+		for row in first_row.. last_row + 1 {
+			for col in first_col.. last_col + 1 {
+				self.cells.entry((col, row)).or_insert(Vec::new()).push(index);
+				self.filed[index].push((col, row));
+			}
+		}
+	}
+
+	/// Forgets the entity at `index` & refiles the last one in its place,
+	/// to match `Vec::swap_remove(index)` on the `Vec` which owns them.
+	/// Only the cells of those two entities are touched.
+	pub fn remove(&mut self, index: usize) {
+		let last = self.filed.len() - 1;
+		for key in self.filed[index].iter() {
+			match self.cells.get_mut(key) {
+				Some(cell) => { cell.retain(|&entry| entry != index); },
+				None => {}
+			}
+		}
+		if index != last {
+			for key in self.filed[last].iter() {
+				match self.cells.get_mut(key) {
+					Some(cell) => {
+						for entry in cell.iter_mut() {
+							if *entry == last {
+								*entry = index;
+							}
+						}
+					},
+					None => {}
+				}
+			}
+		}
+		self.filed.swap_remove(index);
+	}
+
+	/// The indices of the entities which may overlap `rectangle`, in
+	/// ascending order & w/o duplicates. Callers still have to check the
+	/// actual rectangles: sharing a tile doesn't mean colliding.
+	pub fn query(&self, rectangle: &Rectangle) -> Vec<usize> {
+		let ((first_col, last_col), (first_row, last_row)) = tile_span(rectangle);
+		let mut found: Vec<usize> = Vec::new();
+		for row in first_row.. last_row + 1 {
+			for col in first_col.. last_col + 1 {
+				match self.cells.get(&(col, row)) {
+					Some(cell) => { found.extend(cell.iter().cloned()); },
+					None => {}
+				}
+			}
+		}
+		found.sort();
+		found.dedup();
+
+		found
+	}
+}
+
+/// The first & last column & row of tiles `rectangle` touches.
+/// Anything left of or above the map is filed under its first tile.
+fn tile_span(rectangle: &Rectangle) -> ((u32, u32), (u32, u32)) {
+	let (left, top) = (clamped(rectangle.left()), clamped(rectangle.top()));
+	let (right, bottom) = (clamped(rectangle.right()), clamped(rectangle.bottom()));
+	let (units::Tile(first_col), units::Tile(last_col)) = (left.to_tile(), right.to_tile());
+	let (units::Tile(first_row), units::Tile(last_row)) = (top.to_tile(), bottom.to_tile());
+
+	((first_col, last_col), (first_row, last_row))
+}
+
+fn clamped(position: units::Game) -> units::Game {
+	let units::Game(position) = position;
+	units::Game(position.max(0.0))
+}
+
+#[cfg(test)]
+mod tests {
+	use super::SpatialHash;
+	use game::collisions::Rectangle;
+	use game::units;
+
+	/// The 16x16 rectangle in the middle of tile (`col`, `row`).
+	fn in_tile(col: u32, row: u32) -> Rectangle {
+		Rectangle {
+			x: units::Game((col * units::TILE_SIZE as u32 + 8) as f64),
+			y: units::Game((row * units::TILE_SIZE as u32 + 8) as f64),
+			width: units::Game(16.0), height: units::Game(16.0)
+		}
+	}
+
+	#[test]
+	fn query_finds_what_shares_a_tile() {
+		let mut grid = SpatialHash::new();
+		grid.insert(0, &in_tile(1, 1));
+		grid.insert(1, &in_tile(5, 5));
+		grid.insert(2, &in_tile(1, 1));
+		assert_eq!(grid.query(&in_tile(1, 1)), vec![0, 2]);
+		assert!(grid.query(&in_tile(3, 3)).is_empty());
+	}
+
+	#[test]
+	fn remove_moves_the_last_entity_into_place() {
+		let mut grid = SpatialHash::new();
+		grid.insert(0, &in_tile(1, 1));
+		grid.insert(1, &in_tile(2, 2));
+		grid.insert(2, &in_tile(3, 3));
+		grid.remove(0);
+
+		assert!(grid.query(&in_tile(1, 1)).is_empty());
+		assert_eq!(grid.query(&in_tile(2, 2)), vec![1]);
+		assert_eq!(grid.query(&in_tile(3, 3)), vec![0]);
+
+		// & the moved entity can be removed from its new place
+		grid.remove(0);
+		assert!(grid.query(&in_tile(3, 3)).is_empty());
+		assert_eq!(grid.query(&in_tile(2, 2)), vec![0]);
+	}
+}
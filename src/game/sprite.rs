@@ -1,9 +1,16 @@
 use sdl2::rect;
 use sdl2::render;
 
+use std::cell::RefCell;
+use std::cmp;
+use std::collections::{HashMap, HashSet};
+use std::fs::File;
+use std::io::{BufRead, BufReader};
 use std::rc::Rc;
 
+use game::assets;
 use game::graphics;
+use game::packs;
 
 use game::units;
 use game::units::{AsGame,AsPixel};
@@ -23,6 +30,25 @@ pub enum Facing {
 }
 pub const FACINGS: [Facing; 2] = [Facing::West, Facing::East];
 
+/// How `movement` is named in the sprite manifest, e.g. `walk.east`.
+pub fn movement_name(movement: (Motion, Facing)) -> String {
+	let (motion, facing) = movement;
+	let motion = match motion {
+		Motion::Walking  => "walk",
+		Motion::Standing => "stand"
+	};
+
+	format!("{}.{}", motion, facing_name(facing))
+}
+
+/// How `facing` is named in the sprite manifest.
+pub fn facing_name(facing: Facing) -> &'static str {
+	match facing {
+		Facing::West => "west",
+		Facing::East => "east"
+	}
+}
+
 /// Any object which can be represented in 2D space
 /// Coord represents the unit which describes this object's
 /// position in 3D space.
@@ -35,6 +61,13 @@ pub trait Drawable<Coord> {
 /// Any object which understands time and placement in 2D space.
 pub trait Updatable<T> : Drawable<T> { 
 	fn update(&mut self, elapsed_time: units::Millis);
+
+	/// Goes back to the first frame, as when an animator switches to it.
+	fn restart(&mut self) {}
+
+	/// Whether a sprite which plays once has shown its last frame; a sprite
+	/// which loops (or is still) never finishes.
+	fn is_finished(&self) -> bool { false }
 }
 
 /// Represents a static 32x32 2D character
@@ -42,13 +75,15 @@ pub struct Sprite {
 	sprite_sheet:  String,
 	source_rect:   rect::Rect,
 	size:    (units::Game, units::Game),
+	texture: RefCell<Option<assets::TextureHandle>>,
 }
 
 impl Sprite {
 	/// A new sprite which will draw itself at `coords`
 	/// `sprite_at` is the index (row) where the sprite starts in `file_name`
+	///
+	/// The sheet itself is not loaded until the sprite is first drawn.
 	pub fn new<O:AsGame, S:AsGame>(
-		graphics: &mut graphics::Graphics, 
 		offset:  (O,O),  // source_x, source_ys
 		size:    (S,S),  // width, height
 		file_name: String
@@ -70,12 +105,11 @@ impl Sprite {
 			Err(msg) => { panic!(msg) }
 		};
 
-		graphics.load_image(file_name.clone(), true);  // request graphics subsystem cache this sprite.
-
 		return Sprite {
 			sprite_sheet:  file_name,
 			source_rect:   origin.unwrap(),
 			size:          (norm_w,norm_h),
+			texture:       RefCell::new(None),
 		};
 	}
 }
@@ -95,7 +129,10 @@ impl<C: AsGame> Drawable<C> for Sprite {
 			Err(msg) => { panic!(msg) }
 		};
 
-		display.blit_surface(&self.sprite_sheet, &self.source_rect, &dest_rect.unwrap());
+		match sheet_texture(display, &self.sprite_sheet, &self.texture) {
+			Some(texture) => { display.blit_surface(&texture, &self.source_rect, &dest_rect.unwrap()); },
+			None => {}
+		}
 	}
 }
 
@@ -106,30 +143,44 @@ impl<C: AsGame> Updatable<C> for Sprite {
 	}
 }
 
-/// Represents a 32x32 2D character w/ a number of frames
-/// Frames will be selected based on time-deltas supplied through update
+/// How an animation carries on once it has shown its last frame.
+#[derive(PartialEq,Eq,Clone,Copy,Debug)]
+pub enum Playback {
+	/// Starts over from the first frame.
+	Loop,
+	/// Runs back to the first frame, then forward again, & so on.
+	PingPong,
+	/// Stays on the last frame & is finished.
+	Once
+}
+
+/// Represents a 32x32 2D character w/ a number of frames, each shown for
+/// its own length of time. Frames are picked based on the time-deltas
+/// supplied through update.
 #[allow(dead_code)]
 pub struct AnimatedSprite {
 	pub source_rect:   rect::Rect,
 	pub sprite_sheet:  String,
 
-	offset:  (units::Tile, units::Tile),
 	size:    (units::Tile, units::Tile),
+	frames:  Vec<(rect::Rect, units::Millis)>,
+	playback:  Playback,
 
-	current_frame:  units::Frame,
-	num_frames:     units::Frame,
-	fps:            units::Fps,
+	current_frame:  usize,
+	backwards:      bool,
+	finished:       bool,
 
 	last_update: units::Millis,
+	texture:     RefCell<Option<assets::TextureHandle>>,
 }
 
 impl AnimatedSprite {
-	/// Loads character sprites from `assets/MyChar.bmp`
-	/// `source_rect` acts as a viewport of this sprite-sheet.
+	/// An animation of `num_frames` frames at `fps`, looping, the first at
+	/// `offset` on `sheet_path` & the rest following it to its right.
 	///
-	/// Returns an error message if sprite-sheet could not be loaded.
+	/// Returns an error message if there are no frames.
+	/// (The sheet is only handed to the graphics subsystem once the sprite is drawn.)
 	pub fn new(
-		graphics:    &mut graphics::Graphics,
 		sheet_path:  String,
 
 		offset:  (units::Tile, units::Tile),
@@ -138,61 +189,112 @@ impl AnimatedSprite {
 		num_frames:  units::Frame,
 		fps:         units::Fps
 	) -> Result<AnimatedSprite, String> {
-		// attempt to load sprite-sheet from `assets/MyChar.bmp`
-		let (w,h) = size;
 		let (x,y) = offset;
-	
+		let (w,_) = size;
+		let frame_time = units::Millis(1000 / cmp::max(fps, 1) as i64);
+		let frames: Vec<_> = (0..num_frames)
+			.map(|frame| ((x + w * units::Tile(frame), y), frame_time))
+			.collect();
+
+		AnimatedSprite::from_frames(sheet_path, size, &frames, Playback::Loop)
+	}
+
+	/// An animation through `frames`: the tiles each starts at on
+	/// `sheet_path` & how long it's shown for, played as `playback` says.
+	///
+	/// Returns an error message if there are no frames.
+	pub fn from_frames(
+		sheet_path:  String,
+		size:        (units::Tile, units::Tile),
+		frames:      &[((units::Tile, units::Tile), units::Millis)],
+		playback:    Playback
+	) -> Result<AnimatedSprite, String> {
+		if frames.len() == 0 {
+			return Err(format!("an animation on {} needs at least one frame", sheet_path));
+		}
+
+		let (w,h) = size;
 		let (units::Pixel(wi), units::Pixel(hi)) = (w.to_pixel(), h.to_pixel());
-		let (units::Pixel(xi), units::Pixel(yi)) = (x.to_pixel(), y.to_pixel());
-		
-		let origin = match rect::Rect::new(xi, yi, wi as u32, hi as u32) {
-			Ok(rect) => { rect },
-			Err(msg) => { panic!(msg) }
-		};
-		
-		graphics.load_image(sheet_path.clone(), true); // request graphics subsystem cache this sprite.
+		let mut rects = Vec::new();
+		for &((x,y), duration) in frames.iter() {
+			let (units::Pixel(xi), units::Pixel(yi)) = (x.to_pixel(), y.to_pixel());
+			let rect = match rect::Rect::new(xi, yi, wi as u32, hi as u32) {
+				Ok(rect) => { rect.unwrap() },
+				Err(msg) => { panic!(msg) }
+			};
+			// a frame w/o any time at all would never be left
+			rects.push((rect, cmp::max(duration, units::Millis(1))));
+		}
+
 		let sprite = AnimatedSprite {
-			offset:  offset,
 			size:    size,
-			
-			fps: fps,
+			source_rect:  rects[0].0,
+			frames:       rects,
+			playback:     playback,
+
 			current_frame: 0,
-			num_frames:   num_frames,        // our frames are drawn w/ a 0-idx'd window.
-			last_update:  units::Millis(0),
-			
+			backwards:     false,
+			finished:      false,
+			last_update:   units::Millis(0),
+
 			sprite_sheet:  sheet_path,
-			source_rect:   origin.unwrap(),
+			texture:       RefCell::new(None),
 		};
 
 		return Ok(sprite);
 	}
+
+	/// Moves on to the next frame, as the playback mode says.
+	fn advance(&mut self) {
+		let last = self.frames.len() - 1;
+		match self.playback {
+			Playback::Loop => {
+				self.current_frame = if self.current_frame < last { self.current_frame + 1 } else { 0 };
+			},
+			Playback::Once => {
+				if self.current_frame < last {
+					self.current_frame += 1;
+				} else {
+					self.finished = true;
+				}
+			},
+			Playback::PingPong if last == 0 => {},
+			Playback::PingPong => {
+				if self.backwards && self.current_frame == 0 {
+					self.backwards = false;
+				} else if !self.backwards && self.current_frame == last {
+					self.backwards = true;
+				}
+				self.current_frame = if self.backwards { self.current_frame - 1 } else { self.current_frame + 1 };
+			}
+		}
+		self.source_rect = self.frames[self.current_frame].0;
+	}
 }
 
 impl<C: AsGame> Updatable<C> for AnimatedSprite {
 	/// Reads current time-deltas and mutates state accordingly.
 	fn update(&mut self, elapsed_time: units::Millis) {
-		let frame_time = units::Millis(1000 / self.fps as i64);
 		self.last_update = self.last_update + elapsed_time;
 
-		// if we have missed drawing a frame
-		if self.last_update > frame_time {
-			self.last_update = units::Millis(0);  // reset timer
-			self.current_frame += 1;              // increment frame counter
-
-			if self.current_frame < self.num_frames {
-				self.source_rect = match rect::Rect::new(self.source_rect.x() + self.source_rect.width() as i32, self.source_rect.y(), self.source_rect.width(), self.source_rect.height()) {
-					Ok(rect) => { rect.unwrap() },
-					Err(msg) => { panic!(msg) }
-				}
-			} else {
-				self.current_frame  = 0;
-				self.source_rect = match rect::Rect::new(self.source_rect.x() - self.source_rect.width() as i32 * (self.num_frames - 1) as i32, self.source_rect.y(), self.source_rect.width(), self.source_rect.height()) {
-					Ok(rect) => { rect.unwrap() },
-					Err(msg) => { panic!(msg) }
-				}
-			}
+		// catch up on every frame we have missed drawing
+		while !self.finished && self.last_update >= self.frames[self.current_frame].1 {
+			self.last_update = self.last_update - self.frames[self.current_frame].1;
+			self.advance();
 		}
 	}
+
+	fn restart(&mut self) {
+		self.current_frame = 0;
+		self.backwards = false;
+		self.finished = false;
+		self.last_update = units::Millis(0);
+		self.source_rect = self.frames[0].0;
+	}
+
+	fn is_finished(&self) -> bool {
+		self.finished
+	}
 }
 
 impl<C: AsGame> Drawable<C> for AnimatedSprite {
@@ -209,6 +311,311 @@ impl<C: AsGame> Drawable<C> for AnimatedSprite {
 			Ok(d) => { d },
 			Err(msg) => { panic!(msg) }
 		};
-		display.blit_surface(&self.sprite_sheet, &self.source_rect, &dest_rect.unwrap());
+		match sheet_texture(display, &self.sprite_sheet, &self.texture) {
+			Some(texture) => { display.blit_surface(&texture, &self.source_rect, &dest_rect.unwrap()); },
+			None => {}
+		}
+	}
+}
+
+/// The handle to `sheet`, which is only loaded (& kept in `texture`) the
+/// first time a sprite on it is drawn. A sheet which can't be loaded is
+/// drawn as a placeholder (see `Graphics::image_or_placeholder`).
+fn sheet_texture(
+	display: &mut graphics::Graphics,
+	sheet:   &str,
+	texture: &RefCell<Option<assets::TextureHandle>>
+) -> Option<assets::TextureHandle> {
+	let mut texture = texture.borrow_mut();
+	if texture.is_none() {
+		*texture = display.image_or_placeholder(sheet, true);
+	}
+
+	texture.clone()
+}
+
+static SPRITES_HEADER: &'static str = "just-run sprites 1";
+
+/// Where the sprites built by `named()` are described.
+pub static SPRITE_MANIFEST: &'static str = "assets/sprites.cfg";
+
+/// A sprite as described in the manifest: the size of its frames on
+/// `sheet`, where each one is & how long it's shown for, & how they play.
+/// A sprite w/o a playback mode is still & has just the one frame.
+#[derive(Clone)]
+pub struct SpriteDef {
+	pub sheet:    String,
+	pub size:     (units::Tile, units::Tile),
+	pub frames:   Vec<((units::Tile, units::Tile), units::Millis)>,
+	pub playback: Option<Playback>
+}
+
+impl SpriteDef {
+	/// A sprite whose first frame is at `offset`; if it's animated, its
+	/// other frames follow to the right, evenly timed & looping.
+	pub fn strip(
+		sheet:     String,
+		offset:    (units::Tile, units::Tile),
+		size:      (units::Tile, units::Tile),
+		animation: Option<(units::Frame, units::Fps)>
+	) -> SpriteDef {
+		let (x,y) = offset;
+		let (w,_) = size;
+		match animation {
+			Some((num_frames, fps)) => {
+				let frame_time = units::Millis(1000 / cmp::max(fps, 1) as i64);
+				SpriteDef {
+					sheet:    sheet,
+					size:     size,
+					frames:   (0..num_frames).map(|frame| ((x + w * units::Tile(frame), y), frame_time)).collect(),
+					playback: Some(Playback::Loop)
+				}
+			},
+			None => {
+				SpriteDef { sheet: sheet, size: size, frames: vec![(offset, units::Millis(0))], playback: None }
+			}
+		}
+	}
+
+	pub fn build(&self) -> Box<Updatable<units::Game>> {
+		match self.playback {
+			Some(playback) => {
+				Box::new( AnimatedSprite::from_frames(
+					self.sheet.clone(), self.size, &self.frames, playback
+				).unwrap() ) as Box<Updatable<_>>
+			},
+			None => {
+				Box::new( Sprite::new(self.frames[0].0, self.size, self.sheet.clone()) ) as Box<Updatable<_>>
+			}
+		}
+	}
+}
+
+/// Every sprite in a manifest, by name.
+pub struct SpriteDefs {
+	defs: HashMap<String, SpriteDef>
+}
+
+impl SpriteDefs {
+	/// Reads the sprites listed in `file_path`, a plain text file:
+	///
+	///- `just-run sprites 1`
+	///- `sheet <image>` the sheet the sprites after it are on; a sheet which
+///  is missing is only reported (& drawn as a placeholder) once drawn
+	///- `sprite <name> <col> <row> <width> <height>` a sprite, in tiles,
+	///  followed by `<frames> <fps>` if it's animated; its frames run to the
+	///  right & loop
+	///- `clip <name> <width> <height> <loop|pingpong|once>` an animation
+	///  whose frames are listed by the `frame <col> <row> <millis>` lines
+	///  after it, each shown for its own time
+	///
+	/// Names are dotted, from the thing drawn to what it's doing, e.g.
+	/// `player.walk.east`. Lines starting w/ `#` are comments. Returns an
+	/// error naming the offending line if the file is malformed.
+	pub fn load(file_path: &str) -> Result<SpriteDefs, String> {
+		let file = match File::open(packs::locate(file_path)) {
+			Ok(file) => { file },
+			Err(msg) => { return Err(format!("could not open sprites {}: {}", file_path, msg)); }
+		};
+
+		let mut defs: HashMap<String, SpriteDef> = HashMap::new();
+		let mut sheet: Option<String> = None;
+		let mut clip: Option<String> = None;
+		for (index, line) in BufReader::new(file).lines().enumerate() {
+			let line_num = index + 1;
+			let line = match line {
+				Ok(line) => { line },
+				Err(msg) => { return Err(format!("{}:{}: {}", file_path, line_num, msg)); }
+			};
+
+			if line_num == 1 {
+				if line.trim() != SPRITES_HEADER {
+					return Err(format!("{}:{}: not a sprites file", file_path, line_num));
+				}
+				continue;
+			}
+
+			let fields: Vec<&str> = line.split_whitespace().collect();
+			if fields.len() == 0 || fields[0].starts_with("#") {
+				continue;
+			}
+
+			match (fields[0], fields.len()) {
+				("sheet", 2) => {
+					clip = None;
+					sheet = Some(fields[1].to_string());
+				},
+				("sprite", 6) | ("sprite", 8) => {
+					let sheet = match sheet {
+						Some(ref sheet) => { sheet.clone() },
+						None => { return Err(format!("{}:{}: `sprite` must follow a `sheet`", file_path, line_num)); }
+					};
+					if defs.contains_key(fields[1]) {
+						return Err(format!("{}:{}: sprite `{}` is listed twice", file_path, line_num, fields[1]));
+					}
+					let mut numbers: Vec<u32> = Vec::new();
+					for field in fields[2..].iter() {
+						numbers.push(try!(parse_number(file_path, line_num, field)));
+					}
+					let animation = if numbers.len() == 6 { Some((numbers[4], numbers[5])) } else { None };
+					defs.insert(fields[1].to_string(), SpriteDef::strip(
+						sheet,
+						(units::Tile(numbers[0]), units::Tile(numbers[1])),
+						(units::Tile(numbers[2]), units::Tile(numbers[3])),
+						animation
+					));
+					clip = None;
+				},
+				("sprite", _) => {
+					return Err(format!("{}:{}: expected `sprite <name> <col> <row> <width> <height> [<frames> <fps>]`", file_path, line_num));
+				},
+				("clip", 5) => {
+					let sheet = match sheet {
+						Some(ref sheet) => { sheet.clone() },
+						None => { return Err(format!("{}:{}: `clip` must follow a `sheet`", file_path, line_num)); }
+					};
+					if defs.contains_key(fields[1]) {
+						return Err(format!("{}:{}: sprite `{}` is listed twice", file_path, line_num, fields[1]));
+					}
+					let width = try!(parse_number(file_path, line_num, fields[2]));
+					let height = try!(parse_number(file_path, line_num, fields[3]));
+					let playback = match fields[4] {
+						"loop"     => Playback::Loop,
+						"pingpong" => Playback::PingPong,
+						"once"     => Playback::Once,
+						other => { return Err(format!("{}:{}: expected `loop`, `pingpong` or `once`, found `{}`", file_path, line_num, other)); }
+					};
+					defs.insert(fields[1].to_string(), SpriteDef {
+						sheet:    sheet,
+						size:     (units::Tile(width), units::Tile(height)),
+						frames:   Vec::new(),
+						playback: Some(playback)
+					});
+					clip = Some(fields[1].to_string());
+				},
+				("clip", _) => {
+					return Err(format!("{}:{}: expected `clip <name> <width> <height> <loop|pingpong|once>`", file_path, line_num));
+				},
+				("frame", 4) => {
+					let def = match clip {
+						Some(ref name) => { defs.get_mut(name).unwrap() },
+						None => { return Err(format!("{}:{}: `frame` must follow a `clip`", file_path, line_num)); }
+					};
+					let col = try!(parse_number(file_path, line_num, fields[1]));
+					let row = try!(parse_number(file_path, line_num, fields[2]));
+					let millis = try!(parse_number(file_path, line_num, fields[3]));
+					def.frames.push(((units::Tile(col), units::Tile(row)), units::Millis(millis as i64)));
+				},
+				("frame", _) => {
+					return Err(format!("{}:{}: expected `frame <col> <row> <millis>`", file_path, line_num));
+				},
+				(other, _) => {
+					return Err(format!("{}:{}: unexpected `{}`", file_path, line_num, other));
+				}
+			}
+		}
+
+		match defs.iter().find(|&(_, def)| def.frames.len() == 0) {
+			Some((name, _)) => { Err(format!("{}: clip `{}` has no frames", file_path, name)) },
+			None => { Ok(SpriteDefs { defs: defs }) }
+		}
+	}
+
+	pub fn get(&self, name: &str) -> Option<&SpriteDef> {
+		self.defs.get(name)
+	}
+}
+
+// the manifest, read the first time a sprite is built by name; one which
+// can't be read is reported & leaves every sprite a placeholder
+thread_local!(static MANIFEST: SpriteDefs = match SpriteDefs::load(SPRITE_MANIFEST) {
+	Ok(defs) => defs,
+	Err(msg) => {
+		println!("{}, drawing placeholders", msg);
+		SpriteDefs { defs: HashMap::new() }
+	}
+});
+
+// the names asked for which aren't in the manifest, so each is reported once
+thread_local!(static MISSING: RefCell<HashSet<String>> = RefCell::new(HashSet::new()));
+
+/// Builds the sprite called `name` in the sprite manifest (see
+/// `SpriteDefs::load`), animated or not as it says. A sprite which isn't
+/// in the manifest is reported & drawn as a placeholder, like a missing sheet.
+pub fn named(name: &str) -> Box<Updatable<units::Game>> {
+	let found = MANIFEST.with(|defs| defs.get(name).map(|def| def.build()));
+	match found {
+		Some(sprite) => { sprite },
+		None => {
+			MISSING.with(|missing| {
+				if missing.borrow_mut().insert(name.to_string()) {
+					println!("there is no sprite `{}` in {}, drawing a placeholder", name, SPRITE_MANIFEST);
+				}
+			});
+			// no sheet is found at this path, so it's drawn as a placeholder
+			let sheet = format!("<missing sprite {}>", name);
+			Box::new( Sprite::new((units::Tile(0), units::Tile(0)), (units::Tile(1), units::Tile(1)), sheet) ) as Box<Updatable<_>>
+		}
+	}
+}
+
+fn parse_number(file_path: &str, line_num: usize, field: &str) -> Result<u32, String> {
+	match field.parse::<u32>() {
+		Ok(value) => { Ok(value) },
+		Err(_) => { Err(format!("{}:{}: expected a number, found `{}`", file_path, line_num, field)) }
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::{AnimatedSprite, Playback, Updatable};
+	use game::units;
+
+	/// A three frame animation played as `playback`, each frame for 100ms.
+	fn animation(playback: Playback) -> AnimatedSprite {
+		let frames: Vec<_> = (0.. 3).map(|frame| ((units::Tile(frame), units::Tile(0)), units::Millis(100))).collect();
+		AnimatedSprite::from_frames("sheet.bmp".to_string(), (units::Tile(1), units::Tile(1)), &frames, playback).ok().unwrap()
+	}
+
+	/// The frame shown at first & after each of `steps` more frame lengths.
+	fn frames_shown(sprite: &mut AnimatedSprite, steps: usize) -> Vec<usize> {
+		let mut shown = vec![sprite.current_frame];
+		for _ in 0.. steps {
+			<AnimatedSprite as Updatable<units::Game>>::update(sprite, units::Millis(100));
+			shown.push(sprite.current_frame);
+		}
+		shown
+	}
+
+	#[test]
+	fn loop_starts_over() {
+		let mut sprite = animation(Playback::Loop);
+		assert_eq!(frames_shown(&mut sprite, 6), vec![0, 1, 2, 0, 1, 2, 0]);
+		assert!(!<AnimatedSprite as Updatable<units::Game>>::is_finished(&sprite));
+	}
+
+	#[test]
+	fn ping_pong_runs_back() {
+		let mut sprite = animation(Playback::PingPong);
+		assert_eq!(frames_shown(&mut sprite, 8), vec![0, 1, 2, 1, 0, 1, 2, 1, 0]);
+	}
+
+	#[test]
+	fn once_stops_on_the_last_frame() {
+		let mut sprite = animation(Playback::Once);
+		assert_eq!(frames_shown(&mut sprite, 2), vec![0, 1, 2]);
+		assert!(!<AnimatedSprite as Updatable<units::Game>>::is_finished(&sprite));
+		assert_eq!(frames_shown(&mut sprite, 2), vec![2, 2, 2]);
+		assert!(<AnimatedSprite as Updatable<units::Game>>::is_finished(&sprite));
+
+		<AnimatedSprite as Updatable<units::Game>>::restart(&mut sprite);
+		assert_eq!(frames_shown(&mut sprite, 1), vec![0, 1]);
+	}
+
+	#[test]
+	fn long_updates_skip_frames() {
+		let mut sprite = animation(Playback::Loop);
+		<AnimatedSprite as Updatable<units::Game>>::update(&mut sprite, units::Millis(250));
+		assert_eq!(sprite.current_frame, 2);
 	}
 }
@@ -0,0 +1,60 @@
+use std::env;
+use std::fs;
+use std::fs::File;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+// the directory under the platform's data directory the game keeps its files in
+static DATA_DIRECTORY: &'static str = "just-run";
+
+/// Where the game keeps files which should outlive a run, e.g. the
+/// leaderboard: `$XDG_DATA_HOME/just-run`, `~/.local/share/just-run` or
+/// `%APPDATA%\just-run`. Falls back to the working directory if none of
+/// those are set or the directory can't be created.
+pub fn data_dir() -> PathBuf {
+	let base = match (env::var_os("XDG_DATA_HOME"), env::var_os("APPDATA"), env::var_os("HOME")) {
+		(Some(data_home), _, _) => { Some(PathBuf::from(data_home)) },
+		(None, Some(app_data), _) => { Some(PathBuf::from(app_data)) },
+		(None, None, Some(home)) => { Some(Path::new(&home).join(".local").join("share")) },
+		(None, None, None) => { None }
+	};
+
+	match base {
+		Some(base) => {
+			let dir = base.join(DATA_DIRECTORY);
+			match fs::create_dir_all(&dir) {
+				Ok(_) => { dir },
+				Err(msg) => {
+					println!("could not create {}: {}", dir.display(), msg);
+					PathBuf::from(".")
+				}
+			}
+		},
+		None => { PathBuf::from(".") }
+	}
+}
+
+/// Replaces `file_path` w/ `contents` so that it is never left half
+/// written: the contents go to a file beside it, which is then moved over it.
+pub fn write_atomically(file_path: &Path, contents: &str) -> Result<(), String> {
+	let temp_path = file_path.with_extension("tmp");
+	{
+		let mut file = match File::create(&temp_path) {
+			Ok(file) => { file },
+			Err(msg) => { return Err(format!("could not create {}: {}", temp_path.display(), msg)); }
+		};
+		let written = match file.write_all(contents.as_bytes()) {
+			Ok(_) => { file.sync_all() },
+			Err(msg) => { Err(msg) }
+		};
+		match written {
+			Ok(_) => {},
+			Err(msg) => { return Err(format!("could not write {}: {}", temp_path.display(), msg)); }
+		}
+	}
+
+	match fs::rename(&temp_path, file_path) {
+		Ok(_) => { Ok(()) },
+		Err(msg) => { Err(format!("could not replace {}: {}", file_path.display(), msg)) }
+	}
+}
@@ -0,0 +1,236 @@
+use std::collections::HashMap;
+use std::collections::hash_map::Entry;
+use std::string::String;
+use std::vec::Vec;
+
+use sdl2::pixels;
+use sdl2::rect;
+use sdl2::render;
+use sdl2_ttf;
+
+use game::packs;
+
+static FONT_FILE: &'static str = "assets/font.ttf";
+
+// how many rendered strings are kept before the cache starts over; text
+// which changes every frame (like the timer) would fill it otherwise
+static MAX_CACHED: usize = 128;
+
+/// Which way the lines of some text line up w/ the `x` they're drawn at.
+#[derive(PartialEq,Eq,Clone,Copy,Debug)]
+pub enum Align {
+	/// Lines start at `x`.
+	Left,
+	/// Lines are centered on `x`.
+	Center,
+	/// Lines end at `x`.
+	Right
+}
+
+/// Something drawn behind text to set it off from what's under it.
+#[derive(PartialEq,Eq,Clone,Copy,Debug)]
+pub enum Effect {
+	Plain,
+	/// A copy in this color, this many pixels down & to the right.
+	Shadow((u8, u8, u8), i32),
+	/// A border in this color, this many pixels thick, all the way round.
+	Outline((u8, u8, u8), i32)
+}
+
+/// How text is drawn. Styles are plain values, so callers usually keep
+/// theirs in statics or tweak one w/ `Style { size: 40, .. style }`.
+#[derive(PartialEq,Eq,Clone,Copy,Debug)]
+pub struct Style {
+	/// In points, as the font is loaded at.
+	pub size:   i32,
+	pub color:  (u8, u8, u8),
+	pub align:  Align,
+	/// Lines are broken at spaces to fit this many pixels, if set.
+	pub wrap:   Option<u32>,
+	pub effect: Effect
+}
+
+impl Style {
+	/// Plain red text of `size` points, lined up on the left & not wrapped.
+	pub fn new(size: i32) -> Style {
+		Style { size: size, color: (255, 0, 0), align: Align::Left, wrap: None, effect: Effect::Plain }
+	}
+}
+
+// a rendered line of text: what was rendered & how
+#[derive(PartialEq,Eq,Hash,Clone)]
+struct RenderKey {
+	text:    String,
+	size:    i32,
+	color:   (u8, u8, u8),
+	outline: i32
+}
+
+/// The fonts, loaded once at each size they're used at, & the lines of
+/// text rendered w/ them, so the same text isn't rendered every frame.
+///
+/// When the font can't be loaded the error is reported once & no text is
+/// drawn; it measures as taking up no room.
+pub struct TextCache {
+	fonts:    HashMap<i32, sdl2_ttf::Font>,
+	rendered: HashMap<RenderKey, render::Texture>,
+	// set once loading the font has failed, so it isn't retried every frame
+	no_font:  bool
+}
+
+impl TextCache {
+	pub fn new() -> TextCache {
+		TextCache { fonts: HashMap::new(), rendered: HashMap::new(), no_font: false }
+	}
+
+	/// The width & height `text` takes up in `style`, once wrapped; effects
+	/// are drawn outside of it.
+	pub fn measure(&mut self, text: &str, style: &Style) -> (u32, u32) {
+		let lines = self.layout(text, style);
+		let widths: Vec<u32> = lines.iter().map(|line| self.line_width(line, style.size)).collect();
+		let width = widths.into_iter().max().unwrap_or(0);
+		(width, lines.len() as u32 * self.line_skip(style.size))
+	}
+
+	/// Draws `text` in `style` w/ the top of its first line at `y`; `x` is
+	/// where its lines start, are centered on or end, going by the style's
+	/// alignment. Responds w/ the height drawn, for stacking text.
+	pub fn draw(&mut self, screen: &mut render::Renderer, text: &str, x: i32, y: i32, style: &Style) -> u32 {
+		let lines = self.layout(text, style);
+		let skip = self.line_skip(style.size);
+		for (index, line) in lines.iter().enumerate() {
+			let width = self.line_width(line, style.size) as i32;
+			let left = match style.align {
+				Align::Left   => x,
+				Align::Center => x - width / 2,
+				Align::Right  => x - width
+			};
+			self.draw_line(screen, line, left, y + index as i32 * skip as i32, style);
+		}
+
+		lines.len() as u32 * skip
+	}
+
+	/// Splits `text` into the lines it's drawn as: at each `\n`, & at the
+	/// spaces needed to keep inside the style's `wrap`. A word too long
+	/// to fit is left on a line of its own.
+	fn layout(&mut self, text: &str, style: &Style) -> Vec<String> {
+		let mut lines: Vec<String> = Vec::new();
+		for paragraph in text.split('\n') {
+			let wrap = match style.wrap {
+				Some(wrap) => { wrap },
+				None => { lines.push(paragraph.to_string()); continue; }
+			};
+
+			let mut line = String::new();
+			for word in paragraph.split(' ') {
+				let longer = if line.len() == 0 { word.to_string() } else { format!("{} {}", line, word) };
+				if line.len() > 0 && self.line_width(&longer, style.size) > wrap {
+					lines.push(line);
+					line = word.to_string();
+				} else {
+					line = longer;
+				}
+			}
+			lines.push(line);
+		}
+
+		lines
+	}
+
+	/// The font at `size`, loading it the first time; `None` if it can't be.
+	fn font(&mut self, size: i32) -> Option<&mut sdl2_ttf::Font> {
+		if self.no_font {
+			return None;
+		}
+		match self.fonts.entry(size) {
+			Entry::Occupied(entry) => { return Some(entry.into_mut()); },
+			Entry::Vacant(entry) => {
+				let path = packs::locate(FONT_FILE);
+				match sdl2_ttf::Font::from_file(&path, size) {
+					Ok(font) => { return Some(entry.insert(font)); },
+					Err(msg) => { println!("could not load font {}, drawing no text: {}", path.display(), msg); }
+				}
+			}
+		}
+		self.no_font = true;
+		None
+	}
+
+	fn line_width(&mut self, line: &str, size: i32) -> u32 {
+		if line.len() == 0 {
+			return 0;
+		}
+		match self.font(size) {
+			Some(font) => match font.size_of(line) {
+				Ok((width, _)) => { width },
+				Err(_) => { 0 }
+			},
+			None => { 0 }
+		}
+	}
+
+	fn line_skip(&mut self, size: i32) -> u32 {
+		match self.font(size) {
+			Some(font) => { font.recommended_line_spacing() as u32 },
+			None => { 0 }
+		}
+	}
+
+	/// Draws one line w/ its top-left corner at (`x`, `y`), effect & all.
+	fn draw_line(&mut self, screen: &mut render::Renderer, line: &str, x: i32, y: i32, style: &Style) {
+		if line.trim().len() == 0 {
+			return;
+		}
+
+		match style.effect {
+			Effect::Plain => {},
+			Effect::Shadow(color, offset) => {
+				self.blit(screen, RenderKey { text: line.to_string(), size: style.size, color: color, outline: 0 }, x + offset, y + offset);
+			},
+			Effect::Outline(color, width) => {
+				self.blit(screen, RenderKey { text: line.to_string(), size: style.size, color: color, outline: width }, x - width, y - width);
+			}
+		}
+		self.blit(screen, RenderKey { text: line.to_string(), size: style.size, color: style.color, outline: 0 }, x, y);
+	}
+
+	/// Copies the line `key` describes to the screen, rendering it first
+	/// if it isn't cached. A line which can't be rendered is skipped.
+	#[allow(unused_must_use)]
+	fn blit(&mut self, screen: &mut render::Renderer, key: RenderKey, x: i32, y: i32) {
+		if !self.rendered.contains_key(&key) {
+			if self.rendered.len() >= MAX_CACHED {
+				self.rendered.clear();
+			}
+
+			let (red, green, blue) = key.color;
+			let surface = {
+				let font = match self.font(key.size) {
+					Some(font) => { font },
+					None => { return; }
+				};
+				font.set_outline_width(key.outline as isize);
+				let surface = font.render(&key.text, sdl2_ttf::blended(pixels::Color::RGBA(red, green, blue, 255)));
+				font.set_outline_width(0);
+				surface
+			};
+			let surface = match surface {
+				Ok(surface) => { surface },
+				Err(msg) => { println!("could not render \"{}\": {}", key.text, msg); return; }
+			};
+			let texture = match screen.create_texture_from_surface(&surface) {
+				Ok(texture) => { texture },
+				Err(msg) => { println!("could not render \"{}\": {}", key.text, msg); return; }
+			};
+			self.rendered.insert(key.clone(), texture);
+		}
+
+		let texture = self.rendered.get(&key).unwrap();
+		let query = texture.query();
+		match rect::Rect::new(x, y, query.width, query.height) {
+			Ok(Some(dest)) => { screen.copy(texture, None, Some(dest)); },
+			_ => {}
+		}
+	}
+}
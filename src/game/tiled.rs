@@ -0,0 +1,601 @@
+use std::fs::File;
+use std::io::{BufReader, Read};
+use std::path::Path;
+use std::string::String;
+use std::vec::Vec;
+
+use rustc_serialize::base64::FromBase64;
+use rustc_serialize::json::Json;
+use xml::common::Position;
+use xml::reader::{EventReader, XmlEvent};
+
+use game::map;
+use game::packs;
+use game::units;
+
+// bits Tiled sets on a gid to flip or rotate a tile
+static GID_FLAG_MASK: u32 = 0x0FFFFFFF;
+
+/// A tile sheet embedded in a Tiled map.
+struct Tileset {
+	first_gid:  u32,
+	columns:    u32,
+	image:      Option<String>,
+	walls:      Vec<u32>
+}
+
+/// A point on an object layer.
+struct Object {
+	id:    String,
+	class: String,
+	name:  String,
+	x:     f64,
+	y:     f64
+}
+
+/// Everything read from a Tiled map before it is turned into a `map::Map`.
+struct TiledMap {
+	cols:        u32,
+	rows:        u32,
+	tile_width:  u32,
+	tile_height: u32,
+	backdrop:    Option<String>,
+	tilesets:    Vec<Tileset>,
+	layers:      Vec<Vec<u32>>,
+	objects:     Vec<Object>
+}
+
+impl TiledMap {
+	fn new() -> TiledMap {
+		TiledMap {
+			cols:        0,
+			rows:        0,
+			tile_width:  0,
+			tile_height: 0,
+			backdrop:    None,
+			tilesets:    Vec::new(),
+			layers:      Vec::new(),
+			objects:     Vec::new()
+		}
+	}
+}
+
+/// Builds a map from a map made in the Tiled editor, saved either as XML
+/// (`.tmx`) or JSON (`.tmj`).
+///
+/// Tile layers are stacked into the tile grid: the top-most tile in each
+/// cell supplies the sprite, & the cell is a wall if any of its tiles has
+/// the custom property `tile_type` set to `wall`. Tilesets must be embedded
+/// in the map and use the game's tile size (or that of the pack the map is
+/// in). The edge of the map must be all wall.
+///
+/// Objects on object layers are spawn points. Their class (`type` before
+/// Tiled 1.9) is one of `player`, `vehicle`, `part`, `zombie`, `powerup` or
+/// `trap`; zombies, powerups & traps name their kind (e.g. `crazy`, `nuke`,
+/// `bear`) in the object's name. There must be a `player`, & nothing may
+/// spawn on a wall. The map property `backdrop` names the backdrop image.
+pub fn load(file_path: &str) -> Result<map::Map, String> {
+	let tiled = if file_path.ends_with(".tmx") {
+		try!(read_tmx(file_path))
+	} else if file_path.ends_with(".tmj") || file_path.ends_with(".json") {
+		try!(read_tmj(file_path))
+	} else {
+		return Err(format!("{}: not a Tiled map (.tmx or .tmj)", file_path));
+	};
+
+	build_map(file_path, tiled)
+}
+
+/// Turns the layers & objects of a Tiled map into a `map::Map`.
+fn build_map(file_path: &str, tiled: TiledMap) -> Result<map::Map, String> {
+	if tiled.cols < map::MIN_LEVEL_SIZE || tiled.rows < map::MIN_LEVEL_SIZE {
+		return Err(format!("{}: maps must be at least {}x{} tiles, found {}x{}",
+		                   file_path, map::MIN_LEVEL_SIZE, map::MIN_LEVEL_SIZE, tiled.cols, tiled.rows));
+	}
+	if tiled.tile_width == 0 || tiled.tile_height == 0 {
+		return Err(format!("{}: map has no tile size", file_path));
+	}
+	// the game's own tile size, or that of the pack the map comes from
+	let tile_sizes = [units::TILE_SIZE as u32, packs::tile_size(file_path)];
+	if !tile_sizes.contains(&tiled.tile_width) || tiled.tile_height != tiled.tile_width {
+		return Err(format!("{}: tiles must be {}x{} pixels, found {}x{}",
+		                   file_path, tile_sizes[1], tile_sizes[1], tiled.tile_width, tiled.tile_height));
+	}
+	if tiled.layers.len() == 0 {
+		return Err(format!("{}: map has no tile layers", file_path));
+	}
+
+	let backdrop = match tiled.backdrop {
+		Some(ref backdrop) => { try!(resolve_asset(file_path, backdrop)) },
+		None => { return Err(format!("{}: map has no `backdrop` property", file_path)); }
+	};
+
+	// index 0 is always plain air
+	let mut kinds: Vec<map::TileDef> = vec![map::TileDef { tile_type: map::TileType::Air, sprite: None }];
+	let mut kind_keys: Vec<(u32, bool)> = vec![(0, false)];
+	let mut grid: Vec<Vec<usize>> = Vec::new();
+	for row in 0.. tiled.rows {
+		let mut grid_row: Vec<usize> = Vec::new();
+		for col in 0.. tiled.cols {
+			let cell = (row * tiled.cols + col) as usize;
+			let mut top_gid = 0;
+			let mut is_wall = false;
+			for layer in tiled.layers.iter() {
+				let gid = layer[cell] & GID_FLAG_MASK;
+				if gid == 0 {
+					continue;
+				}
+				let (tileset, local_id) = try!(find_tileset(file_path, &tiled.tilesets, gid));
+				is_wall = is_wall || tileset.walls.contains(&local_id);
+				top_gid = gid;
+			}
+
+			let key = (top_gid, is_wall);
+			let kind = match kind_keys.iter().position(|known| *known == key) {
+				Some(kind) => { kind },
+				None => {
+					let sprite = if top_gid == 0 { None } else {
+						let (tileset, local_id) = try!(find_tileset(file_path, &tiled.tilesets, top_gid));
+						let image = match tileset.image {
+							Some(ref image) => { try!(resolve_asset(file_path, image)) },
+							None => { return Err(format!("{}: tileset starting at gid {} has no image", file_path, tileset.first_gid)); }
+						};
+						Some((image, (units::Tile(local_id % tileset.columns), units::Tile(local_id / tileset.columns))))
+					};
+					let tile_type = if is_wall { map::TileType::Wall } else { map::TileType::Air };
+					kinds.push(map::TileDef { tile_type: tile_type, sprite: sprite });
+					kind_keys.push(key);
+					kinds.len() - 1
+				}
+			};
+			grid_row.push(kind);
+		}
+		grid.push(grid_row);
+	}
+
+	let mut spawns = map::SpawnPoints::new();
+	for object in tiled.objects.iter() {
+		let col = (object.x / tiled.tile_width as f64).floor();
+		let row = (object.y / tiled.tile_height as f64).floor();
+		if col < 0.0 || row < 0.0 || col >= tiled.cols as f64 || row >= tiled.rows as f64 {
+			return Err(format!("{}: object {} is outside the map", file_path, object.id));
+		}
+		let position = (units::Tile(col as u32), units::Tile(row as u32));
+
+		match &object.class[..] {
+			"player"  => { spawns.player = Some(position); },
+			"vehicle" => { spawns.vehicle = Some(position); },
+			"part"    => { spawns.parts.push(position); },
+			"zombie"  => { spawns.zombies.push((object.name.clone(), position)); },
+			"powerup" => { spawns.powerups.push((object.name.clone(), position)); },
+			"trap"    => {
+				match map::trap_kind(&object.name) {
+					Some(kind) => { spawns.traps.push((kind, position)); },
+					None => { return Err(format!("{}: object {} is an unknown trap `{}`", file_path, object.id, object.name)); }
+				}
+			},
+			other => {
+				return Err(format!("{}: object {} has unknown class `{}`", file_path, object.id, other));
+			}
+		}
+	}
+
+	if spawns.player.is_none() {
+		return Err(format!("{}: map has no `player` object", file_path));
+	}
+
+	match map::Map::from_grid(backdrop, &kinds, &grid, spawns) {
+		Ok(map) => { Ok(map) },
+		Err(err) => { Err(format!("{}: {}", file_path, err.message())) }
+	}
+}
+
+/// Finds the tileset `gid` belongs to & the tile's id within it.
+fn find_tileset<'a>(file_path: &str, tilesets: &'a [Tileset], gid: u32) -> Result<(&'a Tileset, u32), String> {
+	let mut found: Option<&Tileset> = None;
+	for tileset in tilesets.iter() {
+		if tileset.first_gid <= gid && found.map_or(true, |best| tileset.first_gid > best.first_gid) {
+			found = Some(tileset);
+		}
+	}
+
+	match found {
+		Some(tileset) if tileset.columns > 0 => { Ok((tileset, gid - tileset.first_gid)) },
+		Some(tileset) => { Err(format!("{}: tileset starting at gid {} has no columns", file_path, tileset.first_gid)) },
+		None => { Err(format!("{}: tile {} belongs to no tileset", file_path, gid)) }
+	}
+}
+
+/// Resolves `asset` relative to the map which references it & checks that it exists.
+fn resolve_asset(file_path: &str, asset: &str) -> Result<String, String> {
+	let asset_path = match Path::new(file_path).parent() {
+		Some(dir) => { dir.join(asset) },
+		None      => { Path::new(asset).to_path_buf() }
+	};
+
+	match asset_path.to_str() {
+		Some(path) if File::open(packs::locate(path)).is_ok() => { Ok(path.to_string()) },
+		_ => { Err(format!("{}: missing asset `{}`", file_path, asset_path.display())) }
+	}
+}
+
+/// Decodes a layer's `data` as stored w/ `encoding` (`csv` or `base64`).
+fn decode_layer(encoding: &str, compression: &str, data: &str) -> Result<Vec<u32>, String> {
+	if compression != "" {
+		return Err(format!("compressed layers ({}) are not supported, save w/ CSV or uncompressed Base64", compression));
+	}
+
+	match encoding {
+		"csv" => {
+			let mut gids: Vec<u32> = Vec::new();
+			for field in data.split(',') {
+				match field.trim().parse::<u32>() {
+					Ok(gid) => { gids.push(gid); },
+					Err(_) => { return Err(format!("invalid tile `{}`", field.trim())); }
+				}
+			}
+			Ok(gids)
+		},
+		"base64" => {
+			let bytes = match data.trim().from_base64() {
+				Ok(bytes) => { bytes },
+				Err(msg) => { return Err(format!("invalid base64 layer: {}", msg)); }
+			};
+			if bytes.len() % 4 != 0 {
+				return Err(format!("base64 layer is {} bytes, not a whole number of tiles", bytes.len()));
+			}
+			Ok(bytes.chunks(4).map(|gid| {
+				(gid[0] as u32) | ((gid[1] as u32) << 8) | ((gid[2] as u32) << 16) | ((gid[3] as u32) << 24)
+			}).collect())
+		},
+		other => { Err(format!("unknown layer encoding `{}`", other)) }
+	}
+}
+
+/// Reads a Tiled XML map.
+///
+/// Errors name the line of the map they were found on.
+fn read_tmx(file_path: &str) -> Result<TiledMap, String> {
+	let file = match File::open(packs::locate(file_path)) {
+		Ok(file) => { file },
+		Err(msg) => { return Err(format!("could not open map {}: {}", file_path, msg)); }
+	};
+
+	let mut tiled = TiledMap::new();
+	let mut parser = EventReader::new(BufReader::new(file));
+	let mut elements: Vec<String> = Vec::new();
+
+	// the layer currently being read
+	let mut encoding = String::new();
+	let mut compression = String::new();
+	let mut layer: Vec<u32> = Vec::new();
+
+	loop {
+		let event = match parser.next() {
+			Ok(event) => { event },
+			Err(msg) => { return Err(format!("{}: {}", file_path, msg)); }
+		};
+		let line_num = parser.position().row + 1;
+
+		match event {
+			XmlEvent::StartElement { name, attributes, .. } => {
+				let element = name.local_name;
+				let mut context = element.clone();
+				{
+					let attribute = |key: &str| -> Option<String> {
+						attributes.iter().find(|attr| attr.name.local_name == key).map(|attr| attr.value.clone())
+					};
+					let number = |key: &str| -> Result<u32, String> {
+						match attribute(key).map(|value| value.parse::<u32>()) {
+							Some(Ok(value)) => { Ok(value) },
+							_ => { Err(format!("{}:{}: <{}> needs a numeric `{}`", file_path, line_num, element, key)) }
+						}
+					};
+					let parent = elements.last().cloned().unwrap_or(String::new());
+
+					match &element[..] {
+						"map" => {
+							tiled.cols = try!(number("width"));
+							tiled.rows = try!(number("height"));
+							tiled.tile_width = try!(number("tilewidth"));
+							tiled.tile_height = try!(number("tileheight"));
+							if attribute("orientation").map_or(false, |value| value != "orthogonal") {
+								return Err(format!("{}:{}: only orthogonal maps are supported", file_path, line_num));
+							}
+							if attribute("infinite").map_or(false, |value| value == "1") {
+								return Err(format!("{}:{}: infinite maps are not supported", file_path, line_num));
+							}
+						},
+						"tileset" => {
+							if attribute("source").is_some() {
+								return Err(format!("{}:{}: external tilesets are not supported, embed the tileset in the map", file_path, line_num));
+							}
+							tiled.tilesets.push(Tileset {
+								first_gid: try!(number("firstgid")),
+								columns:   try!(number("columns")),
+								image:     None,
+								walls:     Vec::new()
+							});
+						},
+						"image" if parent == "tileset" => {
+							tiled.tilesets.last_mut().unwrap().image = attribute("source");
+						},
+						"tile" if parent == "tileset" => {
+							// remember which tile the following properties describe
+							let id = try!(number("id"));
+							context = format!("tile {}", id);
+						},
+						"property" => {
+							let key = attribute("name").unwrap_or(String::new());
+							let value = attribute("value").unwrap_or(String::new());
+							let owner = if elements.len() >= 2 { elements[elements.len() - 2].clone() } else { String::new() };
+							if owner == "map" && key == "backdrop" {
+								tiled.backdrop = Some(value);
+							} else if owner.starts_with("tile ") && key == "tile_type" {
+								let id: u32 = owner[5..].parse().unwrap();
+								match &value[..] {
+									"wall" => { tiled.tilesets.last_mut().unwrap().walls.push(id); },
+									"air"  => {},
+									_ => { return Err(format!("{}:{}: unknown tile type `{}`", file_path, line_num, value)); }
+								}
+							} else if owner == "object" && key == "kind" {
+								tiled.objects.last_mut().unwrap().name = value;
+							}
+						},
+						"layer" => {
+							layer = Vec::new();
+						},
+						"data" => {
+							encoding = attribute("encoding").unwrap_or(String::new());
+							compression = attribute("compression").unwrap_or(String::new());
+							if encoding == "" {
+								// plain XML: one <tile gid=".."/> per cell
+								encoding = "xml".to_string();
+							}
+						},
+						"tile" if parent == "data" => {
+							layer.push(attribute("gid").and_then(|gid| gid.parse::<u32>().ok()).unwrap_or(0));
+						},
+						"chunk" => {
+							return Err(format!("{}:{}: infinite maps are not supported", file_path, line_num));
+						},
+						"object" => {
+							let coordinate = |key: &str| -> Result<f64, String> {
+								match attribute(key).map(|value| value.parse::<f64>()) {
+									Some(Ok(value)) => { Ok(value) },
+									_ => { Err(format!("{}:{}: <object> needs a numeric `{}`", file_path, line_num, key)) }
+								}
+							};
+							tiled.objects.push(Object {
+								id:    format!("{} (line {})", attribute("id").unwrap_or(String::new()), line_num),
+								class: attribute("class").or(attribute("type")).unwrap_or(String::new()),
+								name:  attribute("name").unwrap_or(String::new()),
+								x:     try!(coordinate("x")),
+								y:     try!(coordinate("y"))
+							});
+						},
+						_ => {}
+					}
+				}
+				elements.push(context);
+			},
+			XmlEvent::Characters(data) => {
+				if elements.last().map_or(false, |element| element == "data") {
+					layer = match decode_layer(&encoding, &compression, &data) {
+						Ok(gids) => { gids },
+						Err(msg) => { return Err(format!("{}:{}: {}", file_path, line_num, msg)); }
+					};
+				}
+			},
+			XmlEvent::EndElement { name } => {
+				elements.pop();
+				if name.local_name == "layer" {
+					if layer.len() != (tiled.cols * tiled.rows) as usize {
+						return Err(format!("{}:{}: layer has {} tiles, expected {}x{}",
+						                   file_path, line_num, layer.len(), tiled.cols, tiled.rows));
+					}
+					tiled.layers.push(layer.clone());
+				}
+			},
+			XmlEvent::EndDocument => { break; },
+			_ => {}
+		}
+	}
+
+	Ok(tiled)
+}
+
+/// Reads a Tiled JSON map.
+fn read_tmj(file_path: &str) -> Result<TiledMap, String> {
+	let mut file = match File::open(packs::locate(file_path)) {
+		Ok(file) => { file },
+		Err(msg) => { return Err(format!("could not open map {}: {}", file_path, msg)); }
+	};
+	let mut contents = String::new();
+	match file.read_to_string(&mut contents) {
+		Ok(_) => {},
+		Err(msg) => { return Err(format!("could not read map {}: {}", file_path, msg)); }
+	};
+	// syntax errors carry their own line & column
+	let json = match Json::from_str(&contents) {
+		Ok(json) => { json },
+		Err(msg) => { return Err(format!("{}: {}", file_path, msg)); }
+	};
+
+	let number = |object: &Json, key: &str, owner: &str| -> Result<u32, String> {
+		match object.find(key).and_then(|value| value.as_u64()) {
+			Some(value) => { Ok(value as u32) },
+			None => { Err(format!("{}: {} needs a numeric `{}`", file_path, owner, key)) }
+		}
+	};
+	let string = |object: &Json, key: &str| -> String {
+		object.find(key).and_then(|value| value.as_string()).unwrap_or("").to_string()
+	};
+	let property = |object: &Json, key: &str| -> Option<String> {
+		object.find("properties").and_then(|properties| properties.as_array()).and_then(|properties| {
+			properties.iter()
+			          .find(|property| property.find("name").and_then(|name| name.as_string()) == Some(key))
+			          .and_then(|property| property.find("value").and_then(|value| value.as_string()))
+			          .map(|value| value.to_string())
+		})
+	};
+
+	let mut tiled = TiledMap::new();
+	tiled.cols = try!(number(&json, "width", "map"));
+	tiled.rows = try!(number(&json, "height", "map"));
+	tiled.tile_width = try!(number(&json, "tilewidth", "map"));
+	tiled.tile_height = try!(number(&json, "tileheight", "map"));
+	if string(&json, "orientation") != "orthogonal" {
+		return Err(format!("{}: only orthogonal maps are supported", file_path));
+	}
+	if json.find("infinite").and_then(|value| value.as_boolean()) == Some(true) {
+		return Err(format!("{}: infinite maps are not supported", file_path));
+	}
+	tiled.backdrop = property(&json, "backdrop");
+
+	let empty: Vec<Json> = Vec::new();
+	for (index, tileset) in json.find("tilesets").and_then(|sets| sets.as_array()).unwrap_or(&empty).iter().enumerate() {
+		let owner = format!("tileset {}", index);
+		if tileset.find("source").is_some() {
+			return Err(format!("{}: {} is external, embed the tileset in the map", file_path, owner));
+		}
+		let mut walls: Vec<u32> = Vec::new();
+		for tile in tileset.find("tiles").and_then(|tiles| tiles.as_array()).unwrap_or(&empty).iter() {
+			let id = try!(number(tile, "id", &owner));
+			match property(tile, "tile_type") {
+				Some(ref value) if value == "wall" => { walls.push(id); },
+				Some(ref value) if value == "air"  => {},
+				Some(value) => { return Err(format!("{}: tile {} of {} has unknown tile type `{}`", file_path, id, owner, value)); },
+				None => {}
+			}
+		}
+		tiled.tilesets.push(Tileset {
+			first_gid: try!(number(tileset, "firstgid", &owner)),
+			columns:   try!(number(tileset, "columns", &owner)),
+			image:     tileset.find("image").and_then(|image| image.as_string()).map(|image| image.to_string()),
+			walls:     walls
+		});
+	}
+
+	// group layers nest further layers, so walk them depth first
+	let mut pending: Vec<&Json> = json.find("layers").and_then(|layers| layers.as_array()).unwrap_or(&empty).iter().rev().collect();
+	while let Some(layer) = pending.pop() {
+		let owner = format!("layer `{}`", string(layer, "name"));
+		match &string(layer, "type")[..] {
+			"tilelayer" => {
+				let gids = match layer.find("data") {
+					Some(&Json::Array(ref data)) => {
+						let mut gids: Vec<u32> = Vec::new();
+						for gid in data.iter() {
+							match gid.as_u64() {
+								Some(gid) => { gids.push(gid as u32); },
+								None => { return Err(format!("{}: {} has a tile which is not a number", file_path, owner)); }
+							}
+						}
+						gids
+					},
+					Some(&Json::String(ref data)) => {
+						match decode_layer(&string(layer, "encoding"), &string(layer, "compression"), data) {
+							Ok(gids) => { gids },
+							Err(msg) => { return Err(format!("{}: {}: {}", file_path, owner, msg)); }
+						}
+					},
+					_ => { return Err(format!("{}: {} has no tile data", file_path, owner)); }
+				};
+				if gids.len() != (tiled.cols * tiled.rows) as usize {
+					return Err(format!("{}: {} has {} tiles, expected {}x{}",
+					                   file_path, owner, gids.len(), tiled.cols, tiled.rows));
+				}
+				tiled.layers.push(gids);
+			},
+			"objectgroup" => {
+				for object in layer.find("objects").and_then(|objects| objects.as_array()).unwrap_or(&empty).iter() {
+					let id = object.find("id").and_then(|id| id.as_u64()).unwrap_or(0);
+					let coordinate = |key: &str| -> Result<f64, String> {
+						match object.find(key).and_then(|value| value.as_f64()) {
+							Some(value) => { Ok(value) },
+							None => { Err(format!("{}: object {} needs a numeric `{}`", file_path, id, key)) }
+						}
+					};
+					let class = match string(object, "class") {
+						ref class if class.len() > 0 => { class.clone() },
+						_ => { string(object, "type") }
+					};
+					tiled.objects.push(Object {
+						id:    format!("{} ({})", id, owner),
+						class: class,
+						name:  property(object, "kind").unwrap_or(string(object, "name")),
+						x:     try!(coordinate("x")),
+						y:     try!(coordinate("y"))
+					});
+				}
+			},
+			"group" => {
+				for nested in layer.find("layers").and_then(|layers| layers.as_array()).unwrap_or(&empty).iter().rev() {
+					pending.push(nested);
+				}
+			},
+			_ => {}
+		}
+	}
+
+	Ok(tiled)
+}
+
+#[cfg(test)]
+mod tests {
+	use std::env;
+	use std::fs;
+	use std::fs::File;
+	use std::io::Write;
+
+	use super::{build_map, read_tmx};
+	use game::units;
+
+	static WALLED_TMX: &'static str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<map version="1.10" orientation="orthogonal" width="4" height="3" tilewidth="32" tileheight="32" infinite="0">
+ <properties>
+  <property name="backdrop" value="../base/bkBlue.bmp"/>
+ </properties>
+ <tileset firstgid="1" name="cave" tilewidth="32" tileheight="32" columns="16">
+  <image source="../base/Stage/PrtCave.bmp" width="512" height="512"/>
+  <tile id="1">
+   <properties>
+    <property name="tile_type" value="wall"/>
+   </properties>
+  </tile>
+ </tileset>
+ <layer id="1" name="tiles" width="4" height="3">
+  <data encoding="csv">
+2,2,2,2,
+2,0,1,2,
+2,2,2,2
+</data>
+ </layer>
+ <objectgroup id="2" name="spawns">
+  <object id="1" type="player" x="40" y="40"/>
+ </objectgroup>
+</map>
+"#;
+
+	#[test]
+	fn wall_property_makes_walls() {
+		let path = env::temp_dir().join("just-run-walled.tmx");
+		File::create(&path).unwrap().write_all(WALLED_TMX.as_bytes()).unwrap();
+		let tiled = read_tmx(path.to_str().unwrap());
+		fs::remove_file(&path).unwrap();
+		let tiled = tiled.ok().unwrap();
+		assert!(tiled.tilesets[0].walls == vec![1]);
+
+		// assets are looked up next to the map, so build it as if it were among the levels
+		let map = build_map("assets/maps/walled.tmx", tiled).ok().unwrap();
+		for col in 0.. 4 {
+			assert!(map.is_wall(units::Tile(col), units::Tile(0)));
+			assert!(map.is_wall(units::Tile(col), units::Tile(2)));
+		}
+		assert!(!map.is_wall(units::Tile(1), units::Tile(1)));
+		// a tile w/o the property is drawn but stays open
+		assert!(!map.is_wall(units::Tile(2), units::Tile(1)));
+		assert!(map.spawns.player == Some((units::Tile(1), units::Tile(1))));
+	}
+}
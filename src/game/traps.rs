@@ -1,7 +1,7 @@
 use game::collisions::Rectangle;
 use game::sprite;
+use game::camera;
 use game::graphics;
-use game::Game;
 use game::map;
 
 use game::units;
@@ -9,92 +9,203 @@ use game::units;
 use game::common;
 use game::common::Character;
 
-type MotionTup = (sprite::Motion, sprite::Facing);
+static TRAP_SHEET: &'static str = "assets/base/traps.bmp";
 
-static SPRITE_NUM_FRAMES:  units::Frame  = 2;
-static SPRITE_FPS:         units::Fps    = 20;
+/// Updates a trap the player put down leaves them to get clear of it.
+pub static PLACED_ARMING_TIME: i32 = 60;
 
-// Sprite locations
-const BEAR_TRAP_FRAME: units::Tile = units::Tile(0);
+/// What springing a trap does to whoever sprang it.
+#[derive(PartialEq,Clone,Copy)]
+pub enum Effect {
+	/// Hurts the player (unless they are immune) or kills a zombie.
+	Damage,
+	/// Caps the speed of whoever stands on it to the given fraction.
+	Slow(f64),
+	/// Draws the zombies within `radius` of the trap to it for `updates`.
+	Noise(units::Game, i32)
+}
+
+/// The rules one kind of trap plays by. A trap's kind (see `get_type()`)
+/// is its index in `KINDS`, plus one.
+pub struct TrapKind {
+	pub name:           &'static str,
+
+	// columns in `TRAP_SHEET`
+	pub armed_frame:    units::Tile,
+	pub sprung_frame:   units::Tile,
 
-// Animation frames 
-const BEAR_TRAP_CLOSED_FRAME: units::Tile = units::Tile(1);
+	pub effect:         Effect,
+	pub player_trigger: bool,
+	pub zombie_trigger: bool,
+	/// Springs once for every zombie on it, rather than for just the first.
+	pub catches_all:    bool,
+	/// Is removed once sprung, rather than staying on the level.
+	pub consumed:       bool,
+	/// Updates a trap which stays must wait before it can be sprung again.
+	pub rearm:          i32,
+	pub sound:          Option<&'static str>,
+	pub message:        Option<&'static str>
+}
+
+pub static KINDS: [TrapKind; 6] = [
+	// snaps shut on the first thing to step in it
+	TrapKind {
+		name: "bear", armed_frame: units::Tile(0), sprung_frame: units::Tile(1),
+		effect: Effect::Damage, player_trigger: true, zombie_trigger: true,
+		catches_all: false, consumed: true, rearm: 0,
+		sound: Some("trap"), message: Some("BEAR TRAP")
+	},
+	// stays out, cutting down every zombie (& the player) that crosses it
+	TrapKind {
+		name: "spikes", armed_frame: units::Tile(2), sprung_frame: units::Tile(2),
+		effect: Effect::Damage, player_trigger: true, zombie_trigger: true,
+		catches_all: true, consumed: false, rearm: 0,
+		sound: Some("trap"), message: Some("SPIKE STRIP")
+	},
+	// quietly bogs down anything wading through it
+	TrapKind {
+		name: "tar", armed_frame: units::Tile(3), sprung_frame: units::Tile(3),
+		effect: Effect::Slow(0.4), player_trigger: true, zombie_trigger: true,
+		catches_all: true, consumed: false, rearm: 0,
+		sound: None, message: None
+	},
+	// zombies step over it, the player sets off a racket
+	TrapKind {
+		name: "tripwire", armed_frame: units::Tile(4), sprung_frame: units::Tile(5),
+		effect: Effect::Noise(units::Game(256.0), 240), player_trigger: true, zombie_trigger: false,
+		catches_all: false, consumed: true, rearm: 0,
+		sound: Some("trap"), message: Some("TRIPWIRE")
+	},
+	// too light to catch the player, resets itself after each zombie
+	TrapKind {
+		name: "snare", armed_frame: units::Tile(6), sprung_frame: units::Tile(7),
+		effect: Effect::Damage, player_trigger: false, zombie_trigger: true,
+		catches_all: false, consumed: false, rearm: 180,
+		sound: Some("trap"), message: Some("SNARE")
+	},
+	// blows up under everything standing on it
+	TrapKind {
+		name: "mine", armed_frame: units::Tile(8), sprung_frame: units::Tile(9),
+		effect: Effect::Damage, player_trigger: true, zombie_trigger: true,
+		catches_all: true, consumed: true, rearm: 0,
+		sound: Some("wipeout"), message: Some("MINE")
+	}
+];
+
+/// The kind of trap called `name`.
+pub fn find(name: &str) -> Option<u32> {
+	KINDS.iter().position(|kind| kind.name == name).map(|index| index as u32 + 1)
+}
+
+/// The rules for traps of `kind`.
+pub fn rules(kind: u32) -> Option<&'static TrapKind> {
+	match kind {
+		0 => None,
+		_ => KINDS.get(kind as usize - 1)
+	}
+}
+
+/// A racket which draws nearby zombies to where it was made.
+#[derive(Clone,Copy)]
+pub struct Noise {
+	pub x:       units::Game,
+	pub y:       units::Game,
+	pub radius:  units::Game,
+	pub updates: i32
+}
+
+impl Noise {
+	/// Whether something at (`x`, `y`) is close enough to hear it.
+	pub fn heard_at(&self, x: units::Game, y: units::Game) -> bool {
+		let units::Game(xs) = (x - self.x) * (x - self.x);
+		let units::Game(ys) = (y - self.y) * (y - self.y);
+		let units::Game(radius) = self.radius;
+
+		(xs + ys).sqrt() < radius
+	}
+}
 
 pub trait Trap {
-	fn draw(&self, display: &mut graphics::Graphics);
+	fn draw(&self, display: &mut graphics::Graphics, camera: &camera::Camera);
 	fn update(&mut self, elapsed_time: units::Millis, map: &map::Map);
 	fn damage_rectangle(&self) -> Rectangle;
 	fn get_type(&self) -> i32;
+	fn rules(&self) -> &'static TrapKind;
+	fn is_armed(&self) -> bool;
+	fn triggers_for_player(&self) -> bool;
+	fn spring(&mut self);
+	fn timers(&self) -> (i32, i32);
+	fn restore_timers(&mut self, timers: (i32, i32));
 	fn is_finished(&mut self) -> bool;
 	fn set_timer(&mut self);
 	fn get_map_x(&self) -> units::Game;
 	fn get_map_y(&self) -> units::Game;
 }
 
-pub struct BearTrap {
-	character: Character, 
-	animation_sprite: Vec<Box<sprite::Updatable<units::Game>>>, 
-	animation_timer: i32
+/// A trap whose looks & triggers all come from its `TrapKind`.
+pub struct DefinedTrap {
+	character: Character,
+	kind: u32,
+	rules: &'static TrapKind,
+	sprung_sprite: Box<sprite::Updatable<units::Game>>,
+	animation_timer: i32,
+	rearm_timer: i32,
+	arming_timer: i32
 }
 
-impl BearTrap {
-	pub fn new(graphics: &mut graphics::Graphics,
-	           x: units::Game, y: units::Game) -> BearTrap {
-
-		let animation: Vec<Box<sprite::Updatable<units::Game>>> = Vec::new();
+impl DefinedTrap {
+	/// Panics if `kind` isn't listed in `KINDS`.
+	pub fn new(kind: u32, x: units::Game, y: units::Game) -> DefinedTrap {
+		let rules = rules(kind).unwrap();
+		let sprung_sprite = Box::new( sprite::Sprite::new(
+			(rules.sprung_frame, units::Tile(0)),
+			(units::Tile(1), units::Tile(1)),
+			TRAP_SHEET.to_string()
+		) ) as Box<sprite::Updatable<_>>;
 
-		let mut new_trap = BearTrap { 
-			character: common::Character::new(x, y), 
-			animation_sprite: animation,
-			animation_timer: 0
+		let mut new_trap = DefinedTrap {
+			character: common::Character::new(x, y),
+			kind: kind,
+			rules: rules,
+			sprung_sprite: sprung_sprite,
+			animation_timer: 0,
+			rearm_timer: 0,
+			arming_timer: 0
 		};
 
 		for motion in sprite::MOTIONS.iter() {
 			for facing in sprite::FACINGS.iter() {
-				new_trap.load_sprite(graphics, (*motion, *facing));
+				new_trap.load_sprite((*motion, *facing));
 			}
 		}
 
 		new_trap
 	}
 
-	pub fn load_sprite(&mut self, 
-	               display: &mut graphics::Graphics,
-	               movement: (sprite::Motion, sprite::Facing)) {
-
-		let asset_path = "assets/base/traps.bmp".to_string();
-		let motion_frame = BEAR_TRAP_FRAME;
+	/// A trap the player put down, which spares them while they get clear.
+	pub fn placed(kind: u32, x: units::Game, y: units::Game) -> DefinedTrap {
+		let mut new_trap = DefinedTrap::new(kind, x, y);
+		new_trap.arming_timer = PLACED_ARMING_TIME;
 
-		let facing_frame = units::Tile(0);
+		new_trap
+	}
 
+	pub fn load_sprite(&mut self, movement: (sprite::Motion, sprite::Facing)) {
 		let loaded_sprite = Box::new( sprite::Sprite::new(
-			display,
-			(motion_frame, facing_frame),
+			(self.rules.armed_frame, units::Tile(0)),
 			(units::Tile(1), units::Tile(1)),
-			asset_path
+			TRAP_SHEET.to_string()
 		) ) as Box<sprite::Updatable<_>>;
 
 		self.character.sprites.insert(movement, loaded_sprite);
-
-		let asset_path = "assets/base/traps.bmp".to_string();
-		let motion_frame = BEAR_TRAP_CLOSED_FRAME;
-		let facing_frame = units::Tile(0);
-		let animation_sprite = Box::new( sprite::Sprite::new(
-			display, 
-			(motion_frame, facing_frame),
-			(units::Tile(1), units::Tile(1)),
-			asset_path,
-		) ) as Box<sprite::Updatable<_>>;
-		self.animation_sprite.push(animation_sprite);
 	}
 }
 
-impl Trap for BearTrap {
-	fn draw(&self, display: &mut graphics::Graphics) {
-		let correction_x = self.character.get_map_x() % common::SCREEN_CORRECTION;
-		let correction_y = self.character.get_map_y() % common::SCREEN_CORRECTION;
-		if self.animation_timer > 0 {
-			self.animation_sprite.get(0).unwrap().draw(display, (correction_x, correction_y));
+impl Trap for DefinedTrap {
+	fn draw(&self, display: &mut graphics::Graphics, camera: &camera::Camera) {
+		let (correction_x, correction_y) = camera.to_screen(self.character.get_map_x(), self.character.get_map_y());
+		if self.animation_timer > 0 || self.rearm_timer > 0 {
+			self.sprung_sprite.draw(display, (correction_x, correction_y));
 		} else {
 			self.character.sprites.get(&self.character.movement).unwrap().draw(display, (correction_x, correction_y));
 		}
@@ -102,7 +213,12 @@ impl Trap for BearTrap {
 
 	#[allow(unused_variable)]
 	fn update(&mut self, elapsed_time: units::Millis, map: &map::Map) {
-		;
+		if self.rearm_timer > 0 {
+			self.rearm_timer = self.rearm_timer - 1;
+		}
+		if self.arming_timer > 0 {
+			self.arming_timer = self.arming_timer - 1;
+		}
 	}
 
 	fn damage_rectangle(&self) -> Rectangle {
@@ -110,7 +226,35 @@ impl Trap for BearTrap {
 	}
 
 	fn get_type(&self) -> i32 {
-		1
+		self.kind as i32
+	}
+
+	fn rules(&self) -> &'static TrapKind {
+		self.rules
+	}
+
+	fn is_armed(&self) -> bool {
+		self.rearm_timer == 0
+	}
+
+	fn triggers_for_player(&self) -> bool {
+		self.rules.player_trigger && self.arming_timer == 0
+	}
+
+	/// Shuts a trap which re-arms until it is ready again.
+	fn spring(&mut self) {
+		self.rearm_timer = self.rules.rearm;
+	}
+
+	/// Updates left until it re-arms & until the player can set it off.
+	fn timers(&self) -> (i32, i32) {
+		(self.rearm_timer, self.arming_timer)
+	}
+
+	fn restore_timers(&mut self, timers: (i32, i32)) {
+		let (rearm_timer, arming_timer) = timers;
+		self.rearm_timer = rearm_timer;
+		self.arming_timer = arming_timer;
 	}
 
 	fn is_finished(&mut self) -> bool {
@@ -131,4 +275,4 @@ impl Trap for BearTrap {
 	fn get_map_y(&self) -> units::Game {
 		self.character.map_center_y()
 	}
-}
\ No newline at end of file
+}
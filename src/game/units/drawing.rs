@@ -1,6 +1,7 @@
 use std::ops::{Add, Sub, Mul, Div, Rem};
 
-static TILE_SIZE: i32          =  32;
+/// How many `Game` units (& pixels of the base art) wide a tile is.
+pub static TILE_SIZE: i32      =  32;
 static SCALE: f64              = 1.0;
 
 pub trait AsGame  { fn to_game(&self)  -> Game;  }
@@ -1,6 +1,7 @@
 // Re-export the sub-libraries under the `units::` namespace
 pub use game::units::drawing::{AsGame,AsTile,AsPixel};
 pub use game::units::drawing::{Game,Tile,HalfTile,Pixel};
+pub use game::units::drawing::TILE_SIZE;
 
 pub use game::units::physics::{min,max};
 pub use game::units::physics::{Millis,Velocity,Acceleration};
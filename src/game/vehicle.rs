@@ -1,4 +1,5 @@
 use game::collisions::Rectangle;
+use game::camera;
 use game::graphics;
 
 use game::units;
@@ -7,8 +8,9 @@ pub trait Vehicle {
 	fn damage_rectangle(&self) -> Rectangle;
 	fn update(&mut self, elapsed_time: units::Millis);
 	fn update_for_cinematic(&mut self);
-	fn draw(&self, display: &mut graphics::Graphics);
+	fn draw(&self, display: &mut graphics::Graphics, camera: &camera::Camera);
 	fn add_part(&mut self, part_num: u32);
+	fn parts(&self) -> Vec<u32>;
 	fn is_built(&self) -> bool;
 	fn get_x(&self) -> units::Game;
 	fn get_y(&self) -> units::Game;
@@ -18,11 +20,9 @@ pub trait Vehicle {
 }
 
 pub trait Part {
-	fn draw(&self, display: &mut graphics::Graphics);
+	fn draw(&self, display: &mut graphics::Graphics, camera: &camera::Camera);
 	fn damage_rectangle(&self) -> Rectangle;
 	fn part_type(&self) -> u32;
-	fn get_x(&self) -> units::Game;
-	fn get_y(&self) -> units::Game;
 	fn get_map_x(&self) -> units::Game;
 	fn get_map_y(&self) -> units::Game;
 }
\ No newline at end of file
@@ -0,0 +1,1342 @@
+use std::cmp;
+use std::collections::HashMap;
+use std::mem;
+use std::vec::Vec;
+use rand::Rng;
+
+use game;
+
+pub use game::units::{AsGame,AsTile};
+
+pub use game::units;
+pub use game::camera;
+pub use game::enemies;
+pub use game::powerups;
+pub use game::traps;
+pub use game::map;
+pub use game::vehicle;
+pub use game::car;
+pub use game::heli;
+pub use game::player;
+pub use game::random;
+pub use game::spatial;
+pub use game::collisions;
+pub use game::save;
+pub use game::settings;
+
+// how levels are stocked on normal difficulty (see `settings::Difficulty`);
+// the zombies are for the first level on a `LEVEL_WIDTH` by `LEVEL_HEIGHT`
+// map, & the horde grows w/ the map & the level (see `World::horde_range`)
+pub static MAX_ENEMIES:              u32 = 30;
+// how much bigger the horde gets w/ each level
+pub static HORDE_GROWTH:             f64 = 0.5;
+pub static MAX_POWERUPS:             u32 = 20;
+pub static MAX_TRAPS:                u32 = 5;
+
+// how far, in `units::Game`, random traps are kept from the player
+pub static TRAP_CLEARANCE:           f64 = 96.0;
+
+// how often, in updates, a zombie closing in off screen groans & how
+// close it has to be
+pub static GROAN_EVERY:              i32 = 90;
+pub static GROAN_RANGE:      units::Tile = units::Tile(16);
+
+// the kinds of zombies & powerups
+pub static ZOMBIE_CONFIG:   &'static str = "assets/zombies.cfg";
+pub static POWERUP_CONFIG:  &'static str = "assets/powerups.cfg";
+
+pub static LEVEL_PARTS:                 u32 = 3;
+pub static LEVEL_1_TIME:                 i32 = 5000;
+pub static LEVEL_1_CINEMATIC_FRAMES:     i32 = 300;
+pub static LEVEL_2_TIME:                 i32 = 2000;
+pub static LEVEL_2_CINEMATIC_FRAMES:     i32 = 300;
+
+pub static PLAYER_STARTING_X: units::Tile = units::Tile(1);
+pub static PLAYER_STARTING_Y: units::Tile = units::Tile(4);
+
+pub static VEHICLE_STARTING_X: units::Tile = units::Tile(1);
+pub static VEHICLE_STARTING_Y: units::Tile = units::Tile(1);
+
+/// Something which happened during an update that a front-end
+/// (the windowed `Game`, or nothing at all when headless) may react to.
+pub enum Event {
+	/// Play the sound effect called this in the sound manifest, from
+	/// where it happened on the map, or w/o a place if it's the player's own.
+	Sound(String, Option<(units::Game, units::Game)>),
+	/// A level was just set up; whatever the last one was drawn w/ &
+	/// nothing uses any more can be let go of.
+	LevelStarted
+}
+
+/// What a powerup does when picked up, given the parameters its
+/// definition lists. Responds false if it had nothing to act on.
+pub type Effect = fn(&mut World, &powerups::Params) -> bool;
+
+/// The simulation state of a `just-run` game.
+///
+/// A world owns every actor & the map, but no window, audio or textures:
+/// it can be stepped w/o ever touching the SDL context.
+pub struct World {
+	pub player:     player::Player,
+	pub enemies:    Vec<Box<enemies::Zombie>>,
+	pub powerups:   Vec<Box<powerups::Powerup>>,
+	pub traps:      Vec<Box<traps::Trap>>,
+	pub killed:     Vec<Box<enemies::Zombie>>,
+	pub activated:  Vec<Box<powerups::Powerup>>,
+	pub tripped:    Vec<Box<traps::Trap>>,
+	pub parts:      Vec<Box<vehicle::Part>>,
+	pub coll_parts: Vec<Box<vehicle::Part>>,
+	pub vehicle:    Box<vehicle::Vehicle>,
+	pub map:        map::Map,
+	pub archetypes: enemies::Archetypes,
+	pub powerup_defs: powerups::PowerupDefs,
+
+	// where everything is: built by `index_entities()`, then kept up to
+	// date as things spawn & are removed, w/ the zombies refiled each update
+	pub enemy_grid:   spatial::SpatialHash,
+	pub powerup_grid: spatial::SpatialHash,
+	pub trap_grid:    spatial::SpatialHash,
+	pub part_grid:    spatial::SpatialHash,
+
+	pub updates:        i32,
+	pub level:          i32,
+	pub score:          i32,
+	pub timer:          i32,
+	pub completed_lvl:  bool,
+	pub game_over:      bool,
+	pub freeze_counter: i32,
+	pub noise:          Option<traps::Noise>,
+	pub seed:           u32,
+	pub camera_mode:    camera::CameraMode,
+	pub difficulty:     settings::Difficulty,
+
+	rng:     random::GameRng,
+	events:  Vec<Event>,
+	effects: HashMap<String, Effect>
+}
+
+impl World {
+	/// Builds the first level and populates it w/ zombies, powerups & vehicle parts.
+	/// Every random choice made by this world is drawn from `seed`. Fails
+	/// if the kinds of zombies or powerups can't be loaded.
+	pub fn new(seed: u32) -> Result<World, String> {
+		World::with_difficulty(seed, settings::Difficulty::Normal)
+	}
+
+	/// Like `new()`, but stocks the levels for `difficulty`.
+	pub fn with_difficulty(seed: u32, difficulty: settings::Difficulty) -> Result<World, String> {
+		let mut rng = random::new_rng(seed);
+		let map = map::Map::load_level(1 as i32, &mut rng);
+		let (player_x, player_y) = start_position(map.spawns.player, (PLAYER_STARTING_X, PLAYER_STARTING_Y));
+		let (vehicle_x, vehicle_y) = start_position(map.spawns.vehicle, (VEHICLE_STARTING_X, VEHICLE_STARTING_Y));
+		let vehicle = Box::new( heli::Helicopter::new(vehicle_x, vehicle_y) ) as Box<vehicle::Vehicle>;
+
+		let mut world = try!(World::build(seed, difficulty, rng, map, player::Player::new(player_x, player_y), vehicle));
+		world.populate();
+		world.snap_camera();
+
+		Ok(world)
+	}
+
+	/// A world on `map` w/ just the player & the vehicle, before anything
+	/// else is placed, on the first level & at the start of its timer.
+	fn build(
+		seed:       u32,
+		difficulty: settings::Difficulty,
+		rng:        random::GameRng,
+		map:        map::Map,
+		player:     player::Player,
+		vehicle:    Box<vehicle::Vehicle>
+	) -> Result<World, String> {
+		let archetypes = match enemies::Archetypes::load(ZOMBIE_CONFIG) {
+			Ok(archetypes) => { archetypes },
+			Err(msg) => { return Err(format!("could not load zombies: {}", msg)); }
+		};
+		let powerup_defs = match powerups::PowerupDefs::load(POWERUP_CONFIG) {
+			Ok(powerup_defs) => { powerup_defs },
+			Err(msg) => { return Err(format!("could not load powerups: {}", msg)); }
+		};
+		let enemies_vector: Vec<Box<enemies::Zombie>> = Vec::new();
+		let powerups_vector: Vec<Box<powerups::Powerup>> = Vec::new();
+		let traps_vector: Vec<Box<traps::Trap>> = Vec::new();
+		let killed_vector: Vec<Box<enemies::Zombie>> = Vec::new();
+		let activated_vector: Vec<Box<powerups::Powerup>> = Vec::new();
+		let tripped_vector: Vec<Box<traps::Trap>> = Vec::new();
+		let part_vector: Vec<Box<vehicle::Part>> = Vec::new();
+		let coll_part_vector: Vec<Box<vehicle::Part>> = Vec::new();
+
+		let mut world = World {
+			map: map,
+			archetypes: archetypes,
+			powerup_defs: powerup_defs,
+			player: player,
+
+			enemies: enemies_vector,
+			powerups: powerups_vector,
+			traps: traps_vector,
+			killed: killed_vector,
+			activated: activated_vector,
+			tripped: tripped_vector,
+			parts: part_vector,
+			coll_parts: coll_part_vector,
+
+			vehicle: vehicle,
+
+			enemy_grid:   spatial::SpatialHash::new(),
+			powerup_grid: spatial::SpatialHash::new(),
+			trap_grid:    spatial::SpatialHash::new(),
+			part_grid:    spatial::SpatialHash::new(),
+
+			updates:        0,
+			level:          1,
+			score:          0,
+			timer:          difficulty.level_time(),
+			completed_lvl:  false,
+			game_over:      false,
+			freeze_counter: 0,
+			noise:          None,
+			seed:           seed,
+			camera_mode:    camera::CameraMode::Follow,
+			difficulty:     difficulty,
+
+			rng:            rng,
+			events:         Vec::new(),
+			effects:        HashMap::new()
+		};
+		world.register_effect("bat", bat_effect);
+		world.register_effect("kill_zombie", kill_zombie_effect);
+		world.register_effect("wipe_out", wipe_out_effect);
+		world.register_effect("freeze", freeze_effect);
+		world.register_effect("teleport", teleport_effect);
+		world.register_effect("nuke", nuke_effect);
+		world.register_effect("convert", convert_effect);
+		world.register_effect("trap_kit", trap_kit_effect);
+
+		Ok(world)
+	}
+
+	/// Rebuilds the game `saved` was taken from. Zombies, powerups & traps
+	/// of kinds which are no longer defined are left out.
+	///
+	/// The levels are stocked for the difficulty the game was saved on.
+	/// The random generator isn't part of a save: a resumed game draws from
+	/// one seeded by the save's seed & update count instead. Fails if the
+	/// saved map isn't one which could be played on.
+	pub fn resume(saved: &save::SaveGame) -> Result<World, String> {
+		let rng = random::new_rng(saved.seed ^ saved.updates as u32);
+		let map = match map::Map::from_grid(saved.backdrop.clone(), &saved.kinds, &saved.grid, map::SpawnPoints::new()) {
+			Ok(map) => { map },
+			Err(err) => { return Err(format!("saved map is unplayable: {}", err.message())); }
+		};
+		let mut player = player::Player::new(saved.player.character.map_x, saved.player.character.map_y);
+		player.restore_state(&saved.player);
+		let vehicle = match saved.vehicle_type {
+			1 => { Box::new( heli::Helicopter::new(saved.vehicle_x, saved.vehicle_y) ) as Box<vehicle::Vehicle> },
+			_ => { Box::new( car::Car::new(saved.vehicle_x, saved.vehicle_y) ) as Box<vehicle::Vehicle> }
+		};
+
+		let mut world = try!(World::build(saved.seed, saved.difficulty, rng, map, player, vehicle));
+		world.level = saved.level;
+		world.score = saved.score;
+		world.timer = saved.timer;
+		world.updates = saved.updates;
+		world.freeze_counter = saved.freeze_counter;
+
+		for part in saved.vehicle_parts.iter() {
+			world.vehicle.add_part(*part);
+		}
+		let parts: Vec<Box<vehicle::Part>> = saved.parts.iter().map(|part| world.new_part(part.part_type.saturating_sub(1), part.x, part.y)).collect();
+		let coll_parts: Vec<Box<vehicle::Part>> = saved.collected.iter().map(|part| world.new_part(part.saturating_sub(1), units::Game(0.0), units::Game(0.0))).collect();
+		world.parts = parts;
+		world.coll_parts = coll_parts;
+
+		for zombie in saved.zombies.iter() {
+			match world.archetypes.find(&zombie.kind) {
+				Some(kind) => {
+					let archetype = world.archetypes.get(kind).unwrap().clone();
+					let mut enemy = Box::new( enemies::ArchetypeZombie::new(kind, archetype, zombie.state.map_x, zombie.state.map_y) ) as Box<enemies::Zombie>;
+					enemy.restore_state(&zombie.state, zombie.chasing);
+					world.enemies.push(enemy);
+				},
+				None => { println!("saved game has an unknown zombie `{}`, skipping it", zombie.kind); }
+			}
+		}
+		for saved_powerup in saved.powerups.iter() {
+			match world.powerup_defs.find(&saved_powerup.kind) {
+				Some(kind) => {
+					let def = world.powerup_defs.get(kind).unwrap().clone();
+					let mut powerup = Box::new( powerups::DefinedPowerup::new(kind, def, saved_powerup.x, saved_powerup.y) ) as Box<powerups::Powerup>;
+					if powerup.is_debuff() != saved_powerup.debuff {
+						powerup.toggle_debuff();
+					}
+					world.powerups.push(powerup);
+				},
+				None => { println!("saved game has an unknown powerup `{}`, skipping it", saved_powerup.kind); }
+			}
+		}
+		for saved_trap in saved.traps.iter() {
+			match traps::find(&saved_trap.kind) {
+				Some(kind) => {
+					let mut trap = Box::new( traps::DefinedTrap::new(kind, saved_trap.x, saved_trap.y) ) as Box<traps::Trap>;
+					trap.restore_timers(saved_trap.timers);
+					world.traps.push(trap);
+				},
+				None => { println!("saved game has an unknown trap `{}`, skipping it", saved_trap.kind); }
+			}
+		}
+
+		world.index_entities();
+		world.snap_camera();
+
+		Ok(world)
+	}
+
+	/// Everything `resume` needs to pick this game back up later. Zombies
+	/// falling & powerups or traps still showing their animation are left out.
+	pub fn snapshot(&self) -> save::SaveGame {
+		// actors report their center, but are built from their corner
+		let corner = |center: units::Game| center - units::HalfTile(1);
+		let (backdrop, kinds, grid) = self.map.to_grid();
+
+		save::SaveGame {
+			seed:           self.seed,
+			difficulty:     self.difficulty,
+			level:          self.level,
+			score:          self.score,
+			timer:          self.timer,
+			updates:        self.updates,
+			freeze_counter: self.freeze_counter,
+
+			player:         self.player.state(),
+			vehicle_type:   self.vehicle.get_type(),
+			vehicle_x:      self.vehicle.get_map_x(),
+			vehicle_y:      self.vehicle.get_map_y(),
+			vehicle_parts:  self.vehicle.parts(),
+			collected:      self.coll_parts.iter().map(|part| part.part_type()).collect(),
+			parts:          self.parts.iter().map(|part| save::SavedPart {
+				part_type: part.part_type(),
+				x:         corner(part.get_map_x()),
+				y:         corner(part.get_map_y())
+			}).collect(),
+			zombies:        self.enemies.iter().map(|enemy| {
+				let (state, chasing) = enemy.state();
+				save::SavedZombie {
+					kind:    self.archetypes.get(enemy.zombie_type() as u32).unwrap().name.clone(),
+					state:   state,
+					chasing: chasing
+				}
+			}).collect(),
+			powerups:       self.powerups.iter().map(|powerup| save::SavedPowerup {
+				kind:   self.powerup_defs.get(powerup.get_type() as u32).unwrap().name.clone(),
+				x:      corner(powerup.get_map_x()),
+				y:      corner(powerup.get_map_y()),
+				debuff: powerup.is_debuff()
+			}).collect(),
+			traps:          self.traps.iter().map(|trap| save::SavedTrap {
+				kind:   trap.rules().name.to_string(),
+				x:      corner(trap.get_map_x()),
+				y:      corner(trap.get_map_y()),
+				timers: trap.timers()
+			}).collect(),
+
+			backdrop:       backdrop,
+			kinds:          kinds,
+			grid:           grid
+		}
+	}
+
+	/// Makes `effect` available to powerups as `name`, replacing any
+	/// effect already registered under that name.
+	pub fn register_effect(&mut self, name: &str, effect: Effect) {
+		self.effects.insert(name.to_string(), effect);
+	}
+
+	/// Switches how the camera follows the player, for this & later levels.
+	pub fn set_camera_mode(&mut self, mode: camera::CameraMode) {
+		self.camera_mode = mode;
+		self.map.set_camera_mode(mode);
+		self.snap_camera();
+	}
+
+	/// Stocks levels for `difficulty` from the next one on; the level being
+	/// played is left as it is.
+	pub fn set_difficulty(&mut self, difficulty: settings::Difficulty) {
+		self.difficulty = difficulty;
+	}
+
+	/// Points the camera straight at the player.
+	fn snap_camera(&mut self) {
+		self.map.snap_camera(self.player.character.map_center_x(), self.player.character.map_center_y());
+	}
+
+	/// Fills the level w/ the zombies, powerups, traps & vehicle parts its map
+	/// asks for. Whatever the map has no spawn points for is placed at random.
+	fn populate(&mut self) {
+		let spawns = self.map.spawns.clone();
+		if spawns.zombies.len() > 0 {
+			for &(ref name, (col, row)) in spawns.zombies.iter() {
+				match self.archetypes.find(name) {
+					Some(kind) => { self.spawn_zombie(Some(kind), Some((col.to_game(), row.to_game()))); },
+					None => { println!("level {} has an unknown zombie `{}`, skipping it", self.level, name); }
+				}
+			}
+		} else {
+			let (min_zombies, max_zombies) = self.horde_range();
+			let number_of_zombies = self.rng.gen_range(min_zombies, max_zombies);
+			for _ in 0.. number_of_zombies {
+			  	self.spawn_zombie(None, None);
+			}
+		}
+		if spawns.powerups.len() > 0 {
+			for &(ref name, (col, row)) in spawns.powerups.iter() {
+				match self.powerup_defs.find(name) {
+					Some(kind) => { self.spawn_powerup(Some(kind), Some((col.to_game(), row.to_game()))); },
+					None => { println!("level {} has an unknown powerup `{}`, skipping it", self.level, name); }
+				}
+			}
+		} else {
+			let number_of_powerups = self.rng.gen_range(0u32, self.difficulty.max_powerups());
+			for _ in 0.. number_of_powerups {
+				self.spawn_powerup(None, None);
+			}
+		}
+		if spawns.traps.len() > 0 {
+			for &(kind, (col, row)) in spawns.traps.iter() {
+				self.spawn_trap(Some(kind), Some((col.to_game(), row.to_game())));
+			}
+		} else {
+			let number_of_traps = self.rng.gen_range(0u32, MAX_TRAPS);
+			for _ in 0.. number_of_traps {
+				self.spawn_trap(None, None);
+			}
+		}
+		for i in 0.. LEVEL_PARTS {
+			let location = spawns.parts.get(i as usize).map(|&(col, row)| (col.to_game(), row.to_game()));
+			self.spawn_part(i, location);
+		}
+		self.index_entities();
+	}
+
+	/// The fewest & (one past) the most zombies a random level is given:
+	/// the difficulty's range, made bigger by `HORDE_GROWTH` for each level
+	/// past the first & in proportion to how much bigger than `LEVEL_WIDTH`
+	/// by `LEVEL_HEIGHT` the map is.
+	fn horde_range(&self) -> (u32, u32) {
+		let (min_zombies, max_zombies) = self.difficulty.zombie_range();
+		let (units::Tile(cols), units::Tile(rows)) = (self.map.width(), self.map.height());
+		let (units::Tile(level_cols), units::Tile(level_rows)) = (game::LEVEL_WIDTH, game::LEVEL_HEIGHT);
+		let area = ((cols * rows) as f64 / (level_cols * level_rows) as f64).max(1.0);
+		let scale = area * (1.0 + HORDE_GROWTH * cmp::max(self.level - 1, 0) as f64);
+
+		let min_zombies = (min_zombies as f64 * scale) as u32;
+		(min_zombies, cmp::max((max_zombies as f64 * scale) as u32, min_zombies + 1))
+	}
+
+	/// Files every zombie, powerup, trap & part under the tiles it covers,
+	/// once a level is set up. After that the `spawn_*` & `remove_*`
+	/// methods keep the grids up to date, & `index_enemies()` refiles the
+	/// zombies once they've moved.
+	pub fn index_entities(&mut self) {
+		self.index_enemies();
+		self.powerup_grid.clear();
+		for (index, powerup) in self.powerups.iter().enumerate() {
+			self.powerup_grid.insert(index, &powerup.damage_rectangle());
+		}
+		self.trap_grid.clear();
+		for (index, trap) in self.traps.iter().enumerate() {
+			self.trap_grid.insert(index, &trap.damage_rectangle());
+		}
+		self.part_grid.clear();
+		for (index, part) in self.parts.iter().enumerate() {
+			self.part_grid.insert(index, &part.damage_rectangle());
+		}
+	}
+
+	/// Refiles every zombie under the tiles it covers, after they've moved
+	/// or their `Vec` has been reordered.
+	pub fn index_enemies(&mut self) {
+		self.enemy_grid.clear();
+		for (index, enemy) in self.enemies.iter().enumerate() {
+			self.enemy_grid.insert(index, &enemy.damage_rectangle());
+		}
+	}
+
+	/// Takes the zombie at `index` off the level; the last one takes its place.
+	fn remove_enemy(&mut self, index: usize) -> Box<enemies::Zombie> {
+		self.enemy_grid.remove(index);
+		self.enemies.swap_remove(index)
+	}
+
+	/// Takes the powerup at `index` off the level; the last one takes its place.
+	fn remove_powerup(&mut self, index: usize) -> Box<powerups::Powerup> {
+		self.powerup_grid.remove(index);
+		self.powerups.swap_remove(index)
+	}
+
+	/// Takes the trap at `index` off the level; the last one takes its place.
+	fn remove_trap(&mut self, index: usize) -> Box<traps::Trap> {
+		self.trap_grid.remove(index);
+		self.traps.swap_remove(index)
+	}
+
+	/// Takes the vehicle part at `index` off the level; the last one takes its place.
+	fn remove_part(&mut self, index: usize) -> Box<vehicle::Part> {
+		self.part_grid.remove(index);
+		self.parts.swap_remove(index)
+	}
+
+	/// Indices of the zombies in view of the camera, in drawing order.
+	pub fn enemies_on_screen(&self) -> Vec<usize> {
+		self.enemy_grid.query(&self.map.view_rectangle()).into_iter().filter(|&index| {
+			let enemy = &self.enemies[index];
+			self.map.on_screen(enemy.get_map_x(), enemy.get_map_y())
+		}).collect()
+	}
+
+	/// A random tile inside the level's border.
+	fn random_location(&mut self) -> (units::Game, units::Game) {
+		let (units::Tile(cols), units::Tile(rows)) = (self.map.width(), self.map.height());
+		let x = units::Tile(self.rng.gen_range(1u32, cmp::max(2, cols.saturating_sub(2)))).to_game();
+		let y = units::Tile(self.rng.gen_range(1u32, cmp::max(2, rows.saturating_sub(2)))).to_game();
+		(x, y)
+	}
+
+	/// Where on the level random vehicle parts may be placed:
+	/// `(min, max)` tiles, where `min` keeps parts out of the corner the
+	/// vehicle starts in.
+	fn part_range(&self, size: units::Tile) -> (u32, u32) {
+		let units::Tile(size) = size;
+		let min = cmp::max(1, size / 3);
+		let max = cmp::max(min + 1, size.saturating_sub(5));
+		(min, max)
+	}
+
+	/// Spawns a zombie of `kind` at `location`. W/o a kind one is picked by
+	/// the weights in `ZOMBIE_CONFIG`, w/o a location it is placed at random.
+	/// Zombies which land in a wall are nudged (or re-rolled) until they don't.
+	pub fn spawn_zombie(&mut self, kind: Option<u32>, location: Option<(units::Game, units::Game)>) {
+		let kind = match kind {
+			Some(kind) => kind,
+			None       => self.archetypes.pick(&mut self.rng)
+		};
+		let archetype = match self.archetypes.get(kind) {
+			Some(archetype) => archetype.clone(),
+			None            => { println!("there is no zombie of kind {}", kind); return; }
+		};
+		let (x, y) = match location {
+			Some(location) => location,
+			None           => self.random_location()
+		};
+		let zombie = Box::new( enemies::ArchetypeZombie::new(kind, archetype, x, y) ) as Box<enemies::Zombie>;
+		let colliding_tiles = self.map.get_colliding_tiles(&zombie.damage_rectangle());
+		for tile in colliding_tiles.iter() {
+			if tile.tile_type == map::TileType::Wall {
+				let new_location = location.map(|(old_x, old_y)| (old_x + units::Game(1.0), old_y + units::Game(1.0)));
+				self.spawn_zombie(Some(kind), new_location);
+				return;
+			}
+		}
+
+		self.enemy_grid.insert(self.enemies.len(), &zombie.damage_rectangle());
+		self.enemies.push(zombie);
+	}
+
+	/// Spawns a powerup of `kind` at `location`. W/o a kind one is picked by
+	/// the weights in `POWERUP_CONFIG`, w/o a location it is placed at random.
+	pub fn spawn_powerup(&mut self, kind: Option<u32>, location: Option<(units::Game, units::Game)>) {
+		let kind = match kind {
+			Some(kind) => kind,
+			None       => self.powerup_defs.pick(&mut self.rng)
+		};
+		let def = match self.powerup_defs.get(kind) {
+			Some(def) => def.clone(),
+			None      => { println!("there is no powerup of kind {}", kind); return; }
+		};
+		let (x, y) = match location {
+			Some(location) => location,
+			None           => self.random_location()
+		};
+		let powerup = Box::new( powerups::DefinedPowerup::new(kind, def, x, y) ) as Box<powerups::Powerup>;
+		let colliding_tiles = self.map.get_colliding_tiles(&powerup.damage_rectangle());
+		for tile in colliding_tiles.iter() {
+			if tile.tile_type == map::TileType::Wall {
+				let new_location = location.map(|(old_x, old_y)| (old_x + units::Game(1.0), old_y + units::Game(1.0)));
+				self.spawn_powerup(Some(kind), new_location);
+				return;
+			}
+		}
+
+		self.powerup_grid.insert(self.powerups.len(), &powerup.damage_rectangle());
+		self.powerups.push(powerup);
+	}
+
+	/// Places a trap of `kind` at `location`. W/o a kind one is picked at
+	/// random, w/o a location it is placed at random, out of the player's
+	/// way if the level has room.
+	pub fn spawn_trap(&mut self, kind: Option<u32>, location: Option<(units::Game, units::Game)>) {
+		let kind = match kind {
+			Some(kind) => kind,
+			None       => self.rng.gen_range(1u32, traps::KINDS.len() as u32 + 1)
+		};
+		if traps::rules(kind).is_none() {
+			println!("there is no trap of kind {}", kind);
+			return;
+		}
+		let (x, y) = match location {
+			Some(location) => location,
+			None           => {
+				let mut location = self.random_location();
+				for _ in 0.. 10 {
+					let (x, y) = location;
+					if self.player.character.distance(x, y) >= TRAP_CLEARANCE {
+						break;
+					}
+					location = self.random_location();
+				}
+				location
+			}
+		};
+		let trap = Box::new( traps::DefinedTrap::new(kind, x, y) ) as Box<traps::Trap>;
+		let colliding_tiles = self.map.get_colliding_tiles(&trap.damage_rectangle());
+		for tile in colliding_tiles.iter() {
+			if tile.tile_type == map::TileType::Wall {
+				let new_location = location.map(|(old_x, old_y)| (old_x + units::Game(1.0), old_y + units::Game(1.0)));
+				self.spawn_trap(Some(kind), new_location);
+				return;
+			}
+		}
+
+		self.trap_grid.insert(self.traps.len(), &trap.damage_rectangle());
+		self.traps.push(trap);
+	}
+
+	/// Drops the player's most recent trap kit on the tile they stand on.
+	/// Responds false if they have no kits or that tile is already trapped.
+	pub fn place_trap(&mut self) -> bool {
+		let col = self.player.character.map_center_x().to_tile();
+		let row = self.player.character.map_center_y().to_tile();
+		let (x, y) = (col.to_game(), row.to_game());
+		if self.traps.iter().any(|trap| trap.get_map_x().to_tile() == col && trap.get_map_y().to_tile() == row) {
+			return false;
+		}
+
+		match self.player.take_trap_kit() {
+			Some(kind) => {
+				let trap = Box::new( traps::DefinedTrap::placed(kind, x, y) ) as Box<traps::Trap>;
+				self.trap_grid.insert(self.traps.len(), &trap.damage_rectangle());
+				self.traps.push(trap);
+				true
+			},
+			None => { false }
+		}
+	}
+
+	/// Spawns part `kind` of the current vehicle at `location`, or at
+	/// random (away from the vehicle) if it has none.
+	pub fn spawn_part(&mut self, kind: u32, location: Option<(units::Game, units::Game)>) {
+		let (x, y) = match location {
+			Some(location) => location,
+			None => {
+				let (min_x, max_x) = self.part_range(self.map.width());
+				let (min_y, max_y) = self.part_range(self.map.height());
+				let mut x = self.rng.gen_range(0, max_x);
+				let mut y = self.rng.gen_range(0, max_y);
+				if x < min_x {
+					y = self.rng.gen_range(min_y, max_y);
+				} else if y < min_y {
+					x = self.rng.gen_range(min_x, max_x);
+				}
+				(units::Tile(x).to_game(), units::Tile(y).to_game())
+			}
+		};
+		let part = self.new_part(kind, x, y);
+		let colliding_tiles = self.map.get_colliding_tiles(&part.damage_rectangle());
+		for tile in colliding_tiles.iter() {
+			if tile.tile_type == map::TileType::Wall {
+				let new_location = location.map(|(old_x, old_y)| (old_x + units::Game(1.0), old_y + units::Game(1.0)));
+				self.spawn_part(kind, new_location);
+				return;
+			}
+		}
+		self.part_grid.insert(self.parts.len(), &part.damage_rectangle());
+		self.parts.push(part);
+	}
+
+	/// Builds part `kind` (`part_type() - 1`) of the current vehicle at (`x`, `y`).
+	fn new_part(&self, kind: u32, x: units::Game, y: units::Game) -> Box<vehicle::Part> {
+		match self.vehicle.get_type() {
+			1 => {
+				match kind {
+					0 => {
+						Box::new( heli::Prop::new(x, y) ) as Box<vehicle::Part>
+					},
+					1 => {
+						Box::new( heli::Windshield::new(x, y) ) as Box<vehicle::Part>
+					},
+					_ => {
+						Box::new( heli::Bar::new(x, y) ) as Box<vehicle::Part>
+					}
+				}
+			},
+			_ => {
+				match kind {
+					0 => {
+						Box::new( car::Tire::new(x, y) ) as Box<vehicle::Part>
+					},
+					1 => {
+						Box::new( car::Door::new(x, y) ) as Box<vehicle::Part>
+					},
+					_ => {
+						Box::new( car::Engine::new(x, y) ) as Box<vehicle::Part>
+					}
+				}
+			}
+		}
+	}
+
+	/// Hands the events which piled up since the last call to the caller.
+	pub fn drain_events(&mut self) -> Vec<Event> {
+		mem::replace(&mut self.events, Vec::new())
+	}
+
+	fn play_sound_effect(&mut self, name: &str) {
+		self.events.push(Event::Sound(name.to_string(), None));
+	}
+
+	fn play_sound_at(&mut self, name: &str, x: units::Game, y: units::Game) {
+		self.events.push(Event::Sound(name.to_string(), Some((x, y))));
+	}
+
+	/// Has the closest zombie which is off screen, but within
+	/// `GROAN_RANGE` of the player, let them know it's coming.
+	fn groan(&mut self) {
+		let range = GROAN_RANGE.to_game();
+		let (player_x, player_y) = (self.player.character.map_x, self.player.character.map_y);
+		let earshot = collisions::Rectangle {
+			x: player_x - range, y: player_y - range,
+			width: range + range, height: range + range
+		};
+		let units::Game(range) = range;
+		let mut closest: Option<(f64, units::Game, units::Game)> = None;
+		for i in self.enemy_grid.query(&earshot) {
+			let enemy = &self.enemies[i];
+			let (x, y) = (enemy.get_map_x(), enemy.get_map_y());
+			let distance = self.player.character.distance(x, y);
+			if distance > range || self.map.on_screen(x, y) {
+				continue;
+			}
+			match closest {
+				Some((nearest, _, _)) if nearest <= distance => {},
+				_ => { closest = Some((distance, x, y)); }
+			}
+		}
+
+		match closest {
+			Some((_, x, y)) => { self.play_sound_at("groan", x, y); },
+			None            => {}
+		}
+	}
+
+	pub fn is_game_over(&self) -> bool {
+		self.game_over
+	}
+
+	pub fn set_score_and_timer(&mut self) {
+		self.score = self.score + (self.level * 1000) + (self.player.get_health() as i32 * 1000) + self.timer;
+		self.timer = self.timer + LEVEL_2_TIME;
+	}
+
+	/// Starts over from the first level, drawing from a generator built from `seed`.
+	pub fn restart(&mut self, seed: u32) {
+		println!("Restarting game (seed: {})...", seed);
+		self.seed = seed;
+		self.rng = random::new_rng(seed);
+		self.level = 0;
+		self.new_level(true);
+		self.score = 0;
+		self.timer = self.difficulty.level_time();
+	}
+
+	#[allow(unused_variable)]
+	pub fn new_level(&mut self, restart: bool) {
+		println!("Starting new level...");
+		self.level = self.level + 1;
+
+		let enemies_vector: Vec<Box<enemies::Zombie>> = Vec::new();
+		let powerup_vector: Vec<Box<powerups::Powerup>> = Vec::new();
+		let traps_vector: Vec<Box<traps::Trap>> = Vec::new();
+		let killed_vector: Vec<Box<enemies::Zombie>> = Vec::new();
+		let activated_vector: Vec<Box<powerups::Powerup>> = Vec::new();
+		let tripped_vector: Vec<Box<traps::Trap>> = Vec::new();
+		let coll_part_vector: Vec<Box<vehicle::Part>> = Vec::new();
+		let part_vector: Vec<Box<vehicle::Part>> = Vec::new();
+
+		// create new level map
+		let new_map = map::Map::load_level(self.level, &mut self.rng);
+		self.map = new_map;
+		self.map.set_camera_mode(self.camera_mode);
+
+		let (player_x, player_y) = start_position(self.map.spawns.player, (PLAYER_STARTING_X, PLAYER_STARTING_Y));
+		let (vehicle_x, vehicle_y) = start_position(self.map.spawns.vehicle, (VEHICLE_STARTING_X, VEHICLE_STARTING_Y));
+		self.player = player::Player::new(player_x, player_y);
+
+		let vehicle_num = self.rng.gen_range(0, 2);
+		self.vehicle = match vehicle_num {
+			0 => {
+				Box::new( heli::Helicopter::new(vehicle_x, vehicle_y) ) as Box<vehicle::Vehicle>
+			},
+			_ => {
+				Box::new( car::Car::new(vehicle_x, vehicle_y) ) as Box<vehicle::Vehicle>
+			}
+		};
+
+		self.enemies = enemies_vector;
+		self.powerups = powerup_vector;
+		self.traps = traps_vector;
+		self.killed = killed_vector;
+		self.activated = activated_vector;
+		self.tripped = tripped_vector;
+		self.coll_parts = coll_part_vector;
+		self.parts = part_vector;
+		self.populate();
+		self.snap_camera();
+
+		self.updates = 0;
+		self.freeze_counter = 0;
+		self.noise = None;
+		self.completed_lvl = false;
+		self.game_over = false;
+		self.events.push(Event::LevelStarted);
+	}
+
+	/// Drops killed zombies, finished powerup animations & tripped traps
+	/// once their remaining frames have run out.
+	fn retire_effects(&mut self) {
+		let mut kill_list: Vec<Box<enemies::Zombie>> = Vec::new();
+		let mut active_list: Vec<Box<powerups::Powerup>> = Vec::new();
+		let mut tripped_list: Vec<Box<traps::Trap>> = Vec::new();
+		for activated in self.activated.drain(..) {
+			let mut mut_activated = activated;
+			if !mut_activated.is_finished() {
+				active_list.push(mut_activated);
+			}
+		}
+		for tripped in self.tripped.drain(..) {
+			let mut mut_tripped = tripped;
+			if !mut_tripped.is_finished() {
+				tripped_list.push(mut_tripped);
+			}
+		}
+		for killed in self.killed.drain(..) {
+			let mut mut_killed = killed;
+			if !mut_killed.is_killed() {
+				kill_list.push(mut_killed);
+			}
+		}
+		self.killed = kill_list;
+		self.activated = active_list;
+		self.tripped = tripped_list;
+	}
+
+	/// Passes the current time in milliseconds to our underlying actors.
+	pub fn update(&mut self, elapsed_time: units::Millis) {
+		self.retire_effects();
+		self.map.update(elapsed_time);
+		if self.freeze_counter == 0 {
+			for i in 0u32.. self.enemies.len() as u32 {
+				let enemy = self.enemies.get_mut(i as usize).unwrap();
+				let (player_x, player_y) = self.player.get_follow_coords();
+				// zombies in earshot of a noise go to see what made it
+				match self.noise {
+					Some(noise) if noise.heard_at(enemy.get_map_x(), enemy.get_map_y()) => {
+						enemy.investigate(noise.x, noise.y, &self.map);
+					},
+					_ => { enemy.set_acceleration(player_x, player_y, &self.map, &mut self.rng); }
+				}
+				enemy.update(elapsed_time, &self.map);
+			}
+		} else {
+			self.freeze_counter = self.freeze_counter - 1;
+		}
+		// only the zombies move, the rest are refiled as they come & go
+		self.index_enemies();
+		if self.updates % GROAN_EVERY == 0 {
+			self.groan();
+		}
+		self.noise = match self.noise {
+			Some(noise) if noise.updates > 1 => Some(traps::Noise { updates: noise.updates - 1, .. noise }),
+			_                                => None
+		};
+		self.player.update(elapsed_time, &self.map);
+		self.map.follow(self.player.character.map_center_x(), self.player.character.map_center_y(), elapsed_time);
+		for i in 0.. self.killed.len() { self.killed.get_mut(i).unwrap().update(elapsed_time, &self.map) }
+		for i in 0.. self.activated.len() { self.activated.get_mut(i).unwrap().update(elapsed_time, &self.map) }
+		for i in 0.. self.tripped.len() { self.tripped.get_mut(i).unwrap().update(elapsed_time, &self.map) }
+		for i in 0.. self.traps.len() { self.traps.get_mut(i).unwrap().update(elapsed_time, &self.map) }
+		for i in 0.. self.powerups.len() {
+			let powerup = self.powerups.get_mut(i);
+		    // change debuff status every 10 updates
+			if self.updates % 20 == 0 {
+				powerup.unwrap().toggle_debuff();
+			}
+		}
+		self.vehicle.update(elapsed_time);
+
+		let player_rectangle = self.player.character.damage_rectangle();
+
+		let mut collided_with_zombie = false;
+		if !self.player.is_immune() {
+			for i in self.enemy_grid.query(&player_rectangle) {
+				if self.enemies.get(i).unwrap().damage_rectangle().collides_with_player(&player_rectangle) {
+					if self.player.has_bat() || self.player.is_teleporting() {
+						let enemy = self.remove_enemy(i);
+						self.play_sound_effect("hit");
+						let mut mut_enemy = enemy;
+						mut_enemy.kill_zombie();
+						self.killed.push(mut_enemy);
+						self.player.take_bat();
+						self.timer = self.timer + 100;
+					}
+				 	else {
+				 		collided_with_zombie = true;
+				 	}
+				 	break;
+				}
+			}
+		}
+
+		// Collect part
+		if self.coll_parts.len() == 0 {
+			for i in self.part_grid.query(&player_rectangle) {
+				if self.parts.get(i).unwrap().damage_rectangle().collides_with(&player_rectangle) {
+					let part = self.remove_part(i);
+					self.play_sound_effect("goal");
+					self.coll_parts.push(part);
+					break;
+				}
+			}
+		}
+
+		// Apply parts to vehicle
+		if self.vehicle.damage_rectangle().collides_with(&player_rectangle) {
+			match self.coll_parts.pop() {
+				Some(part) => { self.vehicle.add_part(part.part_type()); },
+				None       => {}
+			};
+		}
+
+		// Apply powerup
+		let hit_powerup = self.powerup_grid.query(&player_rectangle).into_iter().find(|&i| {
+			self.powerups.get(i).unwrap().damage_rectangle().collides_with(&player_rectangle)
+		});
+		match hit_powerup {
+			Some(i) => { self.apply_powerup(i as u32); },
+			None => {}
+		}
+
+		// Spring traps, each by its own rules
+		let player_immune = self.player.is_immune();
+		let mut player_hit_trap = false;
+		let mut sprung: Vec<usize> = Vec::new();
+		let mut caught: Vec<usize> = Vec::new();
+		for counter in 0.. self.traps.len() {
+			let (trap_rectangle, trap_x, trap_y, rules, player_trigger) = {
+				let trap = self.traps.get(counter).unwrap();
+				if !trap.is_armed() {
+					continue;
+				}
+				(trap.damage_rectangle(), trap.get_map_x(), trap.get_map_y(), trap.rules(), trap.triggers_for_player())
+			};
+			let mut triggered = false;
+
+			// check if player hit trap
+			if player_trigger && trap_rectangle.collides_with_player(&player_rectangle) {
+				match rules.effect {
+					traps::Effect::Damage if player_immune => {},
+					traps::Effect::Damage => {
+						player_hit_trap = true;
+						triggered = true;
+					},
+					traps::Effect::Slow(factor) => {
+						self.player.character.slow_down(factor);
+						triggered = true;
+					},
+					traps::Effect::Noise(radius, updates) => {
+						self.noise = Some(traps::Noise { x: trap_x, y: trap_y, radius: radius, updates: updates });
+						triggered = true;
+					}
+				}
+			}
+
+			// check if zombies hit trap
+			if rules.zombie_trigger && (rules.catches_all || !triggered) {
+				let hit_zombies: Vec<usize> = self.enemy_grid.query(&trap_rectangle).into_iter().filter(|&i| {
+					!caught.contains(&i) && self.enemies.get(i).unwrap().damage_rectangle().collides_with(&trap_rectangle)
+				}).collect();
+				for &i in hit_zombies.iter() {
+					match rules.effect {
+						traps::Effect::Damage => { caught.push(i); },
+						traps::Effect::Slow(factor) => { self.enemies.get_mut(i).unwrap().slow_down(factor); },
+						traps::Effect::Noise(radius, updates) => {
+							self.noise = Some(traps::Noise { x: trap_x, y: trap_y, radius: radius, updates: updates });
+						}
+					}
+					triggered = true;
+					if !rules.catches_all {
+						break;
+					}
+				}
+			}
+
+			if triggered {
+				sprung.push(counter);
+			}
+		}
+
+		// walk backwards so removing a zombie or trap only moves one already dealt w/
+		caught.sort();
+		for &i in caught.iter().rev() {
+			let mut mut_enemy = self.remove_enemy(i);
+			mut_enemy.kill_zombie();
+			self.killed.push(mut_enemy);
+		}
+		for &counter in sprung.iter().rev() {
+			self.activate_trap(counter as u32);
+		}
+
+		if self.vehicle.is_built() {
+			self.set_score_and_timer();
+			self.completed_lvl = true;
+		}
+
+		// ran out of time
+		if self.timer == 0 {
+			self.game_over = true;
+		}
+
+		if collided_with_zombie || player_hit_trap {
+			self.play_sound_effect("hit");
+			match self.player.get_health() {
+				health if health > 1 => {
+					self.player.hit_player();
+					self.player.start_immunity();
+				},
+				_ => {
+					self.player.character.kill_character();
+					self.game_over = true;
+				}
+			}
+		}
+
+		// the first zombie of each spawner kind spawns another on its way,
+		// when that kind is due; most updates none is & no zombie is looked at
+		if self.updates != 0 {
+			let mut due = self.archetypes.spawning(self.updates as u32);
+			let mut spawned: Vec<(u32, (units::Game, units::Game))> = Vec::new();
+			for enemy in self.enemies.iter() {
+				if due.len() == 0 {
+					break;
+				}
+				let kind = enemy.zombie_type() as u32;
+				match due.iter().position(|&due_kind| due_kind == kind) {
+					Some(position) => {
+						due.remove(position);
+						spawned.push((kind, enemy.get_target()));
+					},
+					None => {}
+				}
+			}
+			for &(kind, location) in spawned.iter() {
+				self.spawn_zombie(Some(kind), Some(location));
+			}
+		}
+
+		// decrement timer
+		self.timer = self.timer - 1;
+		self.updates = self.updates + 1;
+	}
+
+	/// Keeps the zombies moving & drives the vehicle off while the
+	/// level completion cinematic is playing.
+	pub fn update_cinematic(&mut self, elapsed_time: units::Millis) {
+		self.map.update(elapsed_time);
+		for i in 0u32.. self.enemies.len() as u32 {
+			let enemy = self.enemies.get_mut(i as usize).unwrap();
+			let (player_x, player_y) = self.player.get_follow_coords();
+			enemy.set_acceleration(player_x, player_y, &self.map, &mut self.rng);
+			enemy.update(elapsed_time, &self.map);
+		}
+		self.vehicle.update(elapsed_time);
+		self.vehicle.update_for_cinematic();
+		self.index_enemies();
+	}
+
+	/// Runs the effect of the powerup at `index`, or of its debuff if that
+	/// is showing. Used powerups linger while their animation plays.
+	fn apply_powerup(&mut self, index: u32) {
+		let mut powerup = self.remove_powerup(index as usize);
+		let def = match self.powerup_defs.get(powerup.get_type() as u32) {
+			Some(def) => def.clone(),
+			None      => { return; }
+		};
+		let variant = match def.debuff {
+			Some(ref debuff) if powerup.is_debuff() => debuff,
+			_                                       => &def.buff
+		};
+
+		println!("{}", variant.message);
+		let effect = match self.effects.get(&variant.effect) {
+			Some(effect) => *effect,
+			None => { println!("powerup `{}` has an unknown effect `{}`", def.name, variant.effect); return; }
+		};
+		if effect(self, &variant.params) {
+			self.play_sound_effect(&variant.sound);
+			if !powerup.is_debuff() && (def.linger > 0 || def.tracer) {
+				powerup.set_timer();
+				self.activated.push(powerup);
+			}
+		}
+	}
+
+	/// Sets off the trap at `index`: traps which are used up linger while
+	/// they show as sprung, the rest stay put until they re-arm.
+	fn activate_trap(&mut self, index: u32) {
+		let (rules, trap_x, trap_y) = {
+			let trap = self.traps.get(index as usize).unwrap();
+			(trap.rules(), trap.get_map_x(), trap.get_map_y())
+		};
+		match rules.message {
+			Some(message) => { println!("{}", message); },
+			None          => {}
+		}
+		match rules.sound {
+			Some(sound) => { self.play_sound_at(sound, trap_x, trap_y); },
+			None        => {}
+		}
+
+		if rules.consumed {
+			let mut mut_trap = self.remove_trap(index as usize);
+			mut_trap.set_timer();
+			self.tripped.push(mut_trap);
+		} else {
+			self.traps.get_mut(index as usize).unwrap().spring();
+		}
+	}
+}
+
+/// Where a map's optional spawn point puts an actor, falling back to `default`.
+fn start_position(spawn: Option<(units::Tile, units::Tile)>,
+                  default: (units::Tile, units::Tile)) -> (units::Game, units::Game) {
+	let (col, row) = match spawn {
+		Some(spawn) => spawn,
+		None        => default
+	};
+	(col.to_game(), row.to_game())
+}
+
+/// Lets the player kill the next zombie they touch w/o getting hurt.
+#[allow(unused_variable)]
+fn bat_effect(world: &mut World, params: &powerups::Params) -> bool {
+	world.player.give_bat();
+	true
+}
+
+/// Kills a random zombie. The timer gains `bonus` either way.
+fn kill_zombie_effect(world: &mut World, params: &powerups::Params) -> bool {
+	world.timer = world.timer + params.number("bonus", 0.0) as i32;
+	let length = world.enemies.len();
+	if length == 0 {
+		return false;
+	}
+
+	let index = world.rng.gen_range(0u32, length as u32) as usize;
+	let mut killed = world.remove_enemy(index);
+	world.play_sound_at("hit", killed.get_map_x(), killed.get_map_y());
+	killed.kill_zombie();
+	world.killed.push(killed);
+	true
+}
+
+/// Kills every zombie within `radius` of the player, adding
+/// `bonus_per_kill` to the timer for each zombie still falling.
+fn wipe_out_effect(world: &mut World, params: &powerups::Params) -> bool {
+	let radius = params.number("radius", 0.0);
+	let mut new_enemies: Vec<Box<enemies::Zombie>> = Vec::new();
+	for _ in 0.. world.enemies.len() {
+		let enemy = world.enemies.pop();
+		match enemy {
+			Some(enemy) => {
+				if world.player.character.distance( enemy.get_map_x(), enemy.get_map_y() ) > radius {
+				 	new_enemies.push(enemy);
+				} else {
+				 	let mut mut_enemy = enemy;
+				 	mut_enemy.kill_zombie();
+				 	world.killed.push(mut_enemy);
+				}
+			},
+			None => {}
+		}
+	}
+	world.timer = world.timer + (world.killed.len() as i32 * params.number("bonus_per_kill", 0.0) as i32);
+	world.enemies = new_enemies;
+	world.index_enemies();
+	true
+}
+
+/// Stops every zombie for `updates`.
+fn freeze_effect(world: &mut World, params: &powerups::Params) -> bool {
+	world.freeze_counter = params.number("updates", 0.0) as i32;
+	true
+}
+
+/// Moves the player to the vehicle, `below` tiles under it.
+fn teleport_effect(world: &mut World, params: &powerups::Params) -> bool {
+	world.player.character.map_x = world.vehicle.get_map_x();
+	world.player.character.map_y = world.vehicle.get_map_y() + units::Tile(params.number("below", 0.0) as u32).to_game();
+	world.player.start_teleport_timer();
+	true
+}
+
+/// Kills every zombie on screen, adding `bonus_per_kill` to the timer
+/// for each zombie still falling.
+fn nuke_effect(world: &mut World, params: &powerups::Params) -> bool {
+	// walk backwards so removing a zombie only moves one already dealt w/
+	let on_screen = world.enemies_on_screen();
+	for &index in on_screen.iter().rev() {
+		let mut mut_enemy = world.remove_enemy(index);
+		mut_enemy.kill_zombie();
+		world.killed.push(mut_enemy);
+	}
+	world.timer = world.timer + (world.killed.len() as i32 * params.number("bonus_per_kill", 0.0) as i32);
+	true
+}
+
+/// Turns each zombie into a `zombie` w/ a `chance` in 100.
+fn convert_effect(world: &mut World, params: &powerups::Params) -> bool {
+	let kind = match world.archetypes.find(params.text("zombie", "")) {
+		Some(kind) => kind,
+		None       => { return false; }
+	};
+	let chance = params.number("chance", 100.0) as u32;
+	let mut new_enemies: Vec<Box<enemies::Zombie>> = Vec::new();
+	for _ in 0.. world.enemies.len() {
+		let enemy = world.enemies.pop();
+		match enemy {
+			Some(enemy) => {
+				if world.rng.gen_range(0u32, 100u32) < chance {
+					let converted = Box::new( enemies::ArchetypeZombie::new(
+						kind,
+						world.archetypes.get(kind).unwrap().clone(),
+						enemy.get_map_x(),
+						enemy.get_map_y()
+					) ) as Box<enemies::Zombie>;
+				 	new_enemies.push(converted);
+				} else {
+				 	new_enemies.push(enemy);
+				}
+			},
+			None => {}
+		}
+	}
+	world.enemies = new_enemies;
+	world.index_enemies();
+	true
+}
+
+/// Gives the player `count` kits for the trap called `trap`, to put
+/// down w/ `World::place_trap`.
+fn trap_kit_effect(world: &mut World, params: &powerups::Params) -> bool {
+	let kind = match traps::find(params.text("trap", "")) {
+		Some(kind) => kind,
+		None       => { return false; }
+	};
+	for _ in 0.. params.number("count", 1.0) as u32 {
+		world.player.give_trap_kit(kind);
+	}
+	true
+}
+
+#[cfg(test)]
+mod tests {
+	use super::{Event, World};
+	use game::units;
+
+	static FRAME: units::Millis = units::Millis(16);
+
+	/// A world w/ nothing on the level but the player & the vehicle.
+	fn empty_world() -> World {
+		let mut world = World::new(7).unwrap();
+		world.enemies.clear();
+		world.powerups.clear();
+		world.traps.clear();
+		world.parts.clear();
+		world.index_entities();
+		world.drain_events();
+		world
+	}
+
+	#[test]
+	fn same_seed_same_game() {
+		let mut first = World::new(1234).unwrap();
+		let mut second = World::new(1234).unwrap();
+		for _ in 0.. 300 {
+			first.update(FRAME);
+			second.update(FRAME);
+		}
+
+		let position = |world: &World| (world.player.character.map_x, world.player.character.map_y);
+		assert!(position(&first) == position(&second));
+		assert_eq!(first.score, second.score);
+		assert_eq!(first.timer, second.timer);
+		assert_eq!(first.enemies.len(), second.enemies.len());
+		assert_eq!(first.powerups.len(), second.powerups.len());
+		for (a, b) in first.enemies.iter().zip(second.enemies.iter()) {
+			assert!(a.get_map_x() == b.get_map_x() && a.get_map_y() == b.get_map_y());
+		}
+	}
+
+	#[test]
+	fn zombie_hurts_the_player() {
+		let mut world = empty_world();
+		let kind = world.archetypes.find("slow").unwrap();
+		let (x, y) = (world.player.character.map_x, world.player.character.map_y);
+		world.spawn_zombie(Some(kind), Some((x, y)));
+		let health = world.player.get_health();
+
+		world.update(FRAME);
+
+		assert_eq!(world.player.get_health(), health - 1);
+		assert!(world.player.is_immune());
+		assert!(world.drain_events().iter().any(|event| match *event {
+			Event::Sound(ref name, None) => { name == "hit" },
+			_ => { false }
+		}));
+	}
+
+	#[test]
+	fn powerup_is_picked_up() {
+		let mut world = empty_world();
+		let kind = world.powerup_defs.find("bat").unwrap();
+		let (x, y) = (world.player.character.map_x, world.player.character.map_y);
+		world.spawn_powerup(Some(kind), Some((x, y)));
+		// powerups flip to their debuff on every 20th update
+		world.updates = 1;
+
+		world.update(FRAME);
+
+		assert_eq!(world.powerups.len(), 0);
+		assert!(world.player.has_bat());
+	}
+
+	#[test]
+	fn new_level_is_announced() {
+		let mut world = empty_world();
+		world.new_level(false);
+
+		assert_eq!(world.level, 2);
+		assert!(world.drain_events().iter().any(|event| match *event {
+			Event::LevelStarted => { true },
+			_ => { false }
+		}));
+	}
+}
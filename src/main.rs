@@ -5,14 +5,220 @@ extern crate sdl2_mixer;
 extern crate sdl2_ttf;
 extern crate rand;
 extern crate time;
+extern crate xml;
+extern crate rustc_serialize;
+
+use std::env;
+use std::io::{self, Write};
+use std::process;
+use time::PreciseTime;
 
 pub mod game;
 
+// a simulated frame when running w/o a display
+static HEADLESS_FRAME_TIME: game::units::Millis = game::units::Millis(1000 / 60);
+
+// how many frames `--benchmark` times each broadphase for
+static BENCHMARK_FRAMES: u32 = 60;
+
+static USAGE: &'static str = "usage: just_run [--seed <n>] [--headless <frames>] [--record <file>] \
+[--replay <file>] [--replay-speed <n>] [--camera <follow|page>] [--benchmark <zombies>] [--pack <name|dir>]";
+
 pub fn main() {
+	let args: Vec<String> = env::args().collect();
+
+	// `--seed <n>` replays every run from the same seed
+	// `--headless <frames>` steps the world w/o opening a window
+	// `--record <file>` records every key event to a replay file
+	// `--replay <file>` plays a replay file back instead of the keyboard
+	// `--replay-speed <n>` plays back `n` times faster (0 = unthrottled)
+	// `--camera <follow|page>` picks how the view follows the player
+	// `--benchmark <zombies>` times collision checks for a horde w/o a display
+	// `--pack <name|dir>` lays an asset pack over those in the settings (repeatable)
+	let mut options = ::game::Options::new();
+	let mut packs = ::game::settings::Settings::load().packs;
+	let mut headless_frames: Option<u32> = None;
+	let mut benchmark_zombies: Option<u32> = None;
+	let mut i = 1;
+	while i < args.len() {
+		match &args[i][..] {
+			"--seed" if i + 1 < args.len() => {
+				options.seed = match args[i + 1].parse::<u32>() {
+					Ok(seed) => { Some(seed) },
+					Err(msg) => { usage_error(&format!("invalid seed {}: {}", args[i + 1], msg)) }
+				};
+				i = i + 1;
+			},
+			"--headless" if i + 1 < args.len() => {
+				headless_frames = match args[i + 1].parse::<u32>() {
+					Ok(frames) => { Some(frames) },
+					Err(msg) => { usage_error(&format!("invalid frame count {}: {}", args[i + 1], msg)) }
+				};
+				i = i + 1;
+			},
+			"--record" if i + 1 < args.len() => {
+				options.record_path = Some(args[i + 1].clone());
+				i = i + 1;
+			},
+			"--replay" if i + 1 < args.len() => {
+				options.replay_path = Some(args[i + 1].clone());
+				i = i + 1;
+			},
+			"--replay-speed" if i + 1 < args.len() => {
+				options.replay_speed = match args[i + 1].parse::<u32>() {
+					Ok(speed) => { speed },
+					Err(msg) => { usage_error(&format!("invalid replay speed {}: {}", args[i + 1], msg)) }
+				};
+				i = i + 1;
+			},
+			"--camera" if i + 1 < args.len() => {
+				options.camera_mode = match ::game::camera::CameraMode::from_name(&args[i + 1]) {
+					Some(mode) => { mode },
+					None => { usage_error(&format!("invalid camera mode: {}", args[i + 1])) }
+				};
+				i = i + 1;
+			},
+			"--benchmark" if i + 1 < args.len() => {
+				benchmark_zombies = match args[i + 1].parse::<u32>() {
+					Ok(zombies) => { Some(zombies) },
+					Err(msg) => { usage_error(&format!("invalid zombie count {}: {}", args[i + 1], msg)) }
+				};
+				i = i + 1;
+			},
+			"--pack" if i + 1 < args.len() => {
+				packs.push(args[i + 1].clone());
+				i = i + 1;
+			},
+			arg => { println!("ignoring unknown argument: {}", arg); }
+		}
+		i = i + 1;
+	}
+	::game::packs::activate(&packs);
+
+	match benchmark_zombies {
+		Some(zombies) => {
+			run_benchmark(zombies, options.seed);
+			return;
+		},
+		None => {}
+	}
+
+	match headless_frames {
+		Some(frames) => {
+			run_headless(frames, options.seed);
+			return;
+		},
+		None => {}
+	}
+
 	println!("initalizing sdl ...");
 	let sdl_context = sdl2::init().unwrap();
 	let _ttf_context = sdl2_ttf::init();
 
-	let mut story = ::game::Game::new(&sdl_context);
+	let mut story = match ::game::Game::new(&sdl_context, options) {
+		Ok(story) => { story },
+		Err(msg) => { fail(&msg) }
+	};
 	story.start();
 }
+
+/// Reports a malformed command line & exits.
+fn usage_error(msg: &str) -> ! {
+	let _ = writeln!(&mut io::stderr(), "{}\n{}", msg, USAGE);
+	process::exit(2);
+}
+
+/// Reports why the game couldn't start & exits.
+fn fail(msg: &str) -> ! {
+	let _ = writeln!(&mut io::stderr(), "{}", msg);
+	process::exit(1);
+}
+
+/// Runs the simulation for `frames` updates and reports how it went.
+fn run_headless(frames: u32, seed: Option<u32>) {
+	let seed = match seed {
+		Some(seed) => seed,
+		None       => ::game::random::random_seed()
+	};
+	println!("running headless w/ seed {}", seed);
+	let mut world = match ::game::World::new(seed) {
+		Ok(world) => { world },
+		Err(msg) => { fail(&msg) }
+	};
+	let mut games = 0;
+	for _ in 0.. frames {
+		world.update(HEADLESS_FRAME_TIME);
+		world.drain_events();
+		if world.completed_lvl {
+			println!("completed level {} (score: {})", world.level, world.score);
+			world.new_level(false);
+		}
+		if world.is_game_over() {
+			println!("game over on level {} (score: {})", world.level, world.score);
+			games = games + 1;
+			world.restart(seed);
+		}
+	}
+	println!("ran {} frames, {} game(s) over, level {}, score {}",
+	         frames, games, world.level, world.score);
+}
+
+/// Spawns a horde of `zombies` and times checking them against the player,
+/// the view, and every powerup, part & trap: first by scanning every zombie,
+/// then through the world's spatial hash, refiling the zombies every frame as
+/// an update does. Finishes w/ the cost of a whole update.
+fn run_benchmark(zombies: u32, seed: Option<u32>) {
+	let seed = match seed {
+		Some(seed) => seed,
+		None       => ::game::random::random_seed()
+	};
+	let mut world = match ::game::World::new(seed) {
+		Ok(world) => { world },
+		Err(msg) => { fail(&msg) }
+	};
+	for _ in 0.. zombies {
+		world.spawn_zombie(None, None);
+	}
+	world.index_entities();
+
+	let mut probes = vec![world.player.character.damage_rectangle(), world.map.view_rectangle()];
+	probes.extend(world.powerups.iter().map(|powerup| powerup.damage_rectangle()));
+	probes.extend(world.parts.iter().map(|part| part.damage_rectangle()));
+	probes.extend(world.traps.iter().map(|trap| trap.damage_rectangle()));
+	println!("benchmarking {} zombies against {} rectangles w/ seed {}", world.enemies.len(), probes.len(), seed);
+
+	let start_time = PreciseTime::now();
+	let mut linear_hits = 0;
+	for _ in 0.. BENCHMARK_FRAMES {
+		for probe in probes.iter() {
+			for enemy in world.enemies.iter() {
+				if enemy.damage_rectangle().collides_with(probe) { linear_hits = linear_hits + 1; }
+			}
+		}
+	}
+	let linear_time = start_time.to(PreciseTime::now());
+
+	let start_time = PreciseTime::now();
+	let mut hashed_hits = 0;
+	for _ in 0.. BENCHMARK_FRAMES {
+		world.index_enemies();
+		for probe in probes.iter() {
+			for i in world.enemy_grid.query(probe) {
+				if world.enemies[i].damage_rectangle().collides_with(probe) { hashed_hits = hashed_hits + 1; }
+			}
+		}
+	}
+	let hashed_time = start_time.to(PreciseTime::now());
+
+	let start_time = PreciseTime::now();
+	for _ in 0.. BENCHMARK_FRAMES {
+		world.update(HEADLESS_FRAME_TIME);
+		world.drain_events();
+	}
+	let update_time = start_time.to(PreciseTime::now());
+
+	let per_frame = |time: ::time::Duration| time.num_microseconds().unwrap_or(0) as f64 / BENCHMARK_FRAMES as f64 / 1000.0;
+	println!("linear scan:  {:.3} ms/frame ({} hits)", per_frame(linear_time), linear_hits);
+	println!("spatial hash: {:.3} ms/frame ({} hits, incl. rebuilding)", per_frame(hashed_time), hashed_hits);
+	println!("whole update: {:.3} ms/frame", per_frame(update_time));
+}